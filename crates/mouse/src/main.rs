@@ -7,7 +7,7 @@ use std::{
     path::PathBuf,
 };
 
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
 use dialoguer::{
     theme::{ColorfulTheme, Theme},
     Confirm, Error as DError, FuzzySelect, Input,
@@ -16,6 +16,7 @@ use dialoguer::{
 use crate::value_utils::get_value_from_stdin;
 use sourisdb::{
     client::{ClientError, SyncClient},
+    display_bytes_as_hex_array, is_valid_db_name,
     store::{Store, StoreSerError},
     values::ValueSerError,
 };
@@ -42,14 +43,40 @@ enum Commands {
     RemoveEntry,
     UpdateEntry,
     ExportToJSON {
-        json_location: PathBuf,
+        location: PathBuf,
         #[arg(short, long)]
         add_souris_types: bool,
+        #[arg(short, long, value_enum, default_value_t = ExportFormat::JsonPretty)]
+        format: ExportFormat,
     },
     ImportFromJSON {
         json_location: PathBuf,
     },
     RemoveDatabase,
+    ///Shows how many keys a database has, how big it is when serialised, and which types make up its values
+    Stats,
+    ///Prints the bytes that a database serialises to, for debugging the wire format
+    Dump {
+        ///Break the bytes down into the header, the huffman tree and the values
+        #[arg(short, long)]
+        pretty: bool,
+    },
+    ///Prints the database stored in a file (like the `.sdb` files that `sourisd` writes) rather than one on a server, and prints it again whenever the file changes
+    #[cfg(feature = "watch")]
+    Watch {
+        ///The database file to print and watch
+        file: PathBuf,
+    },
+}
+
+#[derive(ValueEnum, Debug, Copy, Clone, PartialEq, Eq)]
+enum ExportFormat {
+    ///Compact JSON, all on one line
+    Json,
+    ///Indented JSON
+    JsonPretty,
+    ///The raw bytes from serialising the store
+    Souris,
 }
 
 fn main() {
@@ -68,6 +95,8 @@ enum Error {
     Store(StoreSerError),
     NoDatabasesFound,
     Client(Box<ClientError>),
+    #[cfg(feature = "watch")]
+    Notify(notify::Error),
 }
 
 impl Display for Error {
@@ -80,6 +109,8 @@ impl Display for Error {
             Error::Store(e) => write!(f, "Error with store: {e}"),
             Error::NoDatabasesFound => write!(f, "No databases found"),
             Error::Client(e) => write!(f, "Error with souris client: {e}"),
+            #[cfg(feature = "watch")]
+            Error::Notify(e) => write!(f, "Error watching file: {e}"),
         }
     }
 }
@@ -114,6 +145,12 @@ impl From<ClientError> for Error {
         Self::Client(Box::new(value))
     }
 }
+#[cfg(feature = "watch")]
+impl From<notify::Error> for Error {
+    fn from(value: notify::Error) -> Self {
+        Self::Notify(value)
+    }
+}
 
 impl std::error::Error for Error {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
@@ -124,6 +161,8 @@ impl std::error::Error for Error {
             Error::Value(e) => Some(e),
             Error::Store(e) => Some(e),
             Error::Client(e) => Some(e),
+            #[cfg(feature = "watch")]
+            Error::Notify(e) => Some(e),
             Error::NoDatabasesFound => None,
         }
     }
@@ -131,6 +170,11 @@ impl std::error::Error for Error {
 
 #[allow(clippy::collapsible_if, clippy::too_many_lines)]
 fn fun_main(Arguments { path, command }: Arguments) -> Result<(), Error> {
+    #[cfg(feature = "watch")]
+    if let Commands::Watch { file } = &command {
+        return watch(file);
+    }
+
     let theme = ColorfulTheme::default();
     let client = SyncClient::new(path.clone(), 7687)?;
 
@@ -212,11 +256,11 @@ fn fun_main(Arguments { path, command }: Arguments) -> Result<(), Error> {
             }
         }
         Commands::RemoveEntry => {
-            let (db_name, store) = pick_db(&client, &theme)?;
+            let db_name = pick_db_name(false, &client, &theme)?;
 
             println!();
 
-            let mut keys = store.keys().collect::<Vec<_>>();
+            let mut keys = client.get_keys(&db_name)?;
 
             if keys.is_empty() {
                 println!("Database already empty.");
@@ -225,10 +269,9 @@ fn fun_main(Arguments { path, command }: Arguments) -> Result<(), Error> {
                     .with_prompt("Select key to be removed:")
                     .items(&keys)
                     .interact()?;
-                let key = keys.swap_remove(key).clone(); //idc if it gets swapped as we drop it next
+                let key = keys.swap_remove(key); //idc if it gets swapped as we drop it next
 
                 drop(keys);
-                drop(store);
 
                 if Confirm::with_theme(&theme)
                     .with_prompt("Confirm Removal?")
@@ -242,11 +285,11 @@ fn fun_main(Arguments { path, command }: Arguments) -> Result<(), Error> {
             }
         }
         Commands::UpdateEntry => {
-            let (db_name, store) = pick_db(&client, &theme)?;
+            let db_name = pick_db_name(false, &client, &theme)?;
 
             println!();
 
-            let mut keys = store.keys().collect::<Vec<_>>();
+            let mut keys = client.get_keys(&db_name)?;
 
             if keys.is_empty() {
                 println!("Database is empty.");
@@ -255,10 +298,9 @@ fn fun_main(Arguments { path, command }: Arguments) -> Result<(), Error> {
                     .with_prompt("Select key to be updated:")
                     .items(&keys)
                     .interact()?;
-                let key = keys.swap_remove(key).clone(); //idc if it gets swapped as we drop it next
+                let key = keys.swap_remove(key); //idc if it gets swapped as we drop it next
 
                 drop(keys);
-                drop(store);
 
                 let new_val = get_value_from_stdin("New Value: ", &theme)?;
 
@@ -275,30 +317,149 @@ fn fun_main(Arguments { path, command }: Arguments) -> Result<(), Error> {
             }
         }
         Commands::ExportToJSON {
-            json_location,
+            location,
             add_souris_types,
+            format,
         } => {
             let (name, store) = pick_db(&client, &theme)?;
-            println!("Received Database {name:?}, converting to JSON");
 
-            match store.to_json(add_souris_types) {
-                Some(json) => {
-                    let json = serde_json::to_string_pretty(&json)?;
+            let bytes = if format == ExportFormat::Souris {
+                println!("Received Database {name:?}, serialising");
+                store.ser()?
+            } else {
+                println!("Received Database {name:?}, converting to JSON");
 
-                    println!("Converted to JSON, writing to {json_location:?}");
+                let Some(json) = store.to_json(add_souris_types) else {
+                    eprintln!("Unable to convert to JSON - ensure there are no NaN/infinite floats or integers which cannot fit into the range from i64::MIN to u64::MAX");
+                    return Ok(());
+                };
 
-                    let mut file = File::create(json_location)?;
-                    file.write_all(json.as_bytes())?;
+                if format == ExportFormat::JsonPretty {
+                    serde_json::to_vec_pretty(&json)?
+                } else {
+                    serde_json::to_vec(&json)?
                 }
-                None => {
-                    eprintln!("Unable to convert to JSON - ensure there are no NaN/infinite floats or integers which cannot fit into the range from i64::MIN to u64::MAX");
+            };
+
+            println!("Converted, writing to {location:?}");
+
+            let mut file = File::create(location)?;
+            file.write_all(&bytes)?;
+        }
+        Commands::Stats => {
+            let db_name = pick_db_name(false, &client, &theme)?;
+            let stats = client.db_stats(&db_name)?;
+
+            println!("Database {db_name:?}:");
+            println!("Keys: {}", stats.key_count);
+            println!("Serialised size: {} bytes", stats.serialized_size);
+
+            let mut value_types = stats.value_types.into_iter().collect::<Vec<_>>();
+            value_types.sort_unstable_by(|(a_ty, a_count), (b_ty, b_count)| {
+                b_count
+                    .cmp(a_count)
+                    .then_with(|| format!("{a_ty:?}").cmp(&format!("{b_ty:?}")))
+            });
+            if !value_types.is_empty() {
+                println!("Value types:");
+                for (ty, count) in value_types {
+                    println!("  {ty:?}: {count}");
+                }
+            }
+        }
+        Commands::Dump { pretty } => {
+            let (name, store) = pick_db(&client, &theme)?;
+            let bytes = store.ser()?;
+
+            println!("Database {name:?} serialises to {} bytes:", bytes.len());
+
+            if pretty {
+                let layout = Store::layout(&bytes)?;
+
+                println!(
+                    "Header (0..{}): {}, format version {}",
+                    layout.header_len,
+                    display_bytes_as_hex_array(&bytes[..layout.header_len]),
+                    layout.version
+                );
+                println!(
+                    "Payload ({}..{}): compressed with {:?}, {} bytes once decompressed",
+                    layout.header_len,
+                    bytes.len(),
+                    layout.compression,
+                    layout.payload.len()
+                );
+                if let Some(range) = layout.dict {
+                    println!(
+                        "  Binary dictionary ({range:?}): {}",
+                        display_bytes_as_hex_array(&layout.payload[range.clone()])
+                    );
                 }
+                match layout.huffman {
+                    Some(range) => println!(
+                        "  Huffman tree ({range:?}): {}",
+                        display_bytes_as_hex_array(&layout.payload[range.clone()])
+                    ),
+                    None => println!("  No huffman tree"),
+                }
+                println!(
+                    "  Values ({:?}): {}",
+                    layout.values,
+                    display_bytes_as_hex_array(&layout.payload[layout.values.clone()])
+                );
+            } else {
+                println!("{}", display_bytes_as_hex_array(&bytes));
             }
         }
         Commands::RemoveDatabase => {
             let db_name = pick_db_name(false, &client, &theme)?;
-            client.remove_db(&db_name)?;
-            println!("Successfully removed database");
+            if client.remove_db(&db_name)? {
+                println!("Successfully removed database");
+            } else {
+                println!("Database had already been removed");
+            }
+        }
+        #[cfg(feature = "watch")]
+        Commands::Watch { .. } => {
+            unreachable!("watching doesn't need a client, so is handled first")
+        }
+    }
+
+    Ok(())
+}
+
+///Prints the database in `file`, and then prints it again every time the file is changed.
+///
+/// The directory containing the file is watched rather than the file itself, as `sourisd` replaces files by renaming a new file over the top of them which would otherwise end the watch.
+#[cfg(feature = "watch")]
+fn watch(file: &std::path::Path) -> Result<(), Error> {
+    use notify::{EventKind, RecursiveMode, Watcher};
+    use std::sync::mpsc::channel;
+
+    let path = file.canonicalize()?;
+    let print_store = || match std::fs::read(&path)
+        .map_err(Error::from)
+        .and_then(|bytes| Ok(Store::deser(&bytes)?))
+    {
+        Ok(store) => println!("{store}"),
+        //another program could be part-way through writing the file, so keep watching
+        Err(e) => eprintln!("Unable to read database: {e}"),
+    };
+
+    let (tx, rx) = channel();
+    let mut watcher = notify::recommended_watcher(tx)?;
+    watcher.watch(path.parent().unwrap_or(&path), RecursiveMode::NonRecursive)?;
+
+    print_store();
+    for event in rx {
+        let event = event?;
+        if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_))
+            && event
+                .paths
+                .iter()
+                .any(|p| p.file_name() == path.file_name())
+        {
+            print_store();
         }
     }
 
@@ -325,11 +486,11 @@ fn pick_db_name(
             .interact()?
         {
             return Ok(loop {
-                let trial = Input::with_theme(theme)
+                let trial: String = Input::with_theme(theme)
                     .with_prompt("Database Name: ")
                     .interact()?;
-                if &trial == "meta" {
-                    println!("That name is reserved.");
+                if let Err(reason) = is_valid_db_name(&trial) {
+                    println!("Invalid database name: {reason}.");
                     continue;
                 }
 
@@ -1,12 +1,11 @@
-//! `value_utils` currently only provides one function - `get_value_from_stdin` which allows you to easily get a value from `stdin` using `dialoguer`.
+//! `value_utils` currently only provides one public function - `get_value_from_stdin` which allows you to easily get a value from `stdin` using `dialoguer`.
 
 use crate::Error;
 use dialoguer::{theme::Theme, Confirm, FuzzySelect, Input};
 use serde_json::Value as SJValue;
 use sourisdb::{
-    chrono::{Local, NaiveDate, NaiveDateTime, NaiveTime},
+    chrono::{Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc},
     chrono_tz,
-    hashbrown::HashMap,
     types::{binary::BinaryData, imaginary::Imaginary},
     values::{Value, ValueTy},
 };
@@ -14,7 +13,6 @@ use std::{
     fmt::{Display, Formatter},
     format,
     fs::File,
-    io::Read,
     path::PathBuf,
     println,
     str::FromStr,
@@ -47,15 +45,21 @@ pub fn get_value_from_stdin(prompt: impl Display, theme: &dyn Theme) -> Result<V
         ValueTy::Integer,
         ValueTy::Imaginary,
         ValueTy::Timestamp,
+        ValueTy::ZonedTimestamp,
         ValueTy::JSON,
         ValueTy::Null,
         ValueTy::DoubleFloat,
         ValueTy::Array,
         ValueTy::Map,
+        ValueTy::OrderedMap,
         ValueTy::Timezone,
         ValueTy::Ipv4Addr,
         ValueTy::Ipv6Addr,
         ValueTy::SingleFloat,
+        ValueTy::Uuid,
+        ValueTy::Duration,
+        ValueTy::Regex,
+        ValueTy::Tagged,
     ];
     let selection = FuzzySelect::with_theme(theme)
         .with_prompt("Type: ")
@@ -122,17 +126,8 @@ pub fn get_value_from_stdin(prompt: impl Display, theme: &dyn Theme) -> Result<V
                 }
             };
 
-            let mut output = vec![];
             let mut file = File::open(pb)?;
-            let mut tmp = [0_u8; 128];
-            loop {
-                match file.read(&mut tmp)? {
-                    0 => break,
-                    n => output.extend_from_slice(&tmp[..n]),
-                }
-            }
-
-            Value::Binary(BinaryData(output))
+            Value::Binary(BinaryData::from_reader(&mut file)?)
         }
         ValueTy::Boolean => {
             let b = FuzzySelect::with_theme(theme)
@@ -217,6 +212,31 @@ pub fn get_value_from_stdin(prompt: impl Display, theme: &dyn Theme) -> Result<V
 
             Value::Timestamp(ts)
         }
+        ValueTy::ZonedTimestamp => {
+            let chosen_index = FuzzySelect::with_theme(theme)
+                .with_prompt("Timezone: ")
+                .items(&chrono_tz::TZ_VARIANTS)
+                .interact()?;
+            let tz = chrono_tz::TZ_VARIANTS[chosen_index];
+
+            let dt = if Confirm::with_theme(theme).with_prompt("Now?").interact()? {
+                Utc::now().with_timezone(&tz)
+            } else {
+                loop {
+                    let local: NaiveDateTime = Input::with_theme(theme)
+                        .with_prompt("Local time (%Y-%m-%dT%H:%M:%S%.f): ")
+                        .interact()?;
+
+                    //skipped times (eg. in a DST transition) don't exist, and repeated times pick the earlier one
+                    match tz.from_local_datetime(&local).earliest() {
+                        Some(dt) => break dt,
+                        None => println!("Time must exist in {tz}"),
+                    }
+                }
+            };
+
+            Value::ZonedTimestamp(dt)
+        }
         ValueTy::JSON => {
             let v: SJValue = Input::with_theme(theme).with_prompt("JSON: ").interact()?;
             Value::JSON(v)
@@ -253,55 +273,25 @@ pub fn get_value_from_stdin(prompt: impl Display, theme: &dyn Theme) -> Result<V
 
             Value::Array(res)
         }
-        ValueTy::Map => {
-            let map = if Confirm::with_theme(theme)
-                .with_prompt("Do you know how long the store is?")
-                .interact()?
-            {
-                let length: usize = Input::with_theme(theme)
-                    .with_prompt("Length: ")
-                    .interact()?;
-
-                let mut map = HashMap::new();
-
-                for _ in 0..length {
-                    let key: String = Input::with_theme(theme).with_prompt("Key: ").interact()?;
-                    let value = get_value_from_stdin("Value: ", theme)?;
-
-                    map.insert(key, value);
-                }
-
-                map
-            } else {
-                let mut map = HashMap::new();
-
-                loop {
-                    if Confirm::with_theme(theme)
-                        .with_prompt("Is that all the keys & values?")
-                        .interact()?
-                    {
-                        break;
-                    }
-
-                    let key: String = Input::with_theme(theme).with_prompt("Key: ").interact()?;
-                    let value = get_value_from_stdin("Value: ", theme)?;
-
-                    map.insert(key, value);
-                }
-
-                map
-            };
-
-            Value::Map(map)
-        }
+        ValueTy::Map => Value::Map(get_entries_from_stdin(theme)?.into_iter().collect()),
+        ValueTy::OrderedMap => Value::OrderedMap(get_entries_from_stdin(theme)?),
         ValueTy::Null => Value::Null(()),
         ValueTy::DoubleFloat => {
             let f: f64 = Input::with_theme(theme).with_prompt("Value:").interact()?;
             Value::DoubleFloat(f)
         }
         ValueTy::SingleFloat => {
-            let f: f64 = Input::with_theme(theme).with_prompt("Value:").interact()?;
-            Value::DoubleFloat(f)
+            let f: f64 = Input::with_theme(theme)
+                .with_prompt("Value:")
+                .validate_with(|f: &f64| {
+                    if narrow_to_single(*f).is_some() {
+                        Ok(())
+                    } else {
+                        Err("Value is too large for a single-precision float")
+                    }
+                })
+                .interact()?;
+            Value::SingleFloat(narrow_to_single(f).expect("just validated the value fits"))
         }
         ValueTy::Timezone => {
             let chosen_index = FuzzySelect::with_theme(theme)
@@ -322,5 +312,102 @@ pub fn get_value_from_stdin(prompt: impl Display, theme: &dyn Theme) -> Result<V
                 .interact()?;
             Value::Ipv6Addr(addr)
         }
+        ValueTy::Uuid => {
+            let uuid = Input::with_theme(theme).with_prompt("UUID: ").interact()?;
+            Value::Uuid(uuid)
+        }
+        ValueTy::Duration => {
+            let seconds: u64 = Input::with_theme(theme)
+                .with_prompt("Seconds: ")
+                .interact()?;
+            let nanoseconds: u32 = Input::with_theme(theme)
+                .with_prompt("Nanoseconds: ")
+                .validate_with(|n: &u32| {
+                    if *n < 1_000_000_000 {
+                        Ok(())
+                    } else {
+                        Err("Nanoseconds must be less than one billion")
+                    }
+                })
+                .interact()?;
+            Value::from(std::time::Duration::new(seconds, nanoseconds))
+        }
+        ValueTy::Regex => {
+            let regex = Input::with_theme(theme).with_prompt("Regex: ").interact()?;
+            Value::Regex(regex)
+        }
+        ValueTy::Tagged => {
+            let tag: String = Input::with_theme(theme).with_prompt("Tag: ").interact()?;
+            let value = get_value_from_stdin("Value: ", theme)?;
+            Value::Tagged {
+                tag,
+                value: Box::new(value),
+            }
+        }
+        ValueTy::Unknown => unreachable!("unknown types can't be chosen"),
     })
 }
+
+///Converts a double to a single-precision float, returning [`None`] if it is finite but out of the range of an [`f32`] (rather than silently becoming infinite). Infinities and NaN are kept as they are.
+#[allow(clippy::cast_possible_truncation)]
+fn narrow_to_single(f: f64) -> Option<f32> {
+    if f.is_finite() && f.abs() > f64::from(f32::MAX) {
+        None
+    } else {
+        Some(f as f32)
+    }
+}
+
+///Gets the keys and values for a [`Value::Map`] or [`Value::OrderedMap`] from stdin, in the order they were entered.
+fn get_entries_from_stdin(theme: &dyn Theme) -> Result<Vec<(String, Value)>, Error> {
+    let mut entries = vec![];
+
+    if Confirm::with_theme(theme)
+        .with_prompt("Do you know how long the store is?")
+        .interact()?
+    {
+        let length: usize = Input::with_theme(theme)
+            .with_prompt("Length: ")
+            .interact()?;
+
+        for _ in 0..length {
+            let key: String = Input::with_theme(theme).with_prompt("Key: ").interact()?;
+            let value = get_value_from_stdin("Value: ", theme)?;
+
+            entries.push((key, value));
+        }
+    } else {
+        loop {
+            if Confirm::with_theme(theme)
+                .with_prompt("Is that all the keys & values?")
+                .interact()?
+            {
+                break;
+            }
+
+            let key: String = Input::with_theme(theme).with_prompt("Key: ").interact()?;
+            let value = get_value_from_stdin("Value: ", theme)?;
+
+            entries.push((key, value));
+        }
+    }
+
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::narrow_to_single;
+
+    #[test]
+    fn test_narrow_to_single() {
+        assert_eq!(narrow_to_single(1.5), Some(1.5_f32));
+        assert_eq!(narrow_to_single(-0.1), Some(-0.1_f32));
+        assert_eq!(narrow_to_single(f64::from(f32::MAX)), Some(f32::MAX));
+        assert_eq!(narrow_to_single(f64::INFINITY), Some(f32::INFINITY));
+        assert!(narrow_to_single(f64::NAN).is_some_and(f32::is_nan));
+
+        assert_eq!(narrow_to_single(1e39), None);
+        assert_eq!(narrow_to_single(-f64::MAX), None);
+    }
+}
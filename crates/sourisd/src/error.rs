@@ -2,7 +2,10 @@ use axum::{
     http::StatusCode,
     response::{IntoResponse, Response},
 };
-use sourisdb::{store::StoreSerError, types::integer::IntegerSerError, values::ValueSerError};
+use sourisdb::{
+    store::StoreSerError, types::integer::IntegerSerError, values::ValueSerError,
+    InvalidDbNameReason,
+};
 use std::{
     error::Error,
     fmt::{Display, Formatter},
@@ -17,8 +20,13 @@ pub enum SourisError {
     KeyNotFound,
     StoreError(StoreSerError),
     ValueError(ValueSerError),
-    InvalidDatabaseName,
+    InvalidDatabaseName(InvalidDbNameReason),
     IntegerSerError(IntegerSerError),
+    QuotaExceeded {
+        limit: usize,
+        current: usize,
+        attempted: usize,
+    },
 }
 
 impl From<IOError> for SourisError {
@@ -62,11 +70,16 @@ impl Display for SourisError {
             Self::DatabaseNotFound => write!(f, "Could not find database with name"),
             Self::KeyNotFound => write!(f, "Could not find value with name in database provided"),
             Self::ValueError(e) => write!(f, "Error with value: {e}"),
-            Self::InvalidDatabaseName => write!(
+            Self::InvalidDatabaseName(reason) => write!(f, "Invalid database name - {reason}"),
+            Self::IntegerSerError(e) => write!(f, "Error deserialising integer: {e:?}"),
+            Self::QuotaExceeded {
+                limit,
+                current,
+                attempted,
+            } => write!(
                 f,
-                "Invalid database name - database names must be ASCII and not equal to `meta`"
+                "Database quota exceeded - the limit is {limit} bytes, the database currently uses {current} bytes and this write would have made it {attempted} bytes"
             ),
-            Self::IntegerSerError(e) => write!(f, "Error deserialising integer: {e:?}"),
         }
     }
 }
@@ -76,9 +89,10 @@ impl IntoResponse for SourisError {
         error!(?self, "Returning error");
 
         let code = match self {
-            Self::DatabaseNotFound | Self::KeyNotFound | Self::InvalidDatabaseName => {
+            Self::DatabaseNotFound | Self::KeyNotFound | Self::InvalidDatabaseName(_) => {
                 StatusCode::BAD_REQUEST
             }
+            Self::QuotaExceeded { .. } => StatusCode::PAYLOAD_TOO_LARGE,
             _ => StatusCode::INTERNAL_SERVER_ERROR,
         };
 
@@ -4,8 +4,6 @@
 #[macro_use]
 extern crate tracing;
 
-use std::time::Duration;
-
 use axum::{
     extract::DefaultBodyLimit,
     http::StatusCode,
@@ -15,21 +13,33 @@ use axum::{
 use tokio::{
     net::TcpListener,
     signal,
-    sync::{broadcast, broadcast::Sender},
+    sync::{
+        broadcast,
+        broadcast::{Receiver, Sender},
+    },
     task::JoinHandle,
 };
+use tower_http::limit::RequestBodyLimitLayer;
 use tower_http::trace::TraceLayer;
+#[cfg(feature = "gzip")]
+use tower_http::{compression::CompressionLayer, decompression::RequestDecompressionLayer};
 use tracing_subscriber::{prelude::*, EnvFilter};
 
 use crate::v1_routes::{
-    db::{add_db, add_db_with_content, clear_db, get_all_dbs, get_db, remove_db},
+    db::{
+        add_db, add_db_with_content, clear_db, db_stats, export_all, get_all_dbs, get_db, get_keys,
+        import_all, remove_db, snapshot, stream_db,
+    },
     state::SourisState,
-    value::{add_kv, get_value, rm_key},
+    value::{add_kv, add_kvs, get_value, rm_key},
 };
 
 mod error;
 mod v1_routes;
 
+///The largest request body accepted by routes which only use query parameters, in bytes - they never read the body, so anything more than this is almost certainly a mistake or an attack.
+const METADATA_BODY_LIMIT: usize = 1024;
+
 fn setup() {
     tracing_subscriber::registry()
         .with(tracing_subscriber::fmt::layer())
@@ -85,6 +95,34 @@ async fn shutdown_signal(stop_signal: Sender<()>, saver: JoinHandle<()>) {
     }
 }
 
+///Prunes expired keys and saves every database each [`SourisState::save_interval`], and then once more after the stop signal is received.
+async fn save_periodically(state: SourisState, mut stop_rx: Receiver<()>) {
+    let interval = state.save_interval();
+    loop {
+        tokio::select! {
+            _ = stop_rx.recv() => {
+                info!("Stop signal received for saver");
+                break;
+            },
+            () = tokio::time::sleep(interval) => {
+                let pruned = state.prune_expired().await;
+                if pruned > 0 {
+                    trace!(%pruned, "Pruned expired keys");
+                }
+
+                if let Err(e) = state.save().await {
+                    error!(?e, "Error saving state");
+                }
+            }
+        }
+    }
+
+    if let Err(e) = state.save().await {
+        error!(?e, "Error saving state");
+    }
+    info!("Exiting saver");
+}
+
 async fn healthcheck() -> StatusCode {
     StatusCode::OK
 }
@@ -97,48 +135,44 @@ async fn main() {
     info!("Found state {state:?}");
 
     let (stop_tx, stop_rx) = broadcast::channel(1);
-    let saver_state = state.clone();
-
-    let mut saver_stop_rx = stop_rx.resubscribe();
-    let saver = tokio::task::spawn(async move {
-        let state = saver_state;
-        loop {
-            tokio::select! {
-                _ = saver_stop_rx.recv() => {
-                    info!("Stop signal received for saver");
-                    break;
-                },
-                () = tokio::time::sleep(Duration::from_secs(10)) => {
-                    if let Err(e) = state.save().await {
-                        error!(?e, "Error saving state");
-                    }
-                }
-            }
-        }
+    let saver = tokio::task::spawn(save_periodically(state.clone(), stop_rx));
 
-        if let Err(e) = state.save().await {
-            error!(?e, "Error saving state");
-        }
-        info!("Exiting saver");
-    });
+    //bodies which are too large get rejected with `413 Payload Too Large` - either by the extractor for routes with bodies, or by the limit layer for the rest
+    let v1_body_router = Router::new()
+        .route("/add_db_with_content", put(add_db_with_content))
+        .route("/add_kv", put(add_kv))
+        .route("/add_kvs", put(add_kvs))
+        .route("/import_all", put(import_all))
+        .layer(DefaultBodyLimit::max(state.max_body_bytes()));
 
-    let v1_router = Router::new()
+    let v1_metadata_router = Router::new()
         .route("/get_db", get(get_db))
+        .route("/snapshot", get(snapshot))
+        .route("/stream_db", get(stream_db))
+        .route("/get_keys", get(get_keys))
         .route("/get_all_db_names", get(get_all_dbs))
+        .route("/db_stats", get(db_stats))
         .route("/add_db", post(add_db))
-        .route("/add_db_with_content", put(add_db_with_content))
         .route("/rm_db", post(remove_db))
         .route("/clear_db", post(clear_db))
-        .route("/add_kv", put(add_kv))
         .route("/rm_kv", post(rm_key))
-        .route("/get_value", get(get_value));
+        .route("/get_value", get(get_value))
+        .route("/export_all", get(export_all))
+        .layer(RequestBodyLimitLayer::new(METADATA_BODY_LIMIT));
 
     let router = Router::new()
         .route("/healthcheck", get(healthcheck))
-        .nest("/v1", v1_router)
-        .layer(TraceLayer::new_for_http())
-        .layer(DefaultBodyLimit::disable())
-        .with_state(state.clone());
+        .layer(RequestBodyLimitLayer::new(METADATA_BODY_LIMIT))
+        .nest("/v1", v1_body_router.merge(v1_metadata_router))
+        .layer(TraceLayer::new_for_http());
+
+    //decompress gzip request bodies, and gzip responses for clients which send `Accept-Encoding: gzip`
+    #[cfg(feature = "gzip")]
+    let router = router
+        .layer(RequestDecompressionLayer::new())
+        .layer(CompressionLayer::new());
+
+    let router = router.with_state(state.clone());
 
     //port chosen as SOUR in T9 code
     //TODO: option to change port
@@ -1,12 +1,17 @@
 use axum::{
-    body::Bytes,
+    body::{Body, Bytes},
     extract::{Query, State},
     http::StatusCode,
+    response::IntoResponse,
     Json,
 };
+use core::convert::Infallible;
+use futures_util::{stream, StreamExt};
 use serde::Deserialize;
 
-use sourisdb::store::Store;
+use sourisdb::{
+    axum::SourisStore, serde_json::Value as SJValue, store::stream::end_chunk, TOTAL_COUNT_HEADER,
+};
 
 use crate::{error::SourisError, v1_routes::state::SourisState};
 
@@ -21,6 +26,12 @@ pub struct DbByName {
     pub db_name: String,
 }
 
+#[derive(Deserialize)]
+pub struct Pagination {
+    pub offset: Option<usize>,
+    pub limit: Option<usize>,
+}
+
 pub async fn add_db(
     State(state): State<SourisState>,
     Query(NewDB {
@@ -37,12 +48,11 @@ pub async fn add_db_with_content(
         db_name: name,
         overwrite_existing,
     }): Query<NewDB>,
-    body: Bytes,
+    SourisStore(store): SourisStore,
 ) -> Result<StatusCode, SourisError> {
-    let store = Store::deser(body.as_ref())?;
-    Ok(state
+    state
         .new_db_with_contents(name, overwrite_existing, store)
-        .await)
+        .await
 }
 
 pub async fn clear_db(
@@ -69,6 +79,85 @@ pub async fn get_db(
     state.get_db(name).await
 }
 
-pub async fn get_all_dbs(State(state): State<SourisState>) -> Json<Vec<String>> {
-    Json(state.get_all_db_names().await)
+pub async fn snapshot(
+    State(state): State<SourisState>,
+    Query(DbByName { db_name: name }): Query<DbByName>,
+) -> Result<Bytes, SourisError> {
+    let store = state
+        .snapshot(&name)
+        .await
+        .ok_or(SourisError::DatabaseNotFound)?;
+    Ok(Bytes::from(store.ser()?))
+}
+
+///How many entries [`stream_db`] reads from a database at once.
+const STREAM_BATCH_LEN: usize = 256;
+
+///Sends a database one entry at a time (see [`sourisdb::store::stream`]), so that clients can start on the first entries before the rest have arrived.
+///
+/// Only the keys are copied up-front, and then the entries are read a batch at a time, so the whole database is never held twice. That means the stream isn't from one point in time, unlike [`snapshot`] - keys added after it starts aren't sent, keys which are removed (or whose TTL passes) before their batch is read are skipped, and other keys have whatever value they had when their batch was read.
+pub async fn stream_db(
+    State(state): State<SourisState>,
+    Query(DbByName { db_name: name }): Query<DbByName>,
+) -> Result<Body, SourisError> {
+    let keys = state
+        .get_keys(&name)
+        .await
+        .ok_or(SourisError::DatabaseNotFound)?;
+
+    let chunks = stream::unfold(Some((state, name, keys, 0)), |batch| async move {
+        let (state, name, keys, start) = batch?;
+        if start >= keys.len() {
+            return Some((end_chunk(), None));
+        }
+
+        let end = (start + STREAM_BATCH_LEN).min(keys.len());
+        match state.entry_chunks(&name, &keys[start..end]).await {
+            Some(chunk) => Some((chunk, Some((state, name, keys, end)))),
+            //the database was removed part-way through, so there's nothing left to send
+            None => Some((end_chunk(), None)),
+        }
+    });
+    Ok(Body::from_stream(chunks.map(Ok::<_, Infallible>)))
+}
+
+pub async fn get_keys(
+    State(state): State<SourisState>,
+    Query(DbByName { db_name: name }): Query<DbByName>,
+) -> Result<Json<Vec<String>>, SourisError> {
+    state
+        .get_keys(&name)
+        .await
+        .map(Json)
+        .ok_or(SourisError::DatabaseNotFound)
+}
+
+pub async fn db_stats(
+    State(state): State<SourisState>,
+    Query(DbByName { db_name: name }): Query<DbByName>,
+) -> Result<Json<SJValue>, SourisError> {
+    Ok(Json(state.db_stats(name).await?.to_json()))
+}
+
+pub async fn get_all_dbs(
+    State(state): State<SourisState>,
+    Query(Pagination { offset, limit }): Query<Pagination>,
+) -> impl IntoResponse {
+    let (names, total) = state
+        .get_all_db_names(offset.unwrap_or_default(), limit)
+        .await;
+    ([(TOTAL_COUNT_HEADER, total.to_string())], Json(names))
+}
+
+pub async fn export_all(State(state): State<SourisState>) -> Result<Bytes, SourisError> {
+    state.export_all().await
+}
+
+pub async fn import_all(
+    State(state): State<SourisState>,
+    SourisStore(archive): SourisStore,
+) -> Result<StatusCode, SourisError> {
+    let count = state.import_all(archive).await?;
+    info!(%count, "Imported databases");
+    Ok(StatusCode::OK)
 }
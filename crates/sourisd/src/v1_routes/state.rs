@@ -2,18 +2,26 @@ use axum::{body::Bytes, http::StatusCode};
 use color_eyre::eyre::{bail, Context};
 use dirs::data_dir;
 use moka::future::Cache;
-use sourisdb::{store::Store, values::Value};
+use sourisdb::{
+    hashbrown::{hash_map::Entry as StoreEntry, HashMap as StoreMap},
+    is_valid_db_name,
+    store::{stream::entry_chunk, CreationResult, DbStats, Store, StoreSerError},
+    types::binary::BinaryData,
+    values::Value,
+    RESERVED_DB_NAME,
+};
 use std::{
-    collections::{hash_map::Entry, HashMap},
+    collections::{hash_map::Entry, HashMap, HashSet},
     env::var,
     fmt::Debug,
     path::{Path, PathBuf},
     sync::Arc,
+    time::{Duration, Instant},
 };
 use tokio::{
-    fs::{create_dir_all, File},
+    fs::{create_dir_all, remove_file, rename, File},
     io::{AsyncReadExt, AsyncWriteExt, ErrorKind},
-    sync::Mutex,
+    sync::{Mutex, RwLock},
 };
 
 fn running_with_superuser() -> bool {
@@ -25,9 +33,27 @@ mod meta {
     pub const META_DB_FILE_NAME: &str = "meta.sdb";
     ///Name of the key inside the meta information database that stores the array of databases
     pub const DB_FILE_NAMES_KEY: &str = "existing_dbs";
+    ///Name of the environment variable used to set the maximum size of one database in bytes
+    pub const MAX_DB_BYTES_VAR: &str = "SOURIS_MAX_DB_BYTES";
+    ///Name of the environment variable used to set how often all databases are saved to disk, in seconds
+    pub const SAVE_INTERVAL_SECS_VAR: &str = "SOURIS_SAVE_INTERVAL_SECS";
+    ///How often all databases are saved to disk if [`SAVE_INTERVAL_SECS_VAR`] isn't set, in seconds
+    pub const DEFAULT_SAVE_INTERVAL_SECS: u64 = 10;
+    ///Name of the environment variable used to set the largest request body accepted by the routes which take a value or store, in bytes
+    pub const MAX_BODY_BYTES_VAR: &str = "SOURIS_MAX_BODY_BYTES";
+    ///The largest request body accepted by the routes which take a value or store if [`MAX_BODY_BYTES_VAR`] isn't set, in bytes
+    pub const DEFAULT_MAX_BODY_BYTES: usize = 256 * 1024 * 1024;
+    ///Name of the environment variable used to set how long serialising a database can take before a warning is logged, in milliseconds
+    pub const SLOW_SER_MILLIS_VAR: &str = "SOURIS_SLOW_SER_MILLIS";
+    ///How long serialising a database can take before a warning is logged if [`SLOW_SER_MILLIS_VAR`] isn't set, in milliseconds
+    pub const DEFAULT_SLOW_SER_MILLIS: u64 = 500;
 }
 use crate::{error::SourisError, v1_routes::value::KeyAndDb};
-use meta::{DB_FILE_NAMES_KEY, META_DB_FILE_NAME};
+use meta::{
+    DB_FILE_NAMES_KEY, DEFAULT_MAX_BODY_BYTES, DEFAULT_SAVE_INTERVAL_SECS, DEFAULT_SLOW_SER_MILLIS,
+    MAX_BODY_BYTES_VAR, MAX_DB_BYTES_VAR, META_DB_FILE_NAME, SAVE_INTERVAL_SECS_VAR,
+    SLOW_SER_MILLIS_VAR,
+};
 
 #[derive(Clone, Debug)]
 #[allow(clippy::module_name_repetitions)]
@@ -35,8 +61,56 @@ pub struct SourisState {
     ///The base location in which all databases reside
     base_location: PathBuf,
     ///A map of all databases and their names
-    dbs: Arc<Mutex<HashMap<String, Store>>>,
+    dbs: Arc<RwLock<HashMap<String, Store>>>,
+    ///A cache of serialised databases, which also holds the current size of each database for checking the quota.
+    ///
+    /// Entries are only ever inserted while holding a lock on `dbs` (a read lock in [`SourisState::get_db`], or the write lock for the change that was just measured in [`SourisState::over_quota`]), and only ever invalidated while holding a write lock on `dbs`, so a stale database can't end up in the cache. The cache never touches `dbs` itself, so awaiting it while holding either lock can't deadlock.
     db_cache: Cache<String, Bytes>,
+    ///The maximum serialised size of any one database, if there is one
+    max_db_bytes: Option<usize>,
+    ///When keys which were added with a TTL expire, keyed by database name. Databases without any keys with a TTL aren't in here.
+    ///
+    /// This is only ever locked after `dbs` if both are needed, so the two can't deadlock.
+    expiries: Arc<RwLock<HashMap<String, DbExpiries>>>,
+    ///Held by [`SourisState::save`] from taking its snapshot until the files are written, and by [`SourisState::remove_db`] until the file is removed, so that a save can't write a database back out after it has been removed.
+    ///
+    /// This is always locked before `dbs`.
+    save_lock: Arc<Mutex<()>>,
+    ///How long to wait between saving all databases to disk
+    save_interval: Duration,
+    ///The largest request body accepted by the routes which take a value or store
+    max_body_bytes: usize,
+    ///How long serialising a database can take before a warning is logged
+    slow_ser_threshold: Duration,
+}
+
+///When the keys in one database which were added with a TTL expire.
+#[derive(Debug, Default)]
+struct DbExpiries {
+    ///When each key expires.
+    keys: HashMap<String, Instant>,
+    ///No key expires before this, so nothing needs checking until it has passed. It isn't updated when keys are removed, so it can be earlier than every key, but it is never later than any of them.
+    earliest: Option<Instant>,
+}
+
+impl DbExpiries {
+    fn insert(&mut self, key: String, expiry: Instant) {
+        self.keys.insert(key, expiry);
+        self.earliest = Some(
+            self.earliest
+                .map_or(expiry, |earliest| earliest.min(expiry)),
+        );
+    }
+
+    ///Whether any keys might have expired by `now`.
+    fn any_expired(&self, now: Instant) -> bool {
+        self.earliest.is_some_and(|earliest| earliest <= now)
+    }
+
+    ///Whether `key` has a TTL which has passed by `now`.
+    fn has_expired(&self, key: &str, now: Instant) -> bool {
+        self.keys.get(key).is_some_and(|expiry| *expiry <= now)
+    }
 }
 
 impl SourisState {
@@ -45,64 +119,96 @@ impl SourisState {
     /// Returns [`StatusCode::OK`] if an existing database was overwritten, or [`StatusCode::CREATED`] if a new database was created.
     ///
     /// ## Errors
-    /// - [`SourisError::InvalidDatabaseName`] if the name is not ASCII or the name is `meta`.
+    /// - [`SourisError::InvalidDatabaseName`] if the name isn't allowed - see [`is_valid_db_name`].
     #[tracing::instrument(level = "trace", skip(self))]
     pub async fn new_db(
         &self,
         name: String,
         overwrite_existing: bool,
     ) -> Result<StatusCode, SourisError> {
-        if name == "meta" || !name.is_ascii() {
-            return Err(SourisError::InvalidDatabaseName);
-        }
+        is_valid_db_name(&name).map_err(SourisError::InvalidDatabaseName)?;
 
-        let mut dbs = self.dbs.lock().await;
+        let mut dbs = self.dbs.write().await;
 
         if dbs.contains_key(&name) && !overwrite_existing {
             return Ok(StatusCode::OK);
         }
         dbs.insert(name.clone(), Store::default());
-        self.db_cache.invalidate(&name).await;
+        self.invalidate(&name).await;
+        self.forget_expiries(&name, |_| true).await;
 
         Ok(StatusCode::CREATED)
     }
 
+    ///Creates a new database with the given contents, or adds the contents to an existing database.
+    ///
+    /// Returns [`StatusCode::CREATED`] if the database was created, or [`StatusCode::OK`] if it already existed.
+    ///
+    /// ## Errors
+    /// - [`SourisError::InvalidDatabaseName`] if the database would be created and the name isn't allowed - see [`is_valid_db_name`].
+    /// - [`SourisError::QuotaExceeded`] if the resulting database would be bigger than the quota.
+    /// - [`SourisError::StoreError`] if the resulting database couldn't be serialised to check the quota.
     #[tracing::instrument(level = "trace", skip(self, contents))]
     pub async fn new_db_with_contents(
         &self,
         name: String,
         overwrite_existing: bool,
-        contents: Store,
-    ) -> StatusCode {
-        self.db_cache.invalidate(&name).await;
-        let mut dbs = self.dbs.lock().await;
+        mut contents: Store,
+    ) -> Result<StatusCode, SourisError> {
+        let mut dbs = self.dbs.write().await;
+
+        let created_new = !dbs.contains_key(&name);
+        if created_new {
+            is_valid_db_name(&name).map_err(SourisError::InvalidDatabaseName)?;
+        }
+        let cached = self.db_cache.get(&name).await;
+        self.invalidate(&name).await;
 
-        let created_new = dbs.contains_key(&name);
-        let current = dbs.entry(name).or_default();
         if overwrite_existing {
-            *current = contents;
+            if let Some(attempted) = self.over_quota(&name, &contents).await? {
+                let current = dbs.get(&name).cloned().unwrap_or_default();
+                return Err(self.quota_exceeded(&name, &current, cached, attempted));
+            }
+            self.forget_expiries(&name, |_| true).await;
+            dbs.insert(name, contents);
         } else {
-            for (k, v) in &*contents {
-                current.insert(k.clone(), v.clone());
+            let db = dbs.entry(name.clone()).or_default();
+            let old_values: Vec<_> = contents
+                .drain()
+                .map(|(key, value)| (key.clone(), db.insert(key, value)))
+                .collect();
+
+            if let Some(attempted) = self.over_quota(&name, db).await? {
+                undo(db, old_values);
+                let error = self.quota_exceeded(&name, db, cached, attempted);
+                if created_new {
+                    dbs.remove(&name);
+                }
+                return Err(error);
             }
+
+            let added: HashSet<&str> = old_values.iter().map(|(key, _)| key.as_str()).collect();
+            self.forget_expiries(&name, |k| added.contains(k)).await;
         }
 
-        if created_new {
+        Ok(if created_new {
             StatusCode::CREATED
         } else {
             StatusCode::OK
-        }
+        })
     }
 
     ///returns whether it cleared a database
+    ///
+    /// The memory used by the database is freed rather than kept around for new keys - see [`Store::clear_and_shrink`].
     #[tracing::instrument(level = "trace", skip(self))]
     pub async fn clear_db(&self, name: String) -> Result<(), SourisError> {
-        self.db_cache.invalidate(&name).await;
-
-        let mut dbs = self.dbs.lock().await;
+        let mut dbs = self.dbs.write().await;
+        self.invalidate(&name).await;
+        self.forget_expiries(&name, |_| true).await;
 
         if let Entry::Occupied(mut e) = dbs.entry(name) {
-            e.insert(Store::default());
+            e.get_mut().clear_and_shrink();
             Ok(())
         } else {
             trace!("Unable to find store.");
@@ -113,15 +219,16 @@ impl SourisState {
     ///returns whether it removed a database
     #[tracing::instrument(level = "trace", skip(self))]
     pub async fn remove_db(&self, name: String) -> Result<(), SourisError> {
-        self.db_cache.invalidate(&name).await;
-
-        let mut dbs = self.dbs.lock().await;
+        let _saving = self.save_lock.lock().await;
+        let mut dbs = self.dbs.write().await;
+        self.invalidate(&name).await;
 
         if !dbs.contains_key(&name) {
             return Err(SourisError::DatabaseNotFound);
         }
 
         dbs.remove(&name);
+        self.forget_expiries(&name, |_| true).await;
         drop(dbs);
 
         let file_name = self.base_location.join(format!("{name}.sdb"));
@@ -136,82 +243,544 @@ impl SourisState {
     }
 
     pub async fn get_db(&self, name: String) -> Result<Bytes, SourisError> {
+        //pruning invalidates the cache, so this has to come before looking in it
+        self.prune_expired_if_any(Some(&name)).await;
         if let Some(bytes) = self.db_cache.get(&name).await {
             return Ok(bytes);
         }
 
-        let dbs = self.dbs.lock().await;
-        let db = dbs
-            .get(&name)
-            .cloned()
-            .ok_or(SourisError::DatabaseNotFound)?;
+        let dbs = self.dbs.read().await;
+        let db = dbs.get(&name).ok_or(SourisError::DatabaseNotFound)?;
 
-        let sered = db.ser()?;
+        let sered = self.ser_db(&name, db)?;
         let bytes = Bytes::from(sered);
 
         self.db_cache.insert(name, bytes.clone()).await;
+        drop(dbs);
         Ok(bytes)
     }
 
+    ///Clones a database as it is right now, so that several keys can be read from it without any of them changing in between.
+    ///
+    /// Unlike [`SourisState::get_db`], this doesn't serialise the database or touch the cache. Keys whose TTL has passed are pruned first, so they aren't included.
+    pub async fn snapshot(&self, db_name: &str) -> Option<Store> {
+        self.prune_expired_if_any(Some(db_name)).await;
+        self.dbs.read().await.get(db_name).cloned()
+    }
+
+    ///Gets the keys in a database, sorted, without any of their values. Keys whose TTL has passed are skipped, as they are treated as missing by [`SourisState::get_value`].
+    pub async fn get_keys(&self, db_name: &str) -> Option<Vec<String>> {
+        let dbs = self.dbs.read().await;
+        let db = dbs.get(db_name)?;
+
+        let now = Instant::now();
+        let expiries = self.expiries.read().await;
+        let expiries = expiries.get(db_name);
+
+        let mut keys: Vec<String> = db
+            .keys()
+            .filter(|key| !expiries.is_some_and(|expiries| expiries.has_expired(key, now)))
+            .cloned()
+            .collect();
+        keys.sort_unstable();
+        Some(keys)
+    }
+
+    ///Serialises the entries for `keys` into entry stream chunks (see [`entry_chunk`]), one after the other. Keys which have been removed or whose TTL has passed are skipped.
+    ///
+    /// Returns [`None`] if the database doesn't exist.
+    pub async fn entry_chunks(&self, db_name: &str, keys: &[String]) -> Option<Vec<u8>> {
+        let dbs = self.dbs.read().await;
+        let db = dbs.get(db_name)?;
+
+        let now = Instant::now();
+        let expiries = self.expiries.read().await;
+        let expiries = expiries.get(db_name);
+
+        let mut chunks = vec![];
+        for key in keys {
+            if expiries.is_some_and(|expiries| expiries.has_expired(key, now)) {
+                continue;
+            }
+            if let Some(value) = db.get(key) {
+                chunks.extend(entry_chunk(key, value));
+            }
+        }
+        Some(chunks)
+    }
+
+    ///Gets the [`DbStats`] for a database.
+    ///
+    /// ## Errors
+    /// - [`SourisError::DatabaseNotFound`] if the database doesn't exist.
+    /// - [`SourisError::StoreError`] if the database can't be serialised to find its size.
+    pub async fn db_stats(&self, name: String) -> Result<DbStats, SourisError> {
+        self.prune_expired_if_any(Some(&name)).await;
+        let dbs = self.dbs.read().await;
+        let db = dbs.get(&name).ok_or(SourisError::DatabaseNotFound)?;
+
+        Ok(db.stats()?)
+    }
+
+    ///Adds a key-value pair to a database, creating the database if it doesn't exist.
+    ///
+    /// If a `ttl` is given, the key expires after that long - after that it is treated as missing by every read, and it is removed by [`SourisState::prune_expired`]. Keys with a TTL are never written to disk. Adding a key without a TTL removes any TTL it had before.
+    ///
+    /// Returns [`StatusCode::CREATED`] if the key is new, or [`StatusCode::OK`] if an existing value was overwritten.
+    ///
+    /// ## Errors
+    /// - [`SourisError::InvalidDatabaseName`] if the database would be created and the name isn't allowed - see [`is_valid_db_name`].
+    /// - [`SourisError::QuotaExceeded`] if the database would be bigger than the quota.
+    /// - [`SourisError::StoreError`] if the database couldn't be serialised to check the quota.
     pub async fn add_key_value_pair(
         &self,
         KeyAndDb { key, db_name }: KeyAndDb,
         v: Value,
-    ) -> StatusCode {
-        self.db_cache.invalidate(&db_name).await;
+        ttl: Option<Duration>,
+    ) -> Result<StatusCode, SourisError> {
+        let mut dbs = self.dbs.write().await;
 
-        let mut dbs = self.dbs.lock().await;
+        let created_db = !dbs.contains_key(&db_name);
+        if created_db {
+            is_valid_db_name(&db_name).map_err(SourisError::InvalidDatabaseName)?;
+        }
+        let cached = self.db_cache.get(&db_name).await;
+        self.invalidate(&db_name).await;
+        let db = dbs.entry(db_name.clone()).or_default();
 
-        let db = if let Some(d) = dbs.get_mut(&db_name) {
-            d
-        } else {
+        let old = db.insert(key.clone(), v);
+        let status = match old {
+            Some(_) => StatusCode::OK,
+            None => StatusCode::CREATED,
+        };
+
+        if let Some(attempted) = self.over_quota(&db_name, db).await? {
+            undo(db, vec![(key, old)]);
+            let error = self.quota_exceeded(&db_name, db, cached, attempted);
+            if created_db {
+                dbs.remove(&db_name);
+            }
+            return Err(error);
+        }
+
+        //a TTL too big to add to `now` might as well not expire
+        match ttl.and_then(|ttl| Instant::now().checked_add(ttl)) {
+            Some(expiry) => self
+                .expiries
+                .write()
+                .await
+                .entry(db_name)
+                .or_default()
+                .insert(key, expiry),
+            None => self.forget_expiries(&db_name, |k| k == key).await,
+        }
+
+        Ok(status)
+    }
+
+    ///Invalidates everything cached about a database - this must be called while holding a write lock on `dbs` whenever a database is changed.
+    async fn invalidate(&self, db_name: &str) {
+        self.db_cache.invalidate(db_name).await;
+    }
+
+    ///Checks the new contents of a database against the quota, if there is one, returning how big the database would be if it doesn't fit.
+    ///
+    /// This serialises the whole database, as one new string can change the huffman tree or the compression used for everything else. If it fits, the bytes go into `db_cache` so that [`SourisState::get_db`] (and the next check's error) can use them. If it doesn't, the caller has to undo the change and return [`SourisState::quota_exceeded`].
+    ///
+    /// This must be called while holding the write lock on `dbs` that the change was made under, after [`SourisState::invalidate`].
+    ///
+    /// ## Errors
+    /// - [`SourisError::StoreError`] if the database couldn't be serialised.
+    async fn over_quota(&self, db_name: &str, db: &Store) -> Result<Option<usize>, SourisError> {
+        let Some(max) = self.max_db_bytes else {
+            return Ok(None);
+        };
+
+        let bytes = self.ser_db(db_name, db)?;
+        if bytes.len() > max {
+            return Ok(Some(bytes.len()));
+        }
+        self.db_cache
+            .insert(db_name.to_string(), Bytes::from(bytes))
+            .await;
+        Ok(None)
+    }
+
+    ///The error for a change which would have made a database `attempted` bytes, once the change has been undone.
+    ///
+    /// `cached` is what was in `db_cache` for the database before the change, so that `current` only needs serialising again if it had been evicted.
+    fn quota_exceeded(
+        &self,
+        db_name: &str,
+        current: &Store,
+        cached: Option<Bytes>,
+        attempted: usize,
+    ) -> SourisError {
+        let current = match cached {
+            Some(bytes) => bytes.len(),
+            None => match self.ser_db(db_name, current) {
+                Ok(bytes) => bytes.len(),
+                Err(e) => return e.into(),
+            },
+        };
+
+        SourisError::QuotaExceeded {
+            limit: self.max_db_bytes.unwrap_or(usize::MAX),
+            current,
+            attempted,
+        }
+    }
+
+    ///Adds all of the key-value pairs to the given database under one lock.
+    ///
+    /// Returns [`StatusCode::CREATED`] if the database was created, or [`StatusCode::OK`] if it already existed, alongside a [`Store`] containing the [`CreationResult`] for each key.
+    ///
+    /// ## Errors
+    /// - [`SourisError::DatabaseNotFound`] if the database doesn't exist and `create_new` is false.
+    /// - [`SourisError::InvalidDatabaseName`] if the database would be created and the name isn't allowed - see [`is_valid_db_name`].
+    /// - [`SourisError::QuotaExceeded`] if the database would be bigger than the quota.
+    /// - [`SourisError::StoreError`] if the database couldn't be serialised to check the quota.
+    #[tracing::instrument(level = "trace", skip(self, kvs))]
+    pub async fn add_key_value_pairs(
+        &self,
+        db_name: String,
+        create_new: bool,
+        overwrite_existing: bool,
+        mut kvs: Store,
+    ) -> Result<(StatusCode, Store), SourisError> {
+        let mut dbs = self.dbs.write().await;
+        let cached = self.db_cache.get(&db_name).await;
+        self.invalidate(&db_name).await;
+
+        let status = if dbs.contains_key(&db_name) {
+            StatusCode::OK
+        } else if create_new {
+            is_valid_db_name(&db_name).map_err(SourisError::InvalidDatabaseName)?;
             dbs.insert(db_name.clone(), Store::default());
-            dbs.get_mut(&db_name)
-                .expect("just added this database key lol")
+            StatusCode::CREATED
+        } else {
+            return Err(SourisError::DatabaseNotFound);
         };
+        let db = dbs
+            .get_mut(&db_name)
+            .expect("just checked for this database key");
 
-        match db.insert(key, v) {
-            Some(_) => StatusCode::OK,
-            None => StatusCode::CREATED,
+        let mut results = Store::default();
+        let mut old_values = vec![];
+        for (key, value) in kvs.drain() {
+            let result = match db.entry(key.clone()) {
+                StoreEntry::Occupied(mut e) => {
+                    if overwrite_existing {
+                        old_values.push((key.clone(), Some(e.insert(value))));
+                        CreationResult::Overwritten
+                    } else {
+                        CreationResult::LeftAlone
+                    }
+                }
+                StoreEntry::Vacant(e) => {
+                    e.insert(value);
+                    old_values.push((key.clone(), None));
+                    CreationResult::InsertedNew
+                }
+            };
+            results.insert(key, result.into());
+        }
+
+        if let Some(attempted) = self.over_quota(&db_name, db).await? {
+            undo(db, old_values);
+            let error = self.quota_exceeded(&db_name, db, cached, attempted);
+            if status == StatusCode::CREATED {
+                dbs.remove(&db_name);
+            }
+            return Err(error);
         }
+        let overwritten: HashSet<&str> = old_values
+            .iter()
+            .filter(|(_, old)| old.is_some())
+            .map(|(key, _)| key.as_str())
+            .collect();
+        self.forget_expiries(&db_name, |k| overwritten.contains(k))
+            .await;
+
+        Ok((status, results))
     }
 
     pub async fn get_value(
         &self,
         KeyAndDb { key, db_name }: KeyAndDb,
     ) -> Result<Value, SourisError> {
-        let dbs = self.dbs.lock().await;
+        let dbs = self.dbs.read().await;
 
         let Some(db) = dbs.get(&db_name) else {
             return Err(SourisError::DatabaseNotFound);
         };
-        let Some(key) = db.get(&key).cloned() else {
+        let Some(value) = db.get(&key).cloned() else {
             return Err(SourisError::KeyNotFound);
         };
 
-        Ok(key)
+        let expired = self
+            .expiries
+            .read()
+            .await
+            .get(&db_name)
+            .is_some_and(|expiries| expiries.has_expired(&key, Instant::now()));
+        if expired {
+            return Err(SourisError::KeyNotFound);
+        }
+
+        Ok(value)
     }
 
     pub async fn remove_key(&self, KeyAndDb { key, db_name }: KeyAndDb) -> Result<(), SourisError> {
-        self.db_cache.invalidate(&db_name).await;
-        let mut dbs = self.dbs.lock().await;
+        let mut dbs = self.dbs.write().await;
+        self.invalidate(&db_name).await;
 
         let Some(db) = dbs.get_mut(&db_name) else {
             return Err(SourisError::DatabaseNotFound);
         };
 
-        match db.remove(&key) {
+        let removed = db.remove(&key);
+        self.forget_expiries(&db_name, |k| k == key).await;
+
+        match removed {
             Some(_) => Ok(()),
             None => Err(SourisError::KeyNotFound),
         }
     }
 
-    pub async fn get_all_db_names(&self) -> Vec<String> {
-        self.dbs.lock().await.keys().cloned().collect()
+    ///Forgets the expiries of every key in `db_name` that `should_forget` returns `true` for - used when keys are removed, or overwritten without a TTL.
+    async fn forget_expiries(&self, db_name: &str, mut should_forget: impl FnMut(&str) -> bool) {
+        let mut expiries = self.expiries.write().await;
+        let Some(db_expiries) = expiries.get_mut(db_name) else {
+            return;
+        };
+
+        db_expiries.keys.retain(|key, _| !should_forget(key));
+        if db_expiries.keys.is_empty() {
+            expiries.remove(db_name);
+        }
+    }
+
+    ///Removes every key whose TTL has passed, returning how many keys were removed.
+    pub async fn prune_expired(&self) -> usize {
+        let mut dbs = self.dbs.write().await;
+        let mut expiries = self.expiries.write().await;
+        let now = Instant::now();
+
+        let mut pruned = 0;
+        let mut changed_dbs = vec![];
+        expiries.retain(|db_name, db_expiries| {
+            if !db_expiries.any_expired(now) {
+                return true;
+            }
+
+            let mut db = dbs.get_mut(db_name);
+            let before = pruned;
+            db_expiries.keys.retain(|key, expiry| {
+                if *expiry > now {
+                    return true;
+                }
+                if db.as_deref_mut().and_then(|db| db.remove(key)).is_some() {
+                    pruned += 1;
+                }
+                false
+            });
+            if pruned > before {
+                changed_dbs.push(db_name.clone());
+            }
+
+            db_expiries.earliest = db_expiries.keys.values().min().copied();
+            !db_expiries.keys.is_empty()
+        });
+        drop(expiries);
+
+        for db_name in changed_dbs {
+            self.invalidate(&db_name).await;
+        }
+        drop(dbs);
+
+        pruned
+    }
+
+    ///Runs [`SourisState::prune_expired`] if any keys in `db_name` (or in any database, if it is `None`) have expired, so that reads which return whole databases don't include them.
+    ///
+    /// This only takes a read lock and checks the earliest expiry of each database unless there is something to prune, so it is cheap to call before every read.
+    async fn prune_expired_if_any(&self, db_name: Option<&str>) {
+        let now = Instant::now();
+        let expiries = self.expiries.read().await;
+        let any_expired = match db_name {
+            Some(db_name) => expiries
+                .get(db_name)
+                .is_some_and(|expiries| expiries.any_expired(now)),
+            None => expiries.values().any(|expiries| expiries.any_expired(now)),
+        };
+        drop(expiries);
+
+        if any_expired {
+            self.prune_expired().await;
+        }
+    }
+
+    ///Serialises every database into one archive, which is a [`Store`] mapping each database name to the serialised bytes of that database in a [`Value::Binary`]. Keys with a TTL are kept under [`RESERVED_DB_NAME`], which maps each database name to a [`Value::Map`] of those keys to how long they had left as a [`Value::Duration`].
+    ///
+    /// Every database is serialised under one read lock so that they are all from the same point in time, rather than cloning them all first - writers have to wait for that, just like in [`SourisState::save`]. Databases which are already in the cache aren't serialised again, and the archive is written a frame at a time using [`Store::ser_to_writer`] rather than being compressed all at once.
+    ///
+    /// ## Errors
+    /// - [`SourisError::StoreError`] if any of the databases or the archive can't be serialised.
+    pub async fn export_all(&self) -> Result<Bytes, SourisError> {
+        self.prune_expired_if_any(None).await;
+
+        let dbs = self.dbs.read().await;
+        let expiries = self.expiries.read().await;
+        let now = Instant::now();
+
+        let mut archive = Store::default();
+        for (name, db) in dbs.iter() {
+            let bytes = match self.db_cache.get(name).await {
+                Some(bytes) => bytes.to_vec(),
+                None => self.ser_db(name, db)?,
+            };
+            archive.insert(name.clone(), Value::Binary(BinaryData(bytes)));
+        }
+
+        let ttls: StoreMap<_, _> = expiries
+            .iter()
+            .map(|(name, db_expiries)| {
+                let keys = db_expiries
+                    .keys
+                    .iter()
+                    .map(|(key, expiry)| {
+                        (
+                            key.clone(),
+                            Value::from(expiry.saturating_duration_since(now)),
+                        )
+                    })
+                    .collect();
+                (name.clone(), Value::Map(keys))
+            })
+            .collect();
+        if !ttls.is_empty() {
+            archive.insert(RESERVED_DB_NAME.into(), Value::Map(ttls));
+        }
+        drop(expiries);
+        drop(dbs);
+
+        let mut bytes = vec![];
+        archive.ser_to_writer(&mut bytes)?;
+        Ok(Bytes::from(bytes))
+    }
+
+    ///Restores every database in an archive made by [`SourisState::export_all`], overwriting any existing databases with the same names. Databases which aren't in the archive are left alone.
+    ///
+    /// Keys which had a TTL when the archive was made get the same amount of time left again, counting from now. Archives from before TTLs were exported can still be imported, but every key in them is kept until it is removed.
+    ///
+    /// Every database in the archive is checked before any are restored, so if an error is returned then nothing has changed.
+    ///
+    /// Returns how many databases were restored.
+    ///
+    /// ## Errors
+    /// - [`SourisError::InvalidDatabaseName`] if any of the names aren't allowed - see [`is_valid_db_name`].
+    /// - [`SourisError::ValueError`] if any of the values in the archive aren't [`Value::Binary`], or the TTLs aren't laid out as described in [`SourisState::export_all`].
+    /// - [`SourisError::StoreError`] if any of the databases can't be deserialised.
+    /// - [`SourisError::QuotaExceeded`] if any of the databases are bigger than the quota.
+    #[tracing::instrument(level = "trace", skip(self, archive))]
+    pub async fn import_all(&self, mut archive: Store) -> Result<usize, SourisError> {
+        let ttls = match archive.remove(RESERVED_DB_NAME) {
+            Some(ttls) => StoreMap::<String, Value>::try_from(ttls)?
+                .into_iter()
+                .map(|(name, keys)| {
+                    let keys = StoreMap::<String, Value>::try_from(keys)?
+                        .into_iter()
+                        .map(|(key, ttl)| Ok((key, Duration::try_from(ttl)?)))
+                        .collect::<Result<Vec<_>, SourisError>>()?;
+                    Ok((name, keys))
+                })
+                .collect::<Result<HashMap<_, _>, SourisError>>()?,
+            None => HashMap::new(),
+        };
+
+        let restored = archive
+            .drain()
+            .map(|(name, bytes)| {
+                is_valid_db_name(&name).map_err(SourisError::InvalidDatabaseName)?;
+                let BinaryData(bytes) = BinaryData::try_from(bytes)?;
+                Ok((name, Store::deser(&bytes)?))
+            })
+            .collect::<Result<Vec<_>, SourisError>>()?;
+
+        //every database gets replaced, so only the incoming stores need measuring
+        if let Some(max) = self.max_db_bytes {
+            for (name, db) in &restored {
+                //measured the same way as every other write, rather than with `Store::serialized_size`
+                let attempted = self.ser_db(name, db)?.len();
+                if attempted > max {
+                    let current = match self.dbs.read().await.get(name) {
+                        Some(current) => self.ser_db(name, current)?.len(),
+                        None => 0,
+                    };
+                    return Err(SourisError::QuotaExceeded {
+                        limit: max,
+                        current,
+                        attempted,
+                    });
+                }
+            }
+        }
+
+        let mut dbs = self.dbs.write().await;
+
+        let count = restored.len();
+        let now = Instant::now();
+        for (name, db) in restored {
+            self.invalidate(&name).await;
+            self.forget_expiries(&name, |_| true).await;
+
+            let ttls = ttls.get(&name).into_iter().flatten();
+            //a TTL too big to add to `now` might as well not expire, as in `add_key_value_pair`
+            let db_expiries: Vec<_> = ttls
+                .filter(|(key, _)| db.contains_key(key))
+                .filter_map(|(key, ttl)| Some((key.clone(), now.checked_add(*ttl)?)))
+                .collect();
+            if !db_expiries.is_empty() {
+                let mut expiries = self.expiries.write().await;
+                let entry = expiries.entry(name.clone()).or_default();
+                for (key, expiry) in db_expiries {
+                    entry.insert(key, expiry);
+                }
+            }
+
+            dbs.insert(name, db);
+        }
+
+        Ok(count)
+    }
+
+    ///Gets the names of the databases, skipping the first `offset` and taking up to `limit` if it is set, along with the total number of databases.
+    ///
+    /// The names are sorted before being sliced, so the same `offset` and `limit` always give the same page.
+    pub async fn get_all_db_names(
+        &self,
+        offset: usize,
+        limit: Option<usize>,
+    ) -> (Vec<String>, usize) {
+        let dbs = self.dbs.read().await;
+        let total = dbs.len();
+
+        let mut names: Vec<_> = dbs.keys().collect();
+        names.sort_unstable();
+
+        let page = names
+            .into_iter()
+            .skip(offset)
+            .take(limit.unwrap_or(usize::MAX))
+            .cloned()
+            .collect();
+        (page, total)
     }
 }
 
 impl SourisState {
+    #[allow(clippy::too_many_lines)]
     pub async fn new() -> color_eyre::Result<Self> {
         #[tracing::instrument(level = "trace")]
         async fn get_store(location: PathBuf) -> color_eyre::Result<Store> {
@@ -239,7 +808,8 @@ impl SourisState {
                 }
             }
 
-            Ok(Store::deser(&contents)?)
+            //these are our own files, which older versions wrote without any limit on how deeply values were nested
+            Ok(Store::deser_with_max_depth(&contents, usize::MAX)?)
         }
 
         #[tracing::instrument(level = "trace", skip(meta))]
@@ -260,7 +830,14 @@ impl SourisState {
                     continue;
                 };
 
-                match get_store(base.join(format!("{file_name}.sdb"))).await {
+                //databases removed since the last save have had their files deleted, but are still in the list
+                let location = base.join(format!("{file_name}.sdb"));
+                if !tokio::fs::try_exists(&location).await.unwrap_or(true) {
+                    trace!(?file_name, "Skipping removed database");
+                    continue;
+                }
+
+                match get_store(location).await {
                     Ok(s) => {
                         dbs.insert(file_name.to_string(), s);
                     }
@@ -296,26 +873,120 @@ impl SourisState {
             HashMap::default()
         };
 
+        let max_db_bytes = match var(MAX_DB_BYTES_VAR) {
+            Ok(max) => Some(
+                max.parse()
+                    .with_context(|| format!("trying to parse {MAX_DB_BYTES_VAR}"))?,
+            ),
+            Err(_) => None,
+        };
+        let save_interval = match var(SAVE_INTERVAL_SECS_VAR) {
+            Ok(secs) => {
+                let secs: u64 = secs
+                    .parse()
+                    .with_context(|| format!("trying to parse {SAVE_INTERVAL_SECS_VAR}"))?;
+                if secs == 0 {
+                    bail!("{SAVE_INTERVAL_SECS_VAR} must be at least 1 second");
+                }
+                secs
+            }
+            Err(_) => DEFAULT_SAVE_INTERVAL_SECS,
+        };
+        let max_body_bytes = match var(MAX_BODY_BYTES_VAR) {
+            Ok(max) => max
+                .parse()
+                .with_context(|| format!("trying to parse {MAX_BODY_BYTES_VAR}"))?,
+            Err(_) => DEFAULT_MAX_BODY_BYTES,
+        };
+        let slow_ser_millis = match var(SLOW_SER_MILLIS_VAR) {
+            Ok(millis) => millis
+                .parse()
+                .with_context(|| format!("trying to parse {SLOW_SER_MILLIS_VAR}"))?,
+            Err(_) => DEFAULT_SLOW_SER_MILLIS,
+        };
+
         let s = Self {
             base_location,
-            dbs: Arc::new(Mutex::new(dbs)),
+            dbs: Arc::new(RwLock::new(dbs)),
             db_cache: Cache::new(200),
+            max_db_bytes,
+            expiries: Arc::new(RwLock::new(HashMap::new())),
+            save_lock: Arc::new(Mutex::new(())),
+            save_interval: Duration::from_secs(save_interval),
+            max_body_bytes,
+            slow_ser_threshold: Duration::from_millis(slow_ser_millis),
         };
 
         Ok(s)
     }
 
+    ///How long to wait between each call to [`SourisState::save`].
+    pub fn save_interval(&self) -> Duration {
+        self.save_interval
+    }
+
+    ///The largest request body accepted by the routes which take a value or store, in bytes.
+    pub fn max_body_bytes(&self) -> usize {
+        self.max_body_bytes
+    }
+
+    ///Serialises a database, logging a warning with its name and size if that takes longer than the threshold set by [`SLOW_SER_MILLIS_VAR`].
+    ///
+    /// This uses [`Store::ser_to_writer`], so that serialising a big database doesn't need several copies of it at once.
+    fn ser_db(&self, name: &str, db: &Store) -> Result<Vec<u8>, StoreSerError> {
+        let start = Instant::now();
+        let mut bytes = vec![];
+        db.ser_to_writer(&mut bytes)?;
+
+        let elapsed = start.elapsed();
+        if elapsed > self.slow_ser_threshold {
+            warn!(
+                db_name = name,
+                bytes = bytes.len(),
+                ?elapsed,
+                "Slow database serialisation"
+            );
+        }
+
+        Ok(bytes)
+    }
+
     pub async fn save(&self) -> color_eyre::Result<()> {
+        //held until everything is written, so that `remove_db` can't remove a database that is about to be written back out
+        let _saving = self.save_lock.lock().await;
         let mut names = vec![];
 
-        for (name, db) in self.dbs.lock().await.iter() {
+        //serialise everything up-front so we don't hold the lock whilst writing to disk
+        let dbs = self.dbs.read().await;
+        let expiries = self.expiries.read().await;
+
+        //keys with a TTL are never saved, as their `Instant`s wouldn't survive a restart
+        let serialised = dbs
+            .iter()
+            .map(|(name, db)| {
+                let bytes = match expiries.get(name) {
+                    Some(expiries) => self.ser_db(
+                        name,
+                        &db.iter()
+                            .filter(|(k, _)| !expiries.keys.contains_key(k.as_str()))
+                            .map(|(k, v)| (k.clone(), v.clone()))
+                            .collect(),
+                    ),
+                    None => self.ser_db(name, db),
+                };
+                bytes.map(|bytes| (name.clone(), bytes))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        drop(expiries);
+        drop(dbs);
+
+        for (name, bytes) in serialised {
             let file_name = self.base_location.join(format!("{name}.sdb"));
-            let bytes = db.ser()?;
 
-            if let Err(e) = write_to_file(&bytes, file_name, &self.base_location).await {
+            if let Err(e) = write_to_file(&bytes, &file_name, &self.base_location).await {
                 error!(?e, "Error writing out database");
             } else {
-                names.push(Value::String(name.to_string()));
+                names.push(Value::String(name));
             }
         }
 
@@ -324,16 +995,33 @@ impl SourisState {
 
         let location = self.base_location.join(META_DB_FILE_NAME);
         let meta = meta.ser()?;
-        write_to_file(&meta, location, &self.base_location).await
+        write_to_file(&meta, &location, &self.base_location).await
     }
 }
 
+///Undoes changes to a database, given the value each changed key had before (or `None` if it was added).
+fn undo(db: &mut Store, old_values: Vec<(String, Option<Value>)>) {
+    for (key, old) in old_values.into_iter().rev() {
+        match old {
+            Some(old) => db.insert(key, old),
+            None => db.remove(&key),
+        };
+    }
+}
+
+///Atomically replaces the file at `path` with `bytes`.
+///
+/// The bytes are first written to a temporary file next to `path` and synced, which is then renamed over `path`. That way a crash part-way through leaves the old file intact rather than a truncated one.
 async fn write_to_file(
     bytes: &[u8],
-    path: impl AsRef<Path> + Debug,
+    path: &Path,
     base_location: impl AsRef<Path> + Debug,
 ) -> color_eyre::Result<()> {
-    let mut file = match File::create(&path).await {
+    let mut tmp_path = path.as_os_str().to_owned();
+    tmp_path.push(".tmp");
+    let tmp_path = PathBuf::from(tmp_path);
+
+    let mut file = match File::create(&tmp_path).await {
         Ok(f) => f,
         Err(e) => {
             if e.kind() == ErrorKind::NotFound {
@@ -341,14 +1029,166 @@ async fn write_to_file(
                 trace!(?path, ?base_location, "Unable to find folder, creating");
 
                 create_dir_all(&base_location).await?;
-                File::create(path).await?
+                File::create(&tmp_path).await?
             } else {
                 return Err(e.into());
             }
         }
     };
 
-    file.write_all(bytes).await?;
+    let written = async {
+        file.write_all(bytes).await?;
+        file.sync_all().await?;
+        drop(file);
+        rename(&tmp_path, path).await
+    }
+    .await;
+
+    if let Err(e) = written {
+        if let Err(e) = remove_file(&tmp_path).await {
+            warn!(?e, ?tmp_path, "Unable to remove temporary file");
+        }
+        return Err(e).with_context(|| format!("trying to write to {}", path.display()));
+    }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{SourisError, SourisState};
+    use crate::v1_routes::value::KeyAndDb;
+    use axum::http::StatusCode;
+    use moka::future::Cache;
+    use sourisdb::{store::Store, values::Value};
+    use std::{collections::HashMap, sync::Arc, time::Duration};
+    use tokio::sync::{Mutex, RwLock};
+
+    fn state_with(dbs: HashMap<String, Store>, max_db_bytes: Option<usize>) -> SourisState {
+        SourisState {
+            base_location: std::env::temp_dir().join("sourisd-state-tests"),
+            dbs: Arc::new(RwLock::new(dbs)),
+            db_cache: Cache::new(200),
+            max_db_bytes,
+            expiries: Arc::new(RwLock::new(HashMap::new())),
+            save_lock: Arc::new(Mutex::new(())),
+            save_interval: Duration::from_secs(10),
+            max_body_bytes: usize::MAX,
+            slow_ser_threshold: Duration::MAX,
+        }
+    }
+
+    fn key(key: &str) -> KeyAndDb {
+        KeyAndDb {
+            db_name: "db".into(),
+            key: key.into(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_quota_is_checked_when_an_insert_changes_the_huffman_tree() {
+        //strings of only two characters get one-bit huffman codes, until something adds more characters
+        let mut db = Store::default();
+        let mut seed = 0x2545_f491_u32;
+        for i in 0..200 {
+            let s: String = (0..32)
+                .map(|_| {
+                    seed ^= seed << 13;
+                    seed ^= seed >> 17;
+                    seed ^= seed << 5;
+                    if seed & 1 == 0 {
+                        'a'
+                    } else {
+                        'b'
+                    }
+                })
+                .collect();
+            db.insert(format!("k{i}"), Value::String(s));
+        }
+
+        let ser_len = |db: &Store| {
+            let mut bytes = vec![];
+            db.ser_to_writer(&mut bytes).unwrap();
+            bytes.len()
+        };
+        let new_value = Value::String("The quick brown fox jumps over the lazy dog!".into());
+        let entry_size = Value::String("new".into()).ser(None).len() + new_value.ser(None).len();
+        let before = ser_len(&db);
+        //room for the new entry at its own size, but not for the rest of the strings getting longer codes
+        let state = state_with(
+            HashMap::from([("db".to_string(), db.clone())]),
+            Some(before + entry_size),
+        );
+        let Err(SourisError::QuotaExceeded {
+            current, attempted, ..
+        }) = state
+            .add_key_value_pair(key("new"), new_value.clone(), None)
+            .await
+        else {
+            panic!("insert which changes the tree should go over the quota");
+        };
+        assert_eq!(current, before);
+        assert!(attempted > before + entry_size);
+        assert_eq!(state.dbs.read().await.get("db"), Some(&db));
+
+        let Err(SourisError::QuotaExceeded { .. }) = state
+            .add_key_value_pairs(
+                "db".into(),
+                false,
+                true,
+                Store::from_iter([("new".to_string(), new_value)]),
+            )
+            .await
+        else {
+            panic!("bulk insert which changes the tree should go over the quota");
+        };
+        assert_eq!(state.dbs.read().await.get("db"), Some(&db));
+
+        assert_eq!(
+            state
+                .add_key_value_pair(key("fits"), Value::String("ab".into()), None)
+                .await
+                .unwrap(),
+            StatusCode::CREATED
+        );
+        assert_eq!(state.dbs.read().await["db"].len(), 201);
+    }
+
+    #[tokio::test]
+    async fn test_export_and_import_keep_ttls() {
+        let ttl = Duration::from_mins(1);
+        let state = state_with(HashMap::new(), None);
+        state
+            .add_key_value_pair(key("forever"), Value::from(1_u8), None)
+            .await
+            .unwrap();
+        state
+            .add_key_value_pair(key("brief"), Value::from(2_u8), Some(ttl))
+            .await
+            .unwrap();
+
+        let archive = Store::deser(&state.export_all().await.unwrap()).unwrap();
+        let restored = state_with(HashMap::new(), None);
+        assert_eq!(restored.import_all(archive.clone()).await.unwrap(), 1);
+
+        assert_eq!(
+            restored.dbs.read().await.get("db"),
+            state.dbs.read().await.get("db")
+        );
+        let expiries = restored.expiries.read().await;
+        let expiries = &expiries["db"].keys;
+        assert_eq!(expiries.len(), 1);
+        let left = expiries["brief"].saturating_duration_since(std::time::Instant::now());
+        assert!(
+            left > Duration::from_mins(1) - Duration::from_secs(10)
+                && left <= Duration::from_mins(1)
+        );
+
+        //archives from before TTLs were exported don't have them at all
+        let mut old_archive = archive;
+        old_archive.remove(sourisdb::RESERVED_DB_NAME);
+        let restored = state_with(HashMap::new(), None);
+        assert_eq!(restored.import_all(old_archive).await.unwrap(), 1);
+        assert!(restored.expiries.read().await.is_empty());
+    }
+}
@@ -3,8 +3,13 @@ use axum::{
     http::StatusCode,
 };
 use serde::Deserialize;
+use std::time::Duration;
 
-use sourisdb::values::Value;
+use sourisdb::{
+    axum::{SourisStore, SourisValue},
+    store::Store,
+    values::Value,
+};
 
 use crate::{error::SourisError, v1_routes::state::SourisState};
 
@@ -14,14 +19,54 @@ pub struct KeyAndDb {
     pub key: String,
 }
 
+#[derive(Deserialize)]
+pub struct NewKv {
+    pub db_name: String,
+    pub key: String,
+    pub ttl_seconds: Option<u64>,
+}
+
 #[axum::debug_handler]
 pub async fn add_kv(
-    Query(kanddb): Query<KeyAndDb>,
+    Query(NewKv {
+        db_name,
+        key,
+        ttl_seconds,
+    }): Query<NewKv>,
+    State(state): State<SourisState>,
+    SourisValue(value): SourisValue,
+) -> Result<StatusCode, SourisError> {
+    info!(?value, ?ttl_seconds, "Adding value");
+    state
+        .add_key_value_pair(
+            KeyAndDb { db_name, key },
+            value,
+            ttl_seconds.map(Duration::from_secs),
+        )
+        .await
+}
+
+#[derive(Deserialize)]
+pub struct NewKvs {
+    pub db_name: String,
+    pub create_new: bool,
+    pub overwrite_existing: bool,
+}
+
+#[axum::debug_handler]
+pub async fn add_kvs(
+    Query(NewKvs {
+        db_name,
+        create_new,
+        overwrite_existing,
+    }): Query<NewKvs>,
     State(state): State<SourisState>,
-    value: Value,
-) -> StatusCode {
-    info!(?value, "Adding value");
-    state.add_key_value_pair(kanddb, value).await
+    SourisStore(kvs): SourisStore,
+) -> Result<(StatusCode, Store), SourisError> {
+    info!(count = kvs.len(), "Adding values");
+    state
+        .add_key_value_pairs(db_name, create_new, overwrite_existing, kvs)
+        .await
 }
 
 #[axum::debug_handler]
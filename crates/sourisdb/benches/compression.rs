@@ -1,9 +1,11 @@
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
 use sourisdb::{
     types::binary::{
+        dictionary::Dict,
         huffman::{huffman, un_huffman},
         lz::{lz, un_lz},
         rle::{rle, un_rle},
+        BinaryCompression, BinaryData,
     },
     utilities::cursor::Cursor,
 };
@@ -79,5 +81,60 @@ fn huff_and_un_huff(c: &mut Criterion) {
     });
 }
 
-criterion_group!(compression, rle_and_un_rle, lz_and_un_lz, huff_and_un_huff);
+fn json_fragments() -> Vec<BinaryData> {
+    (0..1_000)
+        .map(|i| {
+            BinaryData::from(format!(
+                r#"{{"id":{i},"username":"user_{i}","email":"user_{i}@example.com","is_active":{},"roles":["reader","writer"]}}"#,
+                i % 3 == 0
+            ))
+        })
+        .collect()
+}
+
+fn per_blob_and_dict(c: &mut Criterion) {
+    let fragments = json_fragments();
+    let dict = Dict::train(fragments.iter().map(|b| b.as_slice()), 4_096);
+
+    c.bench_function("train dict", |b| {
+        b.iter(|| {
+            let dict = Dict::train(fragments.iter().map(|b| b.as_slice()), 4_096);
+            black_box(dict);
+        })
+    });
+
+    c.bench_function("lz per-blob", |b| {
+        b.iter(|| {
+            for fragment in &fragments {
+                black_box(fragment.ser_with(BinaryCompression::LempelZiv));
+            }
+        })
+    });
+
+    c.bench_function("lz with dict", |b| {
+        b.iter(|| {
+            for fragment in &fragments {
+                black_box(fragment.ser_with_dict(&dict));
+            }
+        })
+    });
+
+    c.bench_function("un-lz with dict", |b| {
+        let encoded: Vec<Vec<u8>> = fragments.iter().map(|f| f.ser_with_dict(&dict)).collect();
+        b.iter(|| {
+            for bytes in &encoded {
+                let decoded = BinaryData::deser_with_dict(&mut Cursor::new(bytes), &dict).unwrap();
+                black_box(decoded);
+            }
+        })
+    });
+}
+
+criterion_group!(
+    compression,
+    rle_and_un_rle,
+    lz_and_un_lz,
+    huff_and_un_huff,
+    per_blob_and_dict
+);
 criterion_main!(compression);
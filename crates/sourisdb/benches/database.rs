@@ -5,11 +5,15 @@ const EXAMPLE_JSON: &str = include_str!("smallexampledata.json");
 
 fn ser_and_deser(c: &mut Criterion) {
     let json = serde_json::from_str(EXAMPLE_JSON).unwrap();
-    let example = Store::from_json(json);
+    let example = Store::from_json(json).unwrap();
     let sered = example.ser().unwrap();
 
     c.bench_function("serialise_store", |b| b.iter(|| black_box(example.ser())));
 
+    c.bench_function("serialise_store_uncompressed", |b| {
+        b.iter(|| black_box(example.ser_uncompressed()))
+    });
+
     c.bench_function("deserialise_store", |b| {
         b.iter(|| black_box(Store::deser(&sered)))
     });
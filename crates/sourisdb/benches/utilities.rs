@@ -30,6 +30,40 @@ fn en_de_code_beemovie(c: &mut Criterion) {
     });
 }
 
+fn en_de_code_bytes(c: &mut Criterion) {
+    let bytes = EXAMPLE_DATA.as_bytes();
+    let huff = Huffman::new_bytes(bytes).unwrap();
+    let data = huff.encode_bytes(bytes).unwrap();
+
+    c.bench_function("encode huffman bytes generic", |b| {
+        b.iter(|| {
+            let encoded = huff.encode(bytes.iter().copied()).unwrap();
+            black_box(encoded);
+        })
+    });
+
+    c.bench_function("encode huffman bytes fast", |b| {
+        b.iter(|| {
+            let encoded = huff.encode_bytes(bytes).unwrap();
+            black_box(encoded);
+        })
+    });
+
+    c.bench_function("decode huffman bytes generic", |b| {
+        b.iter(|| {
+            let decoded = huff.decode(data.clone()).unwrap();
+            black_box(decoded);
+        })
+    });
+
+    c.bench_function("decode huffman bytes fast", |b| {
+        b.iter(|| {
+            let decoded = huff.decode_bytes(&data).unwrap();
+            black_box(decoded);
+        })
+    });
+}
+
 fn ser_de_huffman(c: &mut Criterion) {
     c.bench_function("serialise huffman", |b| {
         let huff = Huffman::new_str(EXAMPLE_DATA).unwrap();
@@ -76,6 +110,6 @@ fn ser_de_bits(c: &mut Criterion) {
     });
 }
 
-criterion_group!(runtime, en_de_code_beemovie);
+criterion_group!(runtime, en_de_code_beemovie, en_de_code_bytes);
 criterion_group!(serde, ser_de_huffman, ser_de_bits);
 criterion_main!(runtime, serde);
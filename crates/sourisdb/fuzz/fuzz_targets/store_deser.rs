@@ -0,0 +1,10 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use sourisdb::store::Store;
+
+//`Store::deser` should only ever return an error on bad input - any panic, abort or runaway allocation is a bug.
+fuzz_target!(|data: &[u8]| {
+    let _ = Store::deser(data);
+    let _ = Store::deser_skip_unknown(data);
+});
@@ -66,7 +66,7 @@ impl<S: Send + Sync> FromRequest<S> for Value {
         let bytes = Bytes::from_request(req, state).await?;
         let val = match Value::deser(&mut Cursor::new(&bytes), None) {
             Ok(v) => v,
-            Err(e) => return Err(SourisRejection::Value(e, true)),
+            Err(e) => return Err(SourisRejection::Value(e, false)),
         };
         Ok(val)
     }
@@ -89,12 +89,42 @@ impl<S: Send + Sync> FromRequest<S> for Store {
         let bytes = Bytes::from_request(req, state).await?;
         let val = match Store::deser(bytes.as_ref()) {
             Ok(v) => v,
-            Err(e) => return Err(SourisRejection::Store(e, true)),
+            Err(e) => return Err(SourisRejection::Store(e, false)),
         };
         Ok(val)
     }
 }
 
+///An extractor for a [`Store`] request body, for when it's clearer to name the extractor in a handler's arguments.
+///
+/// This behaves identically to extracting a [`Store`] directly - if the body can't be deserialised, a [`SourisRejection`] is returned which responds with [`StatusCode::BAD_REQUEST`] and a description of the error.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourisStore(pub Store);
+
+#[async_trait]
+impl<S: Send + Sync> FromRequest<S> for SourisStore {
+    type Rejection = SourisRejection;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        Store::from_request(req, state).await.map(Self)
+    }
+}
+
+///An extractor for a single [`Value`] request body, for when it's clearer to name the extractor in a handler's arguments.
+///
+/// This behaves identically to extracting a [`Value`] directly - if the body can't be deserialised, a [`SourisRejection`] is returned which responds with [`StatusCode::BAD_REQUEST`] and a description of the error.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourisValue(pub Value);
+
+#[async_trait]
+impl<S: Send + Sync> FromRequest<S> for SourisValue {
+    type Rejection = SourisRejection;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        Value::from_request(req, state).await.map(Self)
+    }
+}
+
 ///Error struct for if there is a failure de/ser-ing a `Store` using `FromRequest` or `IntoResponse`
 #[non_exhaustive]
 pub enum SourisRejection {
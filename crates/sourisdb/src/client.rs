@@ -5,6 +5,8 @@
 //! The methods available on both clients are identical, save the async ones being async. The [`ClientError`] type changes based off which features are enabled to hold the error types for the HTTP library.
 //!
 //! The sync client is backed by [`ureq`] and the async client by [`reqwest`].
+//!
+//! With the `gzip` feature, both clients send `Accept-Encoding: gzip` and transparently decompress gzipped responses, which `sourisd` sends by default. This mostly helps the JSON endpoints on slow links, as stores are already compressed.
 
 use crate::{store::StoreSerError, values::ValueSerError};
 use core::fmt::{Display, Formatter};
@@ -13,7 +15,7 @@ use http::StatusCode;
 #[cfg(feature = "async_client")]
 pub use async_client::AsyncClient;
 #[cfg(feature = "sync_client")]
-pub use sync_client::SyncClient;
+pub use sync_client::{SyncClient, SyncClientBuilder};
 
 #[cfg(feature = "async_client")]
 mod async_client;
@@ -45,6 +47,63 @@ pub enum ClientError {
     ServerNotHealthy(StatusCode),
     ///An error occurred with `serde_json`.
     SerdeJson(serde_json::Error),
+    ///A request took longer than the configured timeout - this could be either connecting to the server or the whole request. These are usually safe to retry.
+    Timeout,
+    ///A header the server always sends was missing or couldn't be parsed.
+    InvalidHeader(&'static str),
+}
+
+impl ClientError {
+    ///Whether the error is likely to be transient, and so whether the request is worth retrying.
+    ///
+    /// This is `true` for timeouts, connection issues and the `502 Bad Gateway`, `503 Service Unavailable` and `504 Gateway Timeout` status codes. It is `false` for every other status code (including all `4xx` codes) and for any errors de/ser-ialising data, as retrying won't change those.
+    ///
+    /// ```rust
+    /// use http::StatusCode;
+    /// use sourisdb::client::ClientError;
+    ///
+    /// assert!(ClientError::HttpErrorCode(StatusCode::SERVICE_UNAVAILABLE).is_retryable());
+    /// assert!(!ClientError::HttpErrorCode(StatusCode::BAD_REQUEST).is_retryable());
+    /// ```
+    #[must_use]
+    pub fn is_retryable(&self) -> bool {
+        fn retryable_status(sc: StatusCode) -> bool {
+            matches!(
+                sc,
+                StatusCode::BAD_GATEWAY
+                    | StatusCode::SERVICE_UNAVAILABLE
+                    | StatusCode::GATEWAY_TIMEOUT
+            )
+        }
+
+        match self {
+            #[cfg(feature = "sync_client")]
+            Self::Ureq(u) => matches!(
+                u.kind(),
+                ureq::ErrorKind::Dns
+                    | ureq::ErrorKind::ConnectionFailed
+                    | ureq::ErrorKind::Io
+                    | ureq::ErrorKind::ProxyConnect
+            ),
+            #[cfg(feature = "async_client")]
+            Self::Reqwest(r) => r.is_connect() || r.status().is_some_and(retryable_status),
+            #[cfg(feature = "sync_client")]
+            Self::IO(e) => matches!(
+                e.kind(),
+                std::io::ErrorKind::ConnectionRefused
+                    | std::io::ErrorKind::ConnectionReset
+                    | std::io::ErrorKind::ConnectionAborted
+                    | std::io::ErrorKind::BrokenPipe
+                    | std::io::ErrorKind::UnexpectedEof
+                    | std::io::ErrorKind::Interrupted
+            ),
+            Self::HttpErrorCode(sc) | Self::ServerNotHealthy(sc) => retryable_status(*sc),
+            Self::Timeout => true,
+            Self::Store(_) | Self::Value(_) | Self::SerdeJson(_) | Self::InvalidHeader(_) => false,
+            #[cfg(feature = "sync_client")]
+            Self::InvalidStatusCode(_) => false,
+        }
+    }
 }
 
 impl Display for ClientError {
@@ -66,6 +125,8 @@ impl Display for ClientError {
             ),
             Self::SerdeJson(e) => write!(f, "Tried to parse JSON and failed: {e}"),
             Self::Value(e) => write!(f, "Error with value: {e}"),
+            Self::Timeout => write!(f, "Request timed out"),
+            Self::InvalidHeader(h) => write!(f, "Header `{h}` was missing or invalid"),
         }
     }
 }
@@ -84,14 +145,28 @@ impl From<ureq::Error> for ClientError {
                 Ok(sc) => ClientError::HttpErrorCode(sc),
                 Err(e) => ClientError::InvalidStatusCode(e),
             },
-            ureq::Error::Transport(transport_error) => ClientError::Ureq(transport_error),
+            ureq::Error::Transport(transport_error) => {
+                let timed_out = std::error::Error::source(&transport_error)
+                    .and_then(|e| e.downcast_ref::<std::io::Error>())
+                    .is_some_and(|e| e.kind() == std::io::ErrorKind::TimedOut);
+
+                if timed_out {
+                    ClientError::Timeout
+                } else {
+                    ClientError::Ureq(transport_error)
+                }
+            }
         }
     }
 }
 #[cfg(feature = "async_client")]
 impl From<reqwest::Error> for ClientError {
     fn from(value: reqwest::Error) -> Self {
-        Self::Reqwest(value)
+        if value.is_timeout() {
+            Self::Timeout
+        } else {
+            Self::Reqwest(value)
+        }
     }
 }
 impl From<StoreSerError> for ClientError {
@@ -102,7 +177,11 @@ impl From<StoreSerError> for ClientError {
 #[cfg(feature = "sync_client")]
 impl From<std::io::Error> for ClientError {
     fn from(value: std::io::Error) -> Self {
-        Self::IO(value)
+        if value.kind() == std::io::ErrorKind::TimedOut {
+            Self::Timeout
+        } else {
+            Self::IO(value)
+        }
     }
 }
 #[cfg(feature = "sync_client")]
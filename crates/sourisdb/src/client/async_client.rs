@@ -11,13 +11,21 @@
 //! }
 //! ```
 
-use crate::{client::ClientError, store::Store, values::Value};
+use crate::{
+    client::ClientError,
+    store::{stream::EntryStreamDecoder, CreationResult, DbStats, Store, StoreSerError},
+    utilities::cursor::Cursor,
+    values::Value,
+    TOTAL_COUNT_HEADER,
+};
 use alloc::{
     format,
     string::{String, ToString},
     vec::Vec,
 };
 use core::fmt::Display;
+use futures_util::{stream, Stream, StreamExt};
+use hashbrown::HashMap;
 use http::StatusCode;
 use reqwest::{Client, Response};
 
@@ -39,28 +47,37 @@ impl AsyncClient {
         let path = path.to_string();
         let client = Client::new();
 
+        let status = Self::healthcheck_status(&client, &path, port).await?;
+        if status != StatusCode::OK {
+            return Err(ClientError::ServerNotHealthy(status));
+        }
+
+        Ok(Self { path, port, client })
+    }
+
+    ///Checks whether the server is still healthy, which is useful for long-lived clients (eg. for a readiness probe).
+    ///
+    /// Returns `true` if the server responded with [`StatusCode::OK`], and `false` if it responded with anything else.
+    ///
+    /// ## Errors
+    /// - [`reqwest::Error`] if we couldn't get a response from the server at all.
+    pub async fn healthcheck(&self) -> Result<bool, ClientError> {
+        Ok(Self::healthcheck_status(&self.client, &self.path, self.port).await? == StatusCode::OK)
+    }
+
+    async fn healthcheck_status(
+        client: &Client,
+        path: &str,
+        port: u32,
+    ) -> Result<StatusCode, ClientError> {
         match client
-            .get(&format!("http://{path}:{port}/healthcheck"))
+            .get(format!("http://{path}:{port}/healthcheck"))
             .send()
             .await
         {
-            Ok(rsp) => {
-                if rsp.status() != StatusCode::OK {
-                    return Err(ClientError::ServerNotHealthy(rsp.status()));
-                }
-            }
-            Err(e) => {
-                if let Some(status) = e.status() {
-                    if status != StatusCode::OK {
-                        return Err(ClientError::ServerNotHealthy(status));
-                    }
-                } else {
-                    return Err(ClientError::Reqwest(e));
-                }
-            }
-        };
-
-        Ok(Self { path, port, client })
+            Ok(rsp) => Ok(rsp.status()),
+            Err(e) => e.status().ok_or(ClientError::Reqwest(e)),
+        }
     }
 
     ///Get the names of all the databases present in the instance.
@@ -81,6 +98,75 @@ impl AsyncClient {
             .await?)
     }
 
+    ///Get one page of the names of the databases present in the instance, along with the total number of databases.
+    ///
+    /// Names are sorted, so a consistent set of pages can be got by increasing `offset` by `limit` each time until it reaches the total.
+    ///
+    /// ## Errors
+    /// - [`reqwest::Error`] if there is an error with the HTTP request, or we cannot get the raw bytes out
+    /// - [`ClientError::HttpErrorCode`] if an HTTP Error status code is encountered.
+    /// - [`ClientError::InvalidHeader`] if the server didn't say how many databases there are in total.
+    pub async fn get_all_dbs_paged(
+        &self,
+        offset: usize,
+        limit: usize,
+    ) -> Result<(Vec<String>, usize), ClientError> {
+        let rsp = self
+            .client
+            .get(format!(
+                "http://{}:{}/v1/get_all_db_names",
+                self.path, self.port
+            ))
+            .query(&[("offset", offset), ("limit", limit)])
+            .send()
+            .await?;
+        rsp.error_for_status_to_client_error()?;
+
+        let total = rsp
+            .headers()
+            .get(TOTAL_COUNT_HEADER)
+            .and_then(|h| h.to_str().ok())
+            .and_then(|h| h.parse().ok())
+            .ok_or(ClientError::InvalidHeader(TOTAL_COUNT_HEADER))?;
+        Ok((rsp.json().await?, total))
+    }
+
+    ///Gets the keys in a given database, sorted, without having to transfer any of the values.
+    ///
+    /// ## Errors
+    /// - [`reqwest::Error`] if there is an error with the HTTP request, or we cannot get the raw bytes out
+    /// - [`ClientError::HttpErrorCode`] if an HTTP Error status code is encountered - eg. [`StatusCode::BAD_REQUEST`] if the database doesn't exist.
+    pub async fn get_keys(&self, db_name: &str) -> Result<Vec<String>, ClientError> {
+        let rsp = self
+            .client
+            .get(format!("http://{}:{}/v1/get_keys", self.path, self.port))
+            .query(&[("db_name", db_name)])
+            .send()
+            .await?;
+        rsp.error_for_status_to_client_error()?;
+
+        Ok(rsp.json().await?)
+    }
+
+    ///Gets the [`DbStats`] for a given database, without having to transfer the whole database.
+    ///
+    /// ## Errors
+    /// - [`reqwest::Error`] if there is an error with the HTTP request, or we cannot get the raw bytes out
+    /// - [`ClientError::HttpErrorCode`] if an HTTP Error status code is encountered - eg. [`StatusCode::BAD_REQUEST`] if the database doesn't exist.
+    /// - [`ClientError::SerdeJson`] or [`ClientError::Store`] if the response is invalid.
+    pub async fn db_stats(&self, db_name: &str) -> Result<DbStats, ClientError> {
+        let rsp = self
+            .client
+            .get(&format!("http://{}:{}/v1/db_stats", self.path, self.port))
+            .query(&[("db_name", db_name)])
+            .send()
+            .await?;
+        rsp.error_for_status_to_client_error()?;
+
+        let body = rsp.bytes().await?;
+        Ok(DbStats::from_json(&serde_json::from_slice(&body)?)?)
+    }
+
     ///Creates a new database in the connected instance with the given name.
     ///
     /// ## `overwrite_existing`
@@ -120,7 +206,7 @@ impl AsyncClient {
         })
     }
 
-    /// Gets a given store by name. If the store doesn't exist, [`ClientError::HttpErrorCode`] will be returned with a code of [`StatusCode::NOT_FOUND`].
+    /// Gets a given store by name. If the store doesn't exist, [`ClientError::HttpErrorCode`] will be returned with a code of [`StatusCode::BAD_REQUEST`].
     ///
     /// ## Errors
     /// - `[ClientError::HttpErrorCode`] if the database isn't found or another error occurs with the HTTP request.
@@ -130,7 +216,106 @@ impl AsyncClient {
         let rsp = self
             .client
             .get(&format!("http://{}:{}/v1/get_db", self.path, self.port))
-            .query(&["db_name", db_name])
+            .query(&[("db_name", db_name)])
+            .send()
+            .await?;
+        rsp.error_for_status_to_client_error()?;
+        let bytes = rsp.bytes().await?;
+        Ok(Store::deser(bytes.as_ref())?)
+    }
+
+    ///Gets a given store by name one entry at a time, so that huge stores can be processed as they arrive without ever holding the whole store in memory.
+    ///
+    /// The entries come back sorted by key. The stream stops after the first error - if the connection is cut off part of the way through, the last item will be [`ClientError::Store`] with [`StoreSerError::NotEnoughBytes`].
+    ///
+    /// ```rust
+    /// use futures_util::TryStreamExt;
+    /// use sourisdb::client::{AsyncClient, ClientError};
+    ///
+    /// async fn count_keys(client: &AsyncClient) -> Result<usize, ClientError> {
+    ///     client
+    ///         .stream_store("my_db")
+    ///         .await?
+    ///         .try_fold(0, |count, _| async move { Ok(count + 1) })
+    ///         .await
+    /// }
+    /// ```
+    ///
+    /// ## Errors
+    /// - [`ClientError::HttpErrorCode`] if the database isn't found or another error occurs with the HTTP request.
+    /// - [`reqwest::Error`] if a reqwest error occurs.
+    ///
+    /// Each item can also be:
+    /// - [`reqwest::Error`] if a reqwest error occurs while reading the body.
+    /// - [`crate::store::StoreSerError`] if an entry cannot be deserialised, or the body ends too early.
+    pub async fn stream_store(
+        &self,
+        db_name: &str,
+    ) -> Result<impl Stream<Item = Result<(String, Value), ClientError>>, ClientError> {
+        let rsp = self
+            .client
+            .get(format!("http://{}:{}/v1/stream_db", self.path, self.port))
+            .query(&[("db_name", db_name)])
+            .send()
+            .await?;
+        rsp.error_for_status_to_client_error()?;
+
+        Ok(stream::try_unfold(
+            (rsp, EntryStreamDecoder::default()),
+            |(mut rsp, mut decoder)| async move {
+                loop {
+                    if let Some(entry) = decoder.next_entry()? {
+                        return Ok(Some((entry, (rsp, decoder))));
+                    }
+                    if decoder.is_finished() {
+                        return Ok(None);
+                    }
+
+                    let Some(chunk) = rsp.chunk().await? else {
+                        return Err(ClientError::Store(StoreSerError::NotEnoughBytes));
+                    };
+                    decoder.push(&chunk);
+                }
+            },
+        ))
+    }
+
+    ///Gets several stores by name concurrently, with at most `concurrency_limit` requests in flight at once so that the server doesn't get flooded.
+    ///
+    /// The results come back in the same order as `names`, each alongside its name. Databases that don't exist give `Ok(None)`, and any other failures only affect the result for that database.
+    ///
+    /// A `concurrency_limit` of `0` is treated as `1`.
+    pub async fn get_stores(
+        &self,
+        names: &[&str],
+        concurrency_limit: usize,
+    ) -> Vec<(String, Result<Option<Store>, ClientError>)> {
+        stream::iter(names)
+            .map(|&name| async move {
+                let store = match self.get_store(name).await {
+                    Ok(store) => Ok(Some(store)),
+                    Err(ClientError::HttpErrorCode(StatusCode::BAD_REQUEST)) => Ok(None),
+                    Err(e) => Err(e),
+                };
+                (name.to_string(), store)
+            })
+            .buffered(concurrency_limit.max(1))
+            .collect()
+            .await
+    }
+
+    ///Gets an archive of every database in the instance, which is a [`Store`] mapping each database name to the serialised bytes of that database in a [`Value::Binary`]. If any keys have a TTL, how long each had left is kept under [`crate::RESERVED_DB_NAME`], and they get that long again when the archive is imported.
+    ///
+    /// To back up the instance, the archive can be serialised using [`Store::ser`] and written to a file, and then restored using [`AsyncClient::import_all`].
+    ///
+    /// ## Errors
+    /// - [`reqwest::Error`] if a reqwest error occurs or the bytes cannot be obtained.
+    /// - [`ClientError::HttpErrorCode`] if an HTTP Error status code is encountered.
+    /// - [`crate::store::StoreSerError`] if the archive cannot be deserialised from the bytes.
+    pub async fn export_all(&self) -> Result<Store, ClientError> {
+        let rsp = self
+            .client
+            .get(format!("http://{}:{}/v1/export_all", self.path, self.port))
             .send()
             .await?;
         rsp.error_for_status_to_client_error()?;
@@ -138,6 +323,26 @@ impl AsyncClient {
         Ok(Store::deser(bytes.as_ref())?)
     }
 
+    ///Restores every database in an archive from [`AsyncClient::export_all`], overwriting any databases with the same names. Databases which aren't in the archive are left alone.
+    ///
+    /// The server checks every database in the archive before restoring any of them, so either all or none are restored.
+    ///
+    /// ## Errors
+    /// - [`crate::store::StoreSerError`] if we cannot serialise the archive.
+    /// - [`reqwest::Error`] if a reqwest error occurs.
+    /// - [`ClientError::HttpErrorCode`] if an HTTP Error status code is encountered - eg. [`StatusCode::BAD_REQUEST`] if any of the names aren't valid.
+    pub async fn import_all(&self, archive: &Store) -> Result<(), ClientError> {
+        let archive = archive.ser()?;
+
+        self.client
+            .put(format!("http://{}:{}/v1/import_all", self.path, self.port))
+            .body(archive)
+            .send()
+            .await?
+            .error_for_status_to_client_error()?;
+        Ok(())
+    }
+
     ///Adds a new database and immediately inserts the contents of the [`Store`] into it.
     ///
     /// If `overwrite_existing` is true or the store already exists, the server will now have one instance of the provided store with the provided contents.
@@ -208,6 +413,73 @@ impl AsyncClient {
         })
     }
 
+    ///Adds all of the entries in the given [`Store`] to the given database using one request.
+    ///
+    /// If `create_new` is false and the database doesn't exist, [`ClientError::HttpErrorCode`] will be returned with a code of [`StatusCode::BAD_REQUEST`].
+    ///
+    /// If `overwrite_existing` is false, then any keys which already exist in the database will be left alone.
+    ///
+    /// Returns what happened to each key as a [`CreationResult`].
+    ///
+    /// # Errors
+    /// - [`crate::store::StoreSerError`] if we cannot serialise the provided `Store` or deserialise the results.
+    /// - [`reqwest::Error`] if a reqwest error occurs or the bytes cannot be obtained.
+    /// - [`ClientError::HttpErrorCode`] if an HTTP Error status code is encountered.
+    pub async fn add_entries_to_db(
+        &self,
+        database_name: &str,
+        create_new: bool,
+        overwrite_existing: bool,
+        entries: &Store,
+    ) -> Result<HashMap<String, CreationResult>, ClientError> {
+        let entries = entries.ser()?;
+        let rsp = self
+            .client
+            .put(&format!("http://{}:{}/v1/add_kvs", self.path, self.port))
+            .query(&[
+                ("db_name", database_name),
+                ("create_new", if create_new { "true" } else { "false" }),
+                (
+                    "overwrite_existing",
+                    if overwrite_existing { "true" } else { "false" },
+                ),
+            ])
+            .body(entries)
+            .send()
+            .await?;
+        rsp.error_for_status_to_client_error()?;
+
+        let bytes = rsp.bytes().await?;
+        let mut results = Store::deser(bytes.as_ref())?;
+        Ok(results
+            .drain()
+            .map(|(k, v)| CreationResult::try_from(v).map(|cr| (k, cr)))
+            .collect::<Result<_, _>>()?)
+    }
+
+    ///Gets the value stored under `key` in the given database, or `None` if either the database or the key doesn't exist.
+    ///
+    /// # Errors
+    /// - [`reqwest::Error`] if a reqwest error occurs or the bytes cannot be obtained.
+    /// - [`ClientError::HttpErrorCode`] if an HTTP Error status code other than [`StatusCode::BAD_REQUEST`] is encountered.
+    /// - [`crate::values::ValueSerError`] if the value cannot be deserialised from the bytes.
+    pub async fn get_value(&self, db_name: &str, key: &str) -> Result<Option<Value>, ClientError> {
+        let rsp = self
+            .client
+            .get(&format!("http://{}:{}/v1/get_value", self.path, self.port))
+            .query(&[("db_name", db_name), ("key", key)])
+            .send()
+            .await?;
+        match rsp.error_for_status_to_client_error() {
+            Ok(_) => {}
+            Err(ClientError::HttpErrorCode(StatusCode::BAD_REQUEST)) => return Ok(None),
+            Err(e) => return Err(e),
+        }
+
+        let bytes = rsp.bytes().await?;
+        Ok(Some(Value::deser(&mut Cursor::new(&bytes.as_ref()), None)?))
+    }
+
     ///Removes the entry with the given key from the database.
     ///
     /// # Errors
@@ -229,19 +501,24 @@ impl AsyncClient {
 
     ///Removes a given database.
     ///
-    /// NB: A 404 code is returned by the daemon if the database cannot be found, which will show up as [`ClientError::HttpErrorCode`].
+    /// Returns `true` if the database was removed, and `false` if it didn't exist.
     ///
     /// # Errors
     /// - [`reqwest::Error`] if a reqwest error occurs or the bytes cannot be obtained.
-    /// - [`ClientError::HttpErrorCode`] if an HTTP Error status code is encountered.
-    pub async fn remove_db(&self, database_name: &str) -> Result<(), ClientError> {
-        self.client
+    /// - [`ClientError::HttpErrorCode`] if an HTTP Error status code other than [`StatusCode::BAD_REQUEST`] is encountered.
+    pub async fn remove_db(&self, database_name: &str) -> Result<bool, ClientError> {
+        match self
+            .client
             .post(&format!("http://{}:{}/v1/rm_db", self.path, self.port))
             .query(&[("db_name", database_name)])
             .send()
             .await?
-            .error_for_status_to_client_error()?;
-        Ok(())
+            .error_for_status_to_client_error()
+        {
+            Ok(_) => Ok(true),
+            Err(ClientError::HttpErrorCode(StatusCode::BAD_REQUEST)) => Ok(false),
+            Err(e) => Err(e),
+        }
     }
 }
 
@@ -1,10 +1,31 @@
-use core::fmt::Display;
+//! `sync_client` provides a blocking client for use with a `sourisd` client, with the same methods as the [`crate::client::AsyncClient`].
+//!
+//! When you create a new client using [`SyncClient::new`] or a [`SyncClientBuilder`], it polls the database's healthcheck endpoint to confirm that the database is running.
+//!
+//! ```rust,no_run
+//! use sourisdb::client::{ClientError, SyncClient};
+//!
+//! fn get_all_database_names_from_localhost () -> Result<Vec<String>, ClientError> {
+//!     let client = SyncClient::new("localhost", 7687)?;
+//!     client.get_all_dbs()
+//! }
+//! ```
 
+use core::{fmt::Display, time::Duration};
+
+use hashbrown::HashMap;
 use http::StatusCode;
-use ureq::{Agent, Response};
+use ureq::{Agent, AgentBuilder, Response};
 
-use crate::{client::ClientError, store::Store, values::Value};
+use crate::{
+    client::ClientError,
+    store::{CreationResult, DbStats, Store},
+    utilities::cursor::Cursor,
+    values::Value,
+    TOTAL_COUNT_HEADER,
+};
 
+///A client for interacting with `sourisd` synchronously.
 #[derive(Debug, Clone)]
 pub struct SyncClient {
     //TODO: option to change protocol
@@ -13,23 +34,135 @@ pub struct SyncClient {
     agent: Agent, //also internally arc-ed, so easy to clone
 }
 
-impl SyncClient {
+///A builder for a [`SyncClient`] which allows tuning the connection pool and timeouts of the underlying [`Agent`].
+///
+/// Anything which isn't set uses the [`ureq`] defaults.
+#[derive(Debug, Clone)]
+pub struct SyncClientBuilder {
+    path: String,
+    port: u32,
+    pool_max_idle_per_host: Option<usize>,
+    connect_timeout: Option<Duration>,
+    request_timeout: Option<Duration>,
+}
+
+impl SyncClientBuilder {
+    ///Creates a new builder for a server at the given path and port.
+    pub fn new(path: impl Display, port: u32) -> Self {
+        Self {
+            path: path.to_string(),
+            port,
+            pool_max_idle_per_host: None,
+            connect_timeout: None,
+            request_timeout: None,
+        }
+    }
+
+    ///Sets the maximum number of idle connections to keep alive for the server.
+    #[must_use]
+    pub fn pool_max_idle_per_host(mut self, max: usize) -> Self {
+        self.pool_max_idle_per_host = Some(max);
+        self
+    }
+
+    ///Sets the timeout for connecting to the server.
+    #[must_use]
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    ///Sets the timeout for whole requests, including connecting and reading the body.
+    #[must_use]
+    pub fn request_timeout(mut self, timeout: Duration) -> Self {
+        self.request_timeout = Some(timeout);
+        self
+    }
+
+    ///Builds the [`SyncClient`], checking that the server is healthy.
+    ///
+    /// # Errors
+    /// - [`ClientError::ServerNotHealthy`] if the healthcheck doesn't return `200 OK`.
+    /// - [`ClientError::Timeout`] if the healthcheck times out.
+    /// - [`ClientError::Ureq`] if there are any other issues making the request.
     #[allow(clippy::result_large_err)]
-    pub fn new(path: impl Display, port: u32) -> Result<Self, ClientError> {
-        let path = path.to_string();
-        let agent = Agent::new();
+    pub fn build(self) -> Result<SyncClient, ClientError> {
+        let Self {
+            path,
+            port,
+            pool_max_idle_per_host,
+            connect_timeout,
+            request_timeout,
+        } = self;
 
-        let rsp = agent
-            .get(&format!("http://{path}:{port}/healthcheck"))
-            .call()?;
-        let status = rsp.status_code()?;
+        let mut agent = AgentBuilder::new();
+        if let Some(max) = pool_max_idle_per_host {
+            agent = agent.max_idle_connections_per_host(max);
+        }
+        if let Some(timeout) = connect_timeout {
+            agent = agent.timeout_connect(timeout);
+        }
+        if let Some(timeout) = request_timeout {
+            agent = agent.timeout(timeout);
+        }
+        let agent = agent.build();
+
+        let status = SyncClient::healthcheck_status(&agent, &path, port)?;
         if status != StatusCode::OK {
             return Err(ClientError::ServerNotHealthy(status));
         }
 
-        Ok(Self { path, port, agent })
+        Ok(SyncClient { path, port, agent })
+    }
+}
+
+impl SyncClient {
+    ///Create a new synchronous client using the provided path and port, with the default [`ureq`] settings - use [`SyncClient::builder`] to change them.
+    ///
+    /// # Errors
+    /// - [`ClientError::ServerNotHealthy`] if the healthcheck doesn't return `200 OK`.
+    /// - [`ClientError::Timeout`] if the healthcheck times out.
+    /// - [`ClientError::Ureq`] if there are any other issues making the request.
+    #[allow(clippy::result_large_err)]
+    pub fn new(path: impl Display, port: u32) -> Result<Self, ClientError> {
+        SyncClientBuilder::new(path, port).build()
+    }
+
+    ///Creates a [`SyncClientBuilder`] to configure pooling and timeouts before connecting.
+    pub fn builder(path: impl Display, port: u32) -> SyncClientBuilder {
+        SyncClientBuilder::new(path, port)
+    }
+
+    ///Checks whether the server is still healthy, which is useful for long-lived clients (eg. for a readiness probe).
+    ///
+    /// Returns `true` if the server responded with `200 OK`, and `false` if it responded with anything else.
+    ///
+    /// # Errors
+    /// - [`ClientError::Timeout`] if the healthcheck times out.
+    /// - [`ClientError::Ureq`] if we couldn't get a response from the server at all.
+    #[allow(clippy::result_large_err)]
+    pub fn healthcheck(&self) -> Result<bool, ClientError> {
+        Ok(Self::healthcheck_status(&self.agent, &self.path, self.port)? == StatusCode::OK)
+    }
+
+    #[allow(clippy::result_large_err)]
+    fn healthcheck_status(agent: &Agent, path: &str, port: u32) -> Result<StatusCode, ClientError> {
+        match agent
+            .get(&format!("http://{path}:{port}/healthcheck"))
+            .call()
+        {
+            Ok(rsp) => rsp.status_code(),
+            Err(ureq::Error::Status(status, _)) => Ok(StatusCode::try_from(status)?),
+            Err(e) => Err(e.into()),
+        }
     }
 
+    ///Get the names of all the databases present in the instance.
+    ///
+    /// # Errors
+    /// - [`ClientError::Ureq`] or [`ClientError::IO`] if there is an error with the HTTP request, or we cannot get the raw bytes out.
+    /// - [`ClientError::HttpErrorCode`] if an HTTP Error status code is encountered.
+    /// - [`ClientError::SerdeJson`] if the response is invalid.
     #[allow(clippy::result_large_err)]
     pub fn get_all_dbs(&self) -> Result<Vec<String>, ClientError> {
         let rsp = self
@@ -44,6 +177,88 @@ impl SyncClient {
         Ok(serde_json::from_slice(&body)?)
     }
 
+    ///Get one page of the names of the databases present in the instance, along with the total number of databases.
+    ///
+    /// Names are sorted, so a consistent set of pages can be got by increasing `offset` by `limit` each time until it reaches the total.
+    ///
+    /// # Errors
+    /// - [`ClientError::Ureq`] or [`ClientError::HttpErrorCode`] if the request fails.
+    /// - [`ClientError::InvalidHeader`] if the server didn't say how many databases there are in total.
+    #[allow(clippy::result_large_err)]
+    pub fn get_all_dbs_paged(
+        &self,
+        offset: usize,
+        limit: usize,
+    ) -> Result<(Vec<String>, usize), ClientError> {
+        let rsp = self
+            .agent
+            .get(&format!(
+                "http://{}:{}/v1/get_all_db_names",
+                self.path, self.port
+            ))
+            .query("offset", &offset.to_string())
+            .query("limit", &limit.to_string())
+            .call()?;
+
+        let total = rsp
+            .header(TOTAL_COUNT_HEADER)
+            .and_then(|h| h.parse().ok())
+            .ok_or(ClientError::InvalidHeader(TOTAL_COUNT_HEADER))?;
+        let body = rsp.body()?;
+        Ok((serde_json::from_slice(&body)?, total))
+    }
+
+    ///Gets the keys in a given database, sorted, without having to transfer any of the values.
+    ///
+    /// # Errors
+    /// - [`ClientError::Ureq`] or [`ClientError::IO`] if there is an error with the HTTP request, or we cannot get the raw bytes out.
+    /// - [`ClientError::HttpErrorCode`] if an HTTP Error status code is encountered - eg. [`StatusCode::BAD_REQUEST`] if the database doesn't exist.
+    /// - [`ClientError::SerdeJson`] if the response is invalid.
+    #[allow(clippy::result_large_err)]
+    pub fn get_keys(&self, db_name: &str) -> Result<Vec<String>, ClientError> {
+        let rsp = self
+            .agent
+            .get(&format!("http://{}:{}/v1/get_keys", self.path, self.port))
+            .query("db_name", db_name)
+            .call()?;
+
+        let body = rsp.body()?;
+        Ok(serde_json::from_slice(&body)?)
+    }
+
+    ///Gets the [`DbStats`] for a given database, without having to transfer the whole database.
+    ///
+    /// # Errors
+    /// - [`ClientError::Ureq`] or [`ClientError::IO`] if there is an error with the HTTP request, or we cannot get the raw bytes out.
+    /// - [`ClientError::HttpErrorCode`] if an HTTP Error status code is encountered - eg. [`StatusCode::BAD_REQUEST`] if the database doesn't exist.
+    /// - [`ClientError::SerdeJson`] or [`ClientError::Store`] if the response is invalid.
+    #[allow(clippy::result_large_err)]
+    pub fn db_stats(&self, db_name: &str) -> Result<DbStats, ClientError> {
+        let rsp = self
+            .agent
+            .get(&format!("http://{}:{}/v1/db_stats", self.path, self.port))
+            .query("db_name", db_name)
+            .call()?;
+
+        let body = rsp.body()?;
+        Ok(DbStats::from_json(&serde_json::from_slice(&body)?)?)
+    }
+
+    ///Creates a new database in the connected instance with the given name.
+    ///
+    /// ## `overwrite_existing`
+    ///
+    /// If the database already exists, it will be cleared and `Ok(true)` will always be returned in the happy path.
+    ///
+    /// ## !`overwrite_existing`
+    ///
+    /// If the database already exists, it will be left as is and `Ok(false)` will be returned in the happy path.
+    ///
+    /// If it doesn't, then it will be created and `Ok(true)` will be returned.
+    ///
+    /// # Errors
+    /// - [`ClientError::Ureq`] if there is an error with the HTTP request.
+    /// - [`ClientError::HttpErrorCode`] if an HTTP Error status code is encountered.
     #[allow(clippy::result_large_err)]
     pub fn create_new_db(&self, overwrite_existing: bool, name: &str) -> Result<bool, ClientError> {
         let rsp = self
@@ -63,6 +278,12 @@ impl SyncClient {
         })
     }
 
+    /// Gets a given store by name. If the store doesn't exist, [`ClientError::HttpErrorCode`] will be returned with a code of [`StatusCode::BAD_REQUEST`].
+    ///
+    /// # Errors
+    /// - [`ClientError::HttpErrorCode`] if the database isn't found or another error occurs with the HTTP request.
+    /// - [`ClientError::Ureq`] or [`ClientError::IO`] if a ureq error occurs or the bytes cannot be obtained.
+    /// - [`crate::store::StoreSerError`] if the store cannot be deserialised from the bytes.
     #[allow(clippy::result_large_err)]
     pub fn get_store(&self, db_name: &str) -> Result<Store, ClientError> {
         let rsp = self
@@ -71,10 +292,55 @@ impl SyncClient {
             .query("db_name", db_name)
             .call()?;
         let body = rsp.body()?;
-        println!("Received body from client");
         Ok(Store::deser(&body)?)
     }
 
+    ///Gets an archive of every database in the instance, which is a [`Store`] mapping each database name to the serialised bytes of that database in a [`Value::Binary`]. If any keys have a TTL, how long each had left is kept under [`crate::RESERVED_DB_NAME`], and they get that long again when the archive is imported.
+    ///
+    /// To back up the instance, the archive can be serialised using [`Store::ser`] and written to a file, and then restored using [`SyncClient::import_all`].
+    ///
+    /// # Errors
+    /// - [`ClientError::Ureq`] or [`ClientError::IO`] if a ureq error occurs or the bytes cannot be obtained.
+    /// - [`ClientError::HttpErrorCode`] if an HTTP Error status code is encountered.
+    /// - [`crate::store::StoreSerError`] if the archive cannot be deserialised from the bytes.
+    #[allow(clippy::result_large_err)]
+    pub fn export_all(&self) -> Result<Store, ClientError> {
+        let rsp = self
+            .agent
+            .get(&format!("http://{}:{}/v1/export_all", self.path, self.port))
+            .call()?;
+        let body = rsp.body()?;
+        Ok(Store::deser(&body)?)
+    }
+
+    ///Restores every database in an archive from [`SyncClient::export_all`], overwriting any databases with the same names. Databases which aren't in the archive are left alone.
+    ///
+    /// The server checks every database in the archive before restoring any of them, so either all or none are restored.
+    ///
+    /// # Errors
+    /// - [`crate::store::StoreSerError`] if we cannot serialise the archive.
+    /// - [`ClientError::Ureq`] if a ureq error occurs.
+    /// - [`ClientError::HttpErrorCode`] if an HTTP Error status code is encountered - eg. [`StatusCode::BAD_REQUEST`] if any of the names aren't valid.
+    #[allow(clippy::result_large_err)]
+    pub fn import_all(&self, archive: &Store) -> Result<(), ClientError> {
+        let archive = archive.ser()?;
+
+        self.agent
+            .put(&format!("http://{}:{}/v1/import_all", self.path, self.port))
+            .send_bytes(&archive)?;
+        Ok(())
+    }
+
+    ///Adds a new database and immediately inserts the contents of the [`Store`] into it.
+    ///
+    /// If `overwrite_existing` is true or the store already exists, the server will now have one instance of the provided store with the provided contents.
+    ///
+    /// If the database already existed, and `overwrite_existing` is false, then the server will append the keys from the provided database into the new one.
+    ///
+    /// # Errors
+    /// - [`crate::store::StoreSerError`] if we cannot serialise the provided `Store`.
+    /// - [`ClientError::Ureq`] if a ureq error occurs.
+    /// - [`ClientError::HttpErrorCode`] if an HTTP Error status code is encountered.
     #[allow(clippy::result_large_err)]
     pub fn add_db_with_contents(
         &self,
@@ -103,6 +369,13 @@ impl SyncClient {
         })
     }
 
+    ///Adds the given entry to the given database. If that database didn't exist before, it will now.
+    ///
+    /// Returns `true` if the key is new, and `false` if it replaced an existing value.
+    ///
+    /// # Errors
+    /// - [`ClientError::Ureq`] if a ureq error occurs.
+    /// - [`ClientError::HttpErrorCode`] if an HTTP Error status code is encountered.
     #[allow(clippy::result_large_err)]
     pub fn add_entry_to_db(
         &self,
@@ -124,6 +397,74 @@ impl SyncClient {
         })
     }
 
+    ///Adds all of the entries in the given [`Store`] to the given database using one request.
+    ///
+    /// If `create_new` is false and the database doesn't exist, [`ClientError::HttpErrorCode`] will be returned with a code of [`StatusCode::BAD_REQUEST`].
+    ///
+    /// If `overwrite_existing` is false, then any keys which already exist in the database will be left alone.
+    ///
+    /// Returns what happened to each key as a [`CreationResult`].
+    ///
+    /// # Errors
+    /// - [`crate::store::StoreSerError`] if we cannot serialise the provided `Store` or deserialise the results.
+    /// - [`ClientError::Ureq`] or [`ClientError::IO`] if a ureq error occurs or the bytes cannot be obtained.
+    /// - [`ClientError::HttpErrorCode`] if an HTTP Error status code is encountered.
+    #[allow(clippy::result_large_err)]
+    pub fn add_entries_to_db(
+        &self,
+        database_name: &str,
+        create_new: bool,
+        overwrite_existing: bool,
+        entries: &Store,
+    ) -> Result<HashMap<String, CreationResult>, ClientError> {
+        let entries = entries.ser()?;
+        let rsp = self
+            .agent
+            .put(&format!("http://{}:{}/v1/add_kvs", self.path, self.port))
+            .query("db_name", database_name)
+            .query("create_new", if create_new { "true" } else { "false" })
+            .query(
+                "overwrite_existing",
+                if overwrite_existing { "true" } else { "false" },
+            )
+            .send_bytes(&entries)?;
+
+        let body = rsp.body()?;
+        let mut results = Store::deser(&body)?;
+        Ok(results
+            .drain()
+            .map(|(k, v)| CreationResult::try_from(v).map(|cr| (k, cr)))
+            .collect::<Result<_, _>>()?)
+    }
+
+    ///Gets the value stored under `key` in the given database, or `None` if either the database or the key doesn't exist.
+    ///
+    /// # Errors
+    /// - [`ClientError::Ureq`] or [`ClientError::IO`] if a ureq error occurs or the bytes cannot be obtained.
+    /// - [`ClientError::HttpErrorCode`] if an HTTP Error status code other than [`StatusCode::BAD_REQUEST`] is encountered.
+    /// - [`ClientError::Value`] if the value cannot be deserialised from the bytes.
+    #[allow(clippy::result_large_err)]
+    pub fn get_value(&self, db_name: &str, key: &str) -> Result<Option<Value>, ClientError> {
+        let rsp = match self
+            .agent
+            .get(&format!("http://{}:{}/v1/get_value", self.path, self.port))
+            .query("db_name", db_name)
+            .query("key", key)
+            .call()
+        {
+            Ok(rsp) => rsp,
+            Err(ureq::Error::Status(400, _)) => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        let body = rsp.body()?;
+        Ok(Some(Value::deser(&mut Cursor::new(&body), None)?))
+    }
+
+    ///Removes the entry with the given key from the database.
+    ///
+    /// # Errors
+    /// - [`ClientError::Ureq`] if a ureq error occurs.
+    /// - [`ClientError::HttpErrorCode`] if an HTTP Error status code is encountered.
     #[allow(clippy::result_large_err)]
     pub fn remove_entry_from_db(&self, database_name: &str, key: &str) -> Result<(), ClientError> {
         self.agent
@@ -134,13 +475,25 @@ impl SyncClient {
         Ok(())
     }
 
+    ///Removes a given database.
+    ///
+    /// Returns `true` if the database was removed, and `false` if it didn't exist.
+    ///
+    /// # Errors
+    /// - [`ClientError::Ureq`] if a ureq error occurs.
+    /// - [`ClientError::HttpErrorCode`] if an HTTP Error status code other than [`StatusCode::BAD_REQUEST`] is encountered.
     #[allow(clippy::result_large_err)]
-    pub fn remove_db(&self, database_name: &str) -> Result<(), ClientError> {
-        self.agent
+    pub fn remove_db(&self, database_name: &str) -> Result<bool, ClientError> {
+        match self
+            .agent
             .post(&format!("http://{}:{}/v1/rm_db", self.path, self.port))
             .query("db_name", database_name)
-            .call()?;
-        Ok(())
+            .call()
+        {
+            Ok(_) => Ok(true),
+            Err(ureq::Error::Status(400, _)) => Ok(false),
+            Err(e) => Err(e.into()),
+        }
     }
 }
 
@@ -16,12 +16,16 @@ use alloc::{
     format,
     string::{String, ToString},
 };
+use core::fmt::{Display, Formatter};
 
 pub use chrono;
 pub use chrono_tz;
 pub use hashbrown;
 pub use serde_json;
+#[cfg(feature = "uuid")]
+pub use uuid;
 
+pub mod schema;
 pub mod store;
 pub mod types;
 pub mod utilities;
@@ -35,6 +39,68 @@ pub mod axum;
 #[cfg(any(feature = "sync_client", feature = "async_client"))]
 pub mod client;
 
+///The name of the database `sourisd` uses to keep track of all of the other databases, which can't be used for any other database.
+pub const RESERVED_DB_NAME: &str = "meta";
+
+///The header `sourisd` uses to say how many databases there are in total when listing a page of database names.
+pub const TOTAL_COUNT_HEADER: &str = "x-total-count";
+
+///The reason that a database name isn't allowed - see [`is_valid_db_name`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum InvalidDbNameReason {
+    ///The name is [`RESERVED_DB_NAME`].
+    Reserved,
+    ///The name contains non-ASCII characters.
+    NotAscii,
+    ///The name is empty.
+    Empty,
+}
+
+impl Display for InvalidDbNameReason {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Reserved => write!(f, "the name `{RESERVED_DB_NAME}` is reserved"),
+            Self::NotAscii => write!(f, "database names must be ASCII"),
+            Self::Empty => write!(f, "database names can't be empty"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for InvalidDbNameReason {}
+
+///Checks whether a database name is allowed by `sourisd` - names must be non-empty, ASCII, and not [`RESERVED_DB_NAME`].
+///
+/// ```rust
+/// use sourisdb::{is_valid_db_name, InvalidDbNameReason};
+///
+/// assert_eq!(is_valid_db_name("my_database"), Ok(()));
+/// assert_eq!(is_valid_db_name("meta"), Err(InvalidDbNameReason::Reserved));
+/// assert_eq!(is_valid_db_name("café"), Err(InvalidDbNameReason::NotAscii));
+/// assert_eq!(is_valid_db_name(""), Err(InvalidDbNameReason::Empty));
+/// ```
+///
+/// # Errors
+/// Returns the [`InvalidDbNameReason`] if the name isn't allowed.
+pub fn is_valid_db_name(name: &str) -> Result<(), InvalidDbNameReason> {
+    if name.is_empty() {
+        Err(InvalidDbNameReason::Empty)
+    } else if name == RESERVED_DB_NAME {
+        Err(InvalidDbNameReason::Reserved)
+    } else if !name.is_ascii() {
+        Err(InvalidDbNameReason::NotAscii)
+    } else {
+        Ok(())
+    }
+}
+
+///Formats bytes as an array of hex numbers, like `[0x53, 0x4F]`, which is handy for debugging serialised data.
+///
+/// ```rust
+/// use sourisdb::display_bytes_as_hex_array;
+///
+/// assert_eq!(display_bytes_as_hex_array(b"SO"), "[0x53, 0x4F]");
+/// ```
 #[must_use]
 pub fn display_bytes_as_hex_array(b: &[u8]) -> String {
     let mut out;
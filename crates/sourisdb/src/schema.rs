@@ -0,0 +1,224 @@
+//! Provides [`Schema`], a simple description of the keys a [`Store`](crate::store::Store) must have and the types of their values, for validating untrusted stores (like API payloads) before using them.
+//!
+//! ```rust
+//! use sourisdb::{
+//!     schema::{Schema, SchemaError},
+//!     store::Store,
+//!     values::{Value, ValueTy},
+//! };
+//!
+//! let schema = Schema::default()
+//!     .require("name", ValueTy::String)
+//!     .require_map("address", Schema::default().require("city", ValueTy::String));
+//!
+//! let mut store = Store::default();
+//! store.insert("name".into(), Value::from(5_u8));
+//! store.insert("address".into(), Value::Map(Default::default()));
+//!
+//! let errors = store.validate(&schema).unwrap_err();
+//! assert_eq!(
+//!     errors,
+//!     vec![
+//!         SchemaError::Missing {
+//!             path: vec!["address".into(), "city".into()],
+//!             expected: ValueTy::String,
+//!         },
+//!         SchemaError::UnexpectedValueType {
+//!             path: vec!["name".into()],
+//!             found: ValueTy::Integer,
+//!             expected: ValueTy::String,
+//!         },
+//!     ]
+//! );
+//! ```
+
+use alloc::{string::String, vec::Vec};
+use core::fmt::{Display, Formatter};
+
+use hashbrown::HashMap;
+
+use crate::values::{PathSegment, Value, ValueTy};
+
+///What the value for a key in a [`Schema`] must look like.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchemaTy {
+    ///Any value of this type.
+    Ty(ValueTy),
+    ///A [`Value::Map`] or [`Value::OrderedMap`] whose contents match the nested schema.
+    Map(Schema),
+}
+
+impl SchemaTy {
+    ///The type that a value must have to match.
+    #[must_use]
+    pub fn expected_ty(&self) -> ValueTy {
+        match self {
+            Self::Ty(ty) => *ty,
+            Self::Map(_) => ValueTy::Map,
+        }
+    }
+}
+
+///A description of the keys that must be present in a [`Store`](crate::store::Store) or map, and what their values must look like.
+///
+/// Only the required keys are checked - any other keys are allowed.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Schema(HashMap<String, SchemaTy>);
+
+impl Schema {
+    ///Requires that `key` is present and has a value of type `ty`.
+    #[must_use]
+    pub fn require(mut self, key: impl Into<String>, ty: ValueTy) -> Self {
+        self.0.insert(key.into(), SchemaTy::Ty(ty));
+        self
+    }
+
+    ///Requires that `key` is present and is a [`Value::Map`] or [`Value::OrderedMap`] matching `schema`.
+    #[must_use]
+    pub fn require_map(mut self, key: impl Into<String>, schema: Schema) -> Self {
+        self.0.insert(key.into(), SchemaTy::Map(schema));
+        self
+    }
+
+    ///Checks a map (like the contents of a [`Store`](crate::store::Store)) against this schema.
+    ///
+    /// # Errors
+    /// Every [`SchemaError`] found, sorted by path.
+    pub fn validate_map(&self, map: &HashMap<String, Value>) -> Result<(), Vec<SchemaError>> {
+        let mut errors = Vec::new();
+        self.validate_inner(&|key| map.get(key), &mut Vec::new(), &mut errors);
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            errors.sort_unstable_by(|a, b| a.path().cmp(b.path()));
+            Err(errors)
+        }
+    }
+
+    fn validate_inner<'a>(
+        &self,
+        get: &dyn Fn(&str) -> Option<&'a Value>,
+        path: &mut Vec<String>,
+        errors: &mut Vec<SchemaError>,
+    ) {
+        for (key, schema_ty) in &self.0 {
+            path.push(key.clone());
+
+            match (get(key), schema_ty) {
+                (None, _) => errors.push(SchemaError::Missing {
+                    path: path.clone(),
+                    expected: schema_ty.expected_ty(),
+                }),
+                (Some(v @ (Value::Map(_) | Value::OrderedMap(_))), SchemaTy::Map(schema)) => {
+                    schema.validate_inner(
+                        &|key| v.deep_get(&[PathSegment::Key(key.into())]),
+                        path,
+                        errors,
+                    );
+                }
+                (Some(v), _) if v.as_ty() != schema_ty.expected_ty() => {
+                    errors.push(SchemaError::UnexpectedValueType {
+                        path: path.clone(),
+                        found: v.as_ty(),
+                        expected: schema_ty.expected_ty(),
+                    });
+                }
+                (Some(_), _) => {}
+            }
+
+            path.pop();
+        }
+    }
+}
+
+///A way in which a map didn't match a [`Schema`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchemaError {
+    ///A required key wasn't present.
+    Missing {
+        ///The keys leading to the missing key, including the missing key itself.
+        path: Vec<String>,
+        ///The type the schema expected.
+        expected: ValueTy,
+    },
+    ///A value had the wrong type.
+    UnexpectedValueType {
+        ///The keys leading to the value.
+        path: Vec<String>,
+        ///The type we found
+        found: ValueTy,
+        ///The type we expected to find
+        expected: ValueTy,
+    },
+}
+
+impl SchemaError {
+    ///The keys leading to the problem.
+    #[must_use]
+    pub fn path(&self) -> &[String] {
+        match self {
+            Self::Missing { path, .. } | Self::UnexpectedValueType { path, .. } => path,
+        }
+    }
+}
+
+impl Display for SchemaError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        let path = self.path().join("/");
+        match self {
+            Self::Missing { expected, .. } => {
+                write!(f, "Missing {path:?}, expected {expected:?}")
+            }
+            Self::UnexpectedValueType {
+                found, expected, ..
+            } => write!(f, "At {path:?}, expected {expected:?}, found: {found:?}"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for SchemaError {}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{string::ToString, vec};
+
+    use super::{Schema, SchemaError};
+    use crate::{
+        store::Store,
+        values::{Value, ValueTy},
+    };
+
+    #[test]
+    fn test_nested_and_ordered_maps() {
+        let schema = Schema::default()
+            .require("id", ValueTy::Integer)
+            .require_map(
+                "settings",
+                Schema::default().require("theme", ValueTy::String),
+            );
+
+        let mut store = Store::default();
+        store.insert("id".to_string(), Value::from(1_u8));
+        store.insert("extra".to_string(), Value::Boolean(true));
+        store.insert(
+            "settings".to_string(),
+            Value::OrderedMap(vec![("theme".to_string(), Value::String("dark".into()))]),
+        );
+        assert_eq!(store.validate(&schema), Ok(()));
+
+        store.insert(
+            "settings".to_string(),
+            Value::Array(vec![Value::String("dark".into())]),
+        );
+        assert_eq!(
+            store.validate(&schema),
+            Err(vec![SchemaError::UnexpectedValueType {
+                path: vec!["settings".to_string()],
+                found: ValueTy::Array,
+                expected: ValueTy::Map,
+            }])
+        );
+    }
+}
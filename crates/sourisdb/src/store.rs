@@ -1,219 +1,1483 @@
 //! Provides the main key-value store designed to be used for communications.
 
 use alloc::{
+    boxed::Box,
+    format,
     string::{String, ToString},
     vec,
     vec::Vec,
 };
 use core::{
-    fmt::{Display, Formatter},
-    ops::{Deref, DerefMut},
+    fmt::{Debug, Display, Formatter},
+    hash::{Hash, Hasher},
+    ops::{Deref, DerefMut, Range},
 };
 
-use hashbrown::HashMap;
-use serde_json::{Error as SJError, Value as SJValue};
+use hashbrown::{Equivalent, HashMap};
+use serde_json::{Error as SJError, Map as SJMap, Number, Value as SJValue};
 
 use crate::{
+    schema::{Schema, SchemaError},
     types::{
-        binary::{BinaryCompression, BinaryData, BinarySerError},
-        integer::IntegerSerError,
+        binary::{
+            dictionary::{lz_with_known_dict, Dict, MAX_DICT_LEN},
+            huffman::MAX_RUN_LEN,
+            BinaryCompression, BinaryData, BinarySerError,
+        },
+        integer::{Integer, IntegerSerError},
     },
     utilities::{
-        cursor::Cursor,
+        crc32::Crc32,
+        cursor::{Cursor, CursorError},
+        fnv::Fnv1a,
         huffman::{Huffman, HuffmanSerError},
     },
-    values::{Value, ValueSerError, ValueTy},
+    values::{int_key_range, DeserOptions, Value, ValueSerError, ValueTy},
 };
 
+pub mod stream;
+
 ///A key-value store where the keys are [`String`]s and the values are [`Value`]s - this is a thin wrapper around [`hashbrown::HashMap`] and implements both [`Deref`] and [`DerefMut`] pointing to it. This database is optimised for storage when serialised.
 ///
 /// The expectation is that if you need an in-memory key-value database, you do one of two things:
 /// - Spin up a server running `sourisd` and make HTTP requests to it. Then, serialise or deserialise the values appropriately.
 /// - Create a `Store` and keep it in the state of your program. To access values just use it as a [`hashbrown::HashMap`]. When your program exits (or periodically to allow for if the program quits unexpectedly), serialise the database and write it to a file. Then, when starting the program again read the database in.
+///
+/// Callbacks can be added using [`Store::on_change`] to find out when entries are inserted or removed.
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
-pub struct Store(HashMap<String, Value>);
+pub struct Store(HashMap<String, Value>, Observers);
+
+///A change made to a [`Store`], which gets passed to the callbacks added using [`Store::on_change`].
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ChangeEvent<'a> {
+    ///A key which wasn't in the store before was inserted.
+    Inserted {
+        ///The key which was inserted
+        key: &'a str,
+        ///The value which was inserted
+        value: &'a Value,
+    },
+    ///A key which was already in the store was given a new value.
+    Updated {
+        ///The key which was updated
+        key: &'a str,
+        ///The value from before the update
+        old: &'a Value,
+        ///The value from after the update
+        new: &'a Value,
+    },
+    ///A key was removed from the store.
+    Removed {
+        ///The key which was removed
+        key: &'a str,
+        ///The value which was removed
+        value: &'a Value,
+    },
+}
+
+///A callback added using [`Store::on_change`].
+pub type ChangeCallback = Box<dyn FnMut(&ChangeEvent) + Send + Sync>;
+
+///The callbacks for a [`Store`]. These aren't part of the contents of the store, so they are skipped when comparing stores and aren't copied when a store is cloned.
+#[derive(Default)]
+struct Observers(Vec<ChangeCallback>);
+
+impl Observers {
+    fn notify(&mut self, event: &ChangeEvent) {
+        for f in &mut self.0 {
+            f(event);
+        }
+    }
+}
+
+impl Debug for Observers {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{} observers", self.0.len())
+    }
+}
+impl Clone for Observers {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+impl PartialEq for Observers {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+impl Eq for Observers {}
 
 impl Store {
-    ///Serialises a store into bytes. There are 8 magic bytes at the front which read `SOURISDB` and the rest is serialised as a [`Value::Map`] containing the map stored within the caller.
+    ///Serialises a store into bytes. There are 8 magic bytes at the front which read `SOURISDB`, then a byte for the [`STORE_FORMAT_VERSION`], and the rest is serialised as a [`Value::Map`] containing the map stored within the caller. At the very end, there are 4 bytes for a [`Crc32`] checksum of everything before it, which [`Store::deser`] uses to catch corrupted stores.
+    ///
+    /// Strings are huffman-encoded using a tree built from all of the text in the store, unless storing the tree would cost more than it saves. The top bit of the byte after the version records whether a tree was included.
+    ///
+    /// Small binary values are often too small to compress well by themselves, so if there are lots of similar ones, a [`Dict`] is trained from them and stored once at the start of the store, and then each binary value is compressed using it whenever that is smaller. Like the tree, the dictionary is only included when it saves more than it costs, and [`DICT_MARKER`] in the byte after the version records whether it was.
     ///
     /// # Errors
     /// - [`ValueSerError`] if there is an error serialising the internal map as a [`Value::Map`]
     pub fn ser(&self) -> Result<Vec<u8>, StoreSerError> {
-        fn add_value_text_to_string(value: &Value, string: &mut String) {
+        Ok(self.ser_compressed_with(BinaryData::ser))
+    }
+
+    ///Serialises a store in the same format as [`Store::ser`], but skips binary compression entirely and records [`BinaryCompression::Nothing`] after the version. Strings are still huffman-encoded.
+    ///
+    /// [`Store::ser`] tries every [`BinaryCompression`] on the whole serialised store to pick the smallest. Skipping that makes serialising faster at the cost of (often much) bigger output, so this is useful when latency matters more than size or when the store is known not to compress well. Huffman-encoding usually takes more time than binary compression, so don't expect this to be many times faster. [`Store::deser`] reads it back just like any other store.
+    ///
+    /// ```rust
+    /// use sourisdb::{store::Store, values::Value};
+    ///
+    /// let mut store = Store::default();
+    /// store.insert("key".into(), Value::String("value".repeat(100)));
+    ///
+    /// let raw = store.ser_uncompressed().unwrap();
+    /// assert!(raw.len() > store.ser().unwrap().len());
+    /// assert_eq!(Store::deser(&raw).unwrap(), store);
+    /// ```
+    ///
+    /// # Errors
+    /// - [`ValueSerError`] if there is an error serialising the internal map as a [`Value::Map`]
+    pub fn ser_uncompressed(&self) -> Result<Vec<u8>, StoreSerError> {
+        Ok(self.ser_compressed_with(|data| {
+            (
+                BinaryCompression::Nothing,
+                data.ser_with(BinaryCompression::Nothing),
+            )
+        }))
+    }
+
+    ///Serialises a store with the framing for [`Store::deser`], using `compress` to compress the huffman tree and map.
+    fn ser_compressed_with(
+        &self,
+        compress: impl FnOnce(&BinaryData) -> (BinaryCompression, Vec<u8>),
+    ) -> Vec<u8> {
+        let huffman = self.huffman_for_text();
+        let dict = self.dict_for_binary();
+
+        let mut res = dict.as_ref().map(Dict::ser).unwrap_or_default();
+        if let Some(huffman) = &huffman {
+            res.extend(huffman.ser());
+        }
+        Value::ser_map(&self.0, huffman.as_ref(), dict.as_ref(), &mut res);
+
+        let (compression_type, compressed) = compress(&BinaryData(res));
+
+        let magic_ty = (u8::from(huffman.is_some()) << 7)
+            | if dict.is_some() { DICT_MARKER } else { 0 }
+            | u8::from(compression_type);
+
+        let mut fin = vec![];
+        fin.extend(b"SOURISDB");
+        fin.push(VERSION_MARKER | STORE_FORMAT_VERSION);
+        fin.push(magic_ty);
+        fin.extend(compressed);
+        fin.extend(Crc32::checksum(&fin).to_le_bytes());
+
+        fin
+    }
+
+    ///Serialises a store into a writer a frame at a time, in a form that can be read back using [`Store::deser`].
+    ///
+    /// [`Store::ser`] serialises the whole store and then compresses it in one go, so the serialised store and its compressed copies are all in memory at once. This instead serialises one entry at a time into frames of up to [`FRAME_LEN`] bytes, and compresses and writes each frame as soon as it is full, so only one frame (plus the biggest single entry) is ever held. Each frame gets whichever [`BinaryCompression`] suits it best, and as nothing can be matched across frames, the output is usually a little bigger than from [`Store::ser`]. The checksum is calculated as the bytes are written.
+    ///
+    /// Strings are still huffman-encoded using one tree for the whole store, which is built by counting the characters in the store first. That only needs one count per distinct character, rather than a copy of all of the text. Binary values can still be compressed using a [`Dict`] for the whole store, which is trained from a limited sample of them.
+    ///
+    /// The output can't be read by [`Store::deser_headerless`], as there's no [`BinaryCompression`] for a payload in frames.
+    ///
+    /// ```rust
+    /// use sourisdb::{store::Store, values::Value};
+    ///
+    /// let mut store = Store::default();
+    /// store.insert("key".into(), Value::String("value".into()));
+    ///
+    /// let mut bytes = vec![];
+    /// store.ser_to_writer(&mut bytes).unwrap();
+    ///
+    /// assert_eq!(Store::deser(&bytes).unwrap(), store);
+    /// ```
+    ///
+    /// # Errors
+    /// - [`StoreSerError::Io`] if there is an error writing to the writer.
+    #[cfg(feature = "std")]
+    pub fn ser_to_writer<W: std::io::Write>(&self, w: &mut W) -> Result<(), StoreSerError> {
+        let huffman = self.huffman_for_text();
+        let dict = self.dict_for_binary();
+        let magic_ty = (u8::from(huffman.is_some()) << 7)
+            | if dict.is_some() { DICT_MARKER } else { 0 }
+            | FRAMED_MARKER;
+
+        let mut frames = FrameWriter::new(w);
+        frames.write(b"SOURISDB")?;
+        frames.write(&[VERSION_MARKER | STORE_FORMAT_VERSION, magic_ty])?;
+
+        let mut bytes = dict.as_ref().map(Dict::ser).unwrap_or_default();
+        if let Some(huffman) = &huffman {
+            bytes.extend(huffman.ser());
+        }
+        Value::ser_map_len(self.0.len(), &mut bytes);
+        frames.push(&bytes)?;
+
+        //the same entries in the same order as `Value::ser_map`, just without them all being in one buffer
+        for (k, v) in self.iter_sorted() {
+            bytes.clear();
+            bytes.extend(Value::String(k.clone()).ser(huffman.as_ref()));
+            bytes.extend(v.ser_with_dict(huffman.as_ref(), dict.as_ref()));
+            frames.push(&bytes)?;
+        }
+
+        frames.finish()?;
+        Ok(())
+    }
+
+    ///Creates the huffman tree for all of the text in the store, by counting each character rather than collecting all of the text.
+    ///
+    /// The tree has to be serialised alongside the store, so it is skipped (and [`None`] returned) when it would take up more bytes than it is estimated to save - which is usually the case for small stores.
+    fn huffman_for_text(&self) -> Option<Huffman<char>> {
+        let mut frequencies: HashMap<char, usize> = HashMap::new();
+        let mut add_text = |text: &str| {
+            for ch in text.chars() {
+                *frequencies.entry(ch).or_default() += 1;
+            }
+        };
+
+        for k in self.0.keys() {
+            add_text(k);
+        }
+        let mut add_value_text = |value: &Value| match value {
+            Value::Map(map) => {
+                for k in map.keys() {
+                    add_text(k);
+                }
+            }
+            Value::OrderedMap(map) => {
+                for (k, _) in map {
+                    add_text(k);
+                }
+            }
+            Value::JSON(sjv) => {
+                add_text(&sjv.to_string());
+            }
+            Value::Timezone(tz) => {
+                add_text(tz.name());
+            }
+            Value::ZonedTimestamp(dt) => {
+                add_text(dt.timezone().name());
+            }
+            Value::String(s) | Value::Tagged { tag: s, .. } => add_text(s),
+            #[cfg(feature = "regex")]
+            Value::Regex(r) => add_text(r.as_str()),
+            _ => {}
+        };
+
+        for v in self.0.values() {
+            v.walk(&mut add_value_text);
+        }
+
+        let text_len: usize = frequencies
+            .iter()
+            .map(|(ch, count)| ch.len_utf8() * count)
+            .sum();
+        Huffman::from_frequencies(frequencies)
+            .ok()
+            .filter(|huffman| {
+                //ignores the lengths before each string, as they're similar either way
+                let savings = huffman
+                    .encoded_len_of_frequencies()
+                    .map_or(0, |bits| text_len.saturating_sub(bits.div_ceil(8)));
+                savings > huffman.ser().len()
+            })
+    }
+
+    ///Trains a [`Dict`] from the small binary values in the store (up to [`MAX_DICT_TRAINING_LEN`] bytes of them), for compressing lots of similar blobs.
+    ///
+    /// Like the huffman tree, the dictionary has to be serialised alongside the store, so it is skipped (and [`None`] returned) unless it saves more bytes on those binary values than it takes up.
+    fn dict_for_binary(&self) -> Option<Dict> {
+        ///Adds the binary values that could be compressed using the dictionary, which leaves out anything inside an extended type.
+        fn add_samples<'a>(value: &'a Value, samples: &mut Vec<&'a BinaryData>, len: &mut usize) {
             match value {
-                Value::Map(map) => {
-                    for (k, v) in map {
-                        string.push_str(k);
-                        add_value_text_to_string(v, string);
-                    }
+                Value::Binary(b)
+                    if b.len() <= MAX_DICT_SAMPLE_LEN
+                        && *len + b.len() <= MAX_DICT_TRAINING_LEN =>
+                {
+                    *len += b.len();
+                    samples.push(b);
                 }
                 Value::Array(a) => {
                     for v in a {
-                        add_value_text_to_string(v, string);
+                        add_samples(v, samples, len);
                     }
                 }
-                Value::JSON(sjv) => {
-                    string.push_str(&sjv.to_string());
-                }
-                Value::Timezone(tz) => {
-                    string.push_str(tz.name());
+                Value::Map(m) => {
+                    //sorted so that the same samples are picked every time
+                    let mut entries: Vec<_> = m.iter().collect();
+                    entries.sort_unstable_by_key(|(k, _)| *k);
+                    for (_, v) in entries {
+                        add_samples(v, samples, len);
+                    }
                 }
-                Value::String(s) => string.push_str(s),
                 _ => {}
             }
         }
 
-        let raw_map = Value::Map(self.0.clone());
-        let mut all_text = String::new();
-        add_value_text_to_string(&raw_map, &mut all_text);
+        let mut samples = vec![];
+        let mut len = 0;
+        for (_, v) in self.iter_sorted() {
+            add_samples(v, &mut samples, &mut len);
+        }
+
+        let dict = Dict::train(samples.iter().map(|b| b.as_slice()), MAX_DICT_LEN);
+        if dict.as_bytes().is_empty() {
+            return None;
+        }
 
-        let huffman = Huffman::new_str(&all_text);
-        let map = raw_map.ser(huffman.as_ref().ok());
+        let savings: usize = samples
+            .iter()
+            .map(|b| {
+                b.ser()
+                    .1
+                    .len()
+                    .saturating_sub(lz_with_known_dict(b, &dict).len())
+            })
+            .sum();
+        (savings > dict.ser().len()).then_some(dict)
+    }
 
-        let huffman_exists = huffman.is_ok();
-        let mut res = if let Ok(huffman) = huffman {
-            huffman.ser()
+    /// Deserialises bytes (which must require the magic bytes) into a Store. See [`Store::deser_headerless`] for stores which have had their header taken off.
+    ///
+    /// This never panics, whatever bytes it is given - invalid input always ends up as a [`StoreSerError`], and lengths read from the input are checked before anything gets allocated for them. There is a `cargo-fuzz` target for this in `fuzz/`, which can be run using `cargo +nightly fuzz run store_deser` from the `sourisdb` directory.
+    ///
+    /// Stores from before the version byte was added can still be read, and are treated as version `0`. Stores from before version `2` don't have a checksum, so they can't be checked for corruption. Stores from before version `3` can ask for runs of one repeated byte longer than [`MAX_RUN_LEN`], which are still read (as earlier versions of the crate wrote them), so untrusted ones can need a lot of memory - but if it can't be allocated, that is still an error rather than a panic.
+    ///
+    /// # Errors
+    /// - [`StoreSerError::NotEnoughBytes`] if we can't read enough bytes.
+    /// - [`StoreSerError::ExpectedMagicBytes`] if we don't find the magic bytes.
+    /// - [`StoreSerError::UnsupportedVersion`] if the store was serialised by a newer version of the crate, using a format this version doesn't understand.
+    /// - [`StoreSerError::ChecksumMismatch`] if the store has been corrupted or truncated.
+    /// - [`BinarySerError`] if we cannot work out which binary compression type was used, or there's an error deserialising the binary.
+    /// - [`HuffmanSerError`] if we cannot deserialise anything huffman related
+    /// - [`ValueSerError`] if we cannot turn the bytes back into [`Value::Map`]
+    pub fn deser(bytes: &[u8]) -> Result<Self, StoreSerError> {
+        Self::deser_inner(bytes, false, Value::DEFAULT_MAX_DEPTH)
+    }
+
+    /// Deserialises bytes into a Store, allowing containers to be nested at most `max_depth` deep (counting the store itself) - see [`Value::deser_with_max_depth`].
+    ///
+    /// [`Store::deser`] uses [`Value::DEFAULT_MAX_DEPTH`], which older versions of the crate didn't check when serialising, so this can be used to read deeper stores that are trusted.
+    ///
+    /// ```rust
+    /// use sourisdb::{store::{Store, StoreSerError}, values::{Value, ValueSerError}};
+    ///
+    /// let mut nested = Value::Null(());
+    /// for _ in 0..Value::DEFAULT_MAX_DEPTH {
+    ///     nested = Value::Array(vec![nested]);
+    /// }
+    /// let mut store = Store::default();
+    /// store.insert("deep".into(), nested);
+    /// let bytes = store.ser().unwrap();
+    ///
+    /// assert!(matches!(
+    ///     Store::deser(&bytes),
+    ///     Err(StoreSerError::Value(ValueSerError::MaxDepthExceeded))
+    /// ));
+    /// assert_eq!(Store::deser_with_max_depth(&bytes, Value::DEFAULT_MAX_DEPTH + 1).unwrap(), store);
+    /// ```
+    ///
+    /// # Errors
+    /// See [`Store::deser`].
+    pub fn deser_with_max_depth(bytes: &[u8], max_depth: usize) -> Result<Self, StoreSerError> {
+        Self::deser_inner(bytes, false, max_depth)
+    }
+
+    /// Deserialises bytes into a Store, keeping any values of types this version of the crate doesn't know about as [`Value::Unknown`]s - see [`Value::deser_skip_unknown`].
+    ///
+    /// # Errors
+    /// See [`Store::deser`].
+    pub fn deser_skip_unknown(bytes: &[u8]) -> Result<Self, StoreSerError> {
+        Self::deser_inner(bytes, true, Value::DEFAULT_MAX_DEPTH)
+    }
+
+    /// Deserialises a Store from a file by memory-mapping it, which avoids reading the whole file into memory first.
+    ///
+    /// NB: The store is still decompressed into new buffers, so this doesn't make deserialising zero-copy - it just skips the copy of the file itself.
+    ///
+    /// # Safety
+    /// The file mustn't be truncated or changed (by this process or any other) until this returns. The mapped bytes are read as a normal slice, so changes to them part of the way through are undefined behaviour rather than just a deserialisation error. Use [`Store::deser`] on the file's contents if that can't be guaranteed, eg. for a file that `sourisd` might be saving to.
+    ///
+    /// # Errors
+    /// - [`StoreSerError::Io`] if the file can't be opened or mapped.
+    /// - Anything from [`Store::deser`].
+    #[cfg(feature = "mmap")]
+    pub unsafe fn deser_mmap(path: &std::path::Path) -> Result<Self, StoreSerError> {
+        let file = std::fs::File::open(path)?;
+        //SAFETY: the caller promises that the file won't change whilst it is mapped, and the map is dropped before we return
+        let map = unsafe { memmap2::Mmap::map(&file)? };
+        Self::deser(&map)
+    }
+
+    ///Checks the header (and the checksum, if there is one) of a serialised store, and returns the compressed bytes which come after it.
+    fn read_header(all_bytes: &[u8]) -> Result<(Header, &[u8]), StoreSerError> {
+        let mut bytes = Cursor::new(&all_bytes);
+        if bytes.read_exact_checked()? != b"SOURISDB" {
+            return Err(StoreSerError::ExpectedMagicBytes);
+        }
+        let Some(mut next) = bytes.next().copied() else {
+            return Err(StoreSerError::NotEnoughBytes);
+        };
+
+        //legacy stores go straight into the compression byte, which never has the marker set
+        let version = if next & VERSION_MARKER == 0 {
+            0
         } else {
-            vec![]
+            let version = next & !VERSION_MARKER;
+            let Some(compression) = bytes.next().copied() else {
+                return Err(StoreSerError::NotEnoughBytes);
+            };
+            next = compression;
+            version
         };
-        res.extend(&map);
 
-        let (compression_type, compressed) = BinaryData(res).ser();
+        let is_huffman_encoded = (next & 0b1000_0000) != 0;
+        let (is_framed, has_dict, compression) = match version {
+            0..=3 => (
+                false,
+                false,
+                BinaryCompression::try_from(next & 0b0111_1111)?,
+            ),
+            4 => (
+                (next & FRAMED_MARKER) != 0,
+                false,
+                BinaryCompression::try_from(next & 0b0011_1111)?,
+            ),
+            5 => (
+                (next & FRAMED_MARKER) != 0,
+                (next & DICT_MARKER) != 0,
+                BinaryCompression::try_from(next & 0b0001_1111)?,
+            ),
+            _ => return Err(StoreSerError::UnsupportedVersion(version)),
+        };
 
-        let magic_ty = (u8::from(huffman_exists) << 7) | u8::from(compression_type);
+        let header = Header {
+            version,
+            len: bytes.pos(),
+            is_huffman_encoded,
+            is_framed,
+            has_dict,
+            compression,
+        };
 
-        let mut fin = vec![];
-        fin.extend(b"SOURISDB");
-        fin.push(magic_ty);
-        fin.extend(compressed);
+        let body = if version >= 2 {
+            let Some((checked, trailer)) = all_bytes
+                .split_last_chunk::<4>()
+                .filter(|(checked, _)| checked.len() >= header.len)
+            else {
+                return Err(StoreSerError::NotEnoughBytes);
+            };
 
-        Ok(fin)
+            let expected = u32::from_le_bytes(*trailer);
+            let found = Crc32::checksum(checked);
+            if expected != found {
+                return Err(StoreSerError::ChecksumMismatch { expected, found });
+            }
+
+            &checked[header.len..]
+        } else {
+            &all_bytes[bytes.pos()..]
+        };
+        Ok((header, body))
     }
 
-    /// Deserialises bytes (which must require the magic bytes) into a Store.
+    ///Works out where each section of a serialised store is, without deserialising any of the values. This is mainly useful for debugging the wire format.
+    ///
+    /// ```rust
+    /// use sourisdb::{store::{Store, STORE_FORMAT_VERSION}, values::Value};
+    ///
+    /// let mut store = Store::default();
+    /// store.insert("key".into(), Value::String("value".into()));
+    /// let bytes = store.ser().unwrap();
+    ///
+    /// let layout = Store::layout(&bytes).unwrap();
+    /// assert_eq!(layout.version, STORE_FORMAT_VERSION);
+    /// assert_eq!(&bytes[..8], b"SOURISDB");
+    /// assert_eq!(layout.values.end, layout.payload.len());
+    /// ```
     ///
     /// # Errors
     /// - [`StoreSerError::NotEnoughBytes`] if we can't read enough bytes.
     /// - [`StoreSerError::ExpectedMagicBytes`] if we don't find the magic bytes.
+    /// - [`StoreSerError::UnsupportedVersion`] if the store uses a format this version of the crate doesn't understand.
+    /// - [`StoreSerError::ChecksumMismatch`] if the store has been corrupted or truncated.
     /// - [`BinarySerError`] if we cannot work out which binary compression type was used, or there's an error deserialising the binary.
-    /// - [`HuffmanSerError`] if we cannot deserialise anything huffman related
+    /// - [`HuffmanSerError`] if there is a huffman tree which can't be deserialised.
+    pub fn layout(bytes: &[u8]) -> Result<StoreLayout, StoreSerError> {
+        let (header, body) = Self::read_header(bytes)?;
+        let payload = header.decompress(body)?;
+
+        let mut cursor = Cursor::new(&payload);
+        let dict = if header.has_dict {
+            Dict::deser(&mut cursor)?;
+            Some(0..cursor.pos())
+        } else {
+            None
+        };
+        let huffman = if header.is_huffman_encoded {
+            let start = cursor.pos();
+            Huffman::<char>::deser(&mut cursor)?;
+            Some(start..cursor.pos())
+        } else {
+            None
+        };
+        let values = cursor.pos()..payload.len();
+
+        Ok(StoreLayout {
+            version: header.version,
+            header_len: header.len,
+            is_framed: header.is_framed,
+            compression: header.compression,
+            payload,
+            dict,
+            huffman,
+            values,
+        })
+    }
+
+    ///Deserialises a store which has had its header (the magic bytes, version and the byte with the framing parameters) and checksum taken off, for embedding stores inside other formats.
+    ///
+    /// `bytes` should be everything between the header and the checksum of something from [`Store::ser`], and `huffman_present` and `compression` should match the top bit and the rest of the last byte of the header. As there's no version or checksum, this always uses the current [`STORE_FORMAT_VERSION`] and corruption can't be detected here - that is left to whatever format the store is embedded in.
+    ///
+    /// There's nothing to say whether the payload starts with a [`Dict`], so stores which have one (which is recorded by the third bit of the last byte of the header) can't be read this way.
+    ///
+    /// ```rust
+    /// use sourisdb::{store::Store, types::binary::BinaryCompression, values::Value};
+    ///
+    /// let mut store = Store::default();
+    /// store.insert("key".into(), Value::String("value".into()));
+    /// let bytes = store.ser().unwrap();
+    ///
+    /// //strip the magic bytes, the version and the checksum
+    /// let framing = bytes[9];
+    /// let headerless = &bytes[10..bytes.len() - 4];
+    ///
+    /// let compression = BinaryCompression::try_from(framing & 0b0111_1111).unwrap();
+    /// let got_back = Store::deser_headerless(headerless, framing & 0b1000_0000 != 0, compression).unwrap();
+    /// assert_eq!(got_back, store);
+    /// ```
+    ///
+    /// # Errors
+    /// - [`BinarySerError`] if there's an error decompressing the bytes.
+    /// - [`HuffmanSerError`] if `huffman_present` is set, and the huffman tree can't be deserialised.
     /// - [`ValueSerError`] if we cannot turn the bytes back into [`Value::Map`]
-    pub fn deser(bytes: &[u8]) -> Result<Self, StoreSerError> {
+    pub fn deser_headerless(
+        bytes: &[u8],
+        huffman_present: bool,
+        compression: BinaryCompression,
+    ) -> Result<Self, StoreSerError> {
+        let header = Header {
+            version: STORE_FORMAT_VERSION,
+            len: 0,
+            is_huffman_encoded: huffman_present,
+            is_framed: false,
+            has_dict: false,
+            compression,
+        };
+        Self::deser_body(bytes, &header, false, Value::DEFAULT_MAX_DEPTH)
+    }
+
+    fn deser_inner(
+        bytes: &[u8],
+        skip_unknown: bool,
+        max_depth: usize,
+    ) -> Result<Self, StoreSerError> {
+        let (header, body) = Self::read_header(bytes)?;
+        Self::deser_body(body, &header, skip_unknown, max_depth)
+    }
+
+    ///Decompresses and deserialises everything after the header of a store.
+    fn deser_body(
+        body: &[u8],
+        header: &Header,
+        skip_unknown: bool,
+        max_depth: usize,
+    ) -> Result<Self, StoreSerError> {
+        let max_run_len = header.max_run_len();
+        let bytes = header.decompress(body)?;
         let mut bytes = Cursor::new(&bytes);
-        {
-            let Some(magic_bytes) = bytes.read_exact() else {
+
+        let dict = if header.has_dict {
+            Some(Dict::deser(&mut bytes)?)
+        } else {
+            None
+        };
+        let huffman = if header.is_huffman_encoded {
+            Some(Huffman::<char>::deser(&mut bytes)?)
+        } else {
+            None
+        };
+
+        let mut bytes = Cursor::new(&bytes);
+        let options = DeserOptions {
+            huffman: huffman.as_ref(),
+            skip_unknown,
+            max_run_len,
+            dict: dict.as_ref(),
+        };
+        let val = Value::deser_inner(&mut bytes, options, max_depth)?;
+        let ty = val.as_ty();
+        let Some(map) = val.to_map() else {
+            return Err(StoreSerError::ExpectedMap(ty));
+        };
+        Ok(Self(map, Observers::default()))
+    }
+
+    ///Gets the number of bytes that this store takes up when serialised using [`Store::ser`].
+    ///
+    /// # Errors
+    /// - [`StoreSerError`] if there is an error serialising the store - see [`Store::ser`].
+    pub fn serialized_size(&self) -> Result<usize, StoreSerError> {
+        self.ser().map(|bytes| bytes.len())
+    }
+
+    ///Gets the [`DbStats`] for this store.
+    ///
+    /// # Errors
+    /// - [`StoreSerError`] if there is an error serialising the store - see [`Store::ser`].
+    pub fn stats(&self) -> Result<DbStats, StoreSerError> {
+        let mut value_types = HashMap::new();
+        for v in self.0.values() {
+            *value_types.entry(v.as_ty()).or_default() += 1;
+        }
+
+        Ok(DbStats {
+            key_count: self.0.len(),
+            serialized_size: self.serialized_size()?,
+            value_types,
+        })
+    }
+
+    ///Gets the value for a key and converts it into `T`, returning `Ok(None)` if the key doesn't exist.
+    ///
+    /// ```rust
+    /// use sourisdb::{store::Store, values::Value};
+    ///
+    /// let mut store = Store::default();
+    /// store.insert("count".into(), Value::from(5_u8));
+    ///
+    /// assert_eq!(store.get_typed::<u32>("count").unwrap(), Some(5));
+    /// assert_eq!(store.get_typed::<u32>("missing").unwrap(), None);
+    /// assert!(store.get_typed::<bool>("count").is_err());
+    /// ```
+    ///
+    /// # Errors
+    /// - [`ValueSerError`] if the value can't be converted into `T`.
+    pub fn get_typed<T: TryFrom<Value, Error = ValueSerError>>(
+        &self,
+        key: &str,
+    ) -> Result<Option<T>, ValueSerError> {
+        self.0.get(key).cloned().map(T::try_from).transpose()
+    }
+
+    ///Inserts a value into the store, converting both the key and the value, and returns the value that was there before if there was one.
+    ///
+    /// ```rust
+    /// use sourisdb::{store::Store, values::Value};
+    ///
+    /// let mut store = Store::default();
+    /// assert_eq!(store.upsert("n", 42_u32), None);
+    /// assert_eq!(store.upsert("n", -7_i64), Some(Value::from(42_u32)));
+    /// assert_eq!(store.get("n"), Some(&Value::from(-7_i64)));
+    /// ```
+    pub fn upsert<T: Into<Value>>(&mut self, key: impl Into<String>, value: T) -> Option<Value> {
+        self.insert(key.into(), value.into())
+    }
+
+    ///Adds a callback which gets called every time an entry is inserted, updated or removed using [`Store::insert`], [`Store::remove`] or any other method on the store (like [`Store::upsert`], [`Store::rename_prefix`], [`Store::clear_and_shrink`], [`Store::flatten_all_json`] and [`Extend`]).
+    ///
+    /// Changes made directly through the underlying [`HashMap`] (using [`DerefMut`], eg. `get_mut`, `retain` or `drain`) bypass the callbacks. Callbacks aren't copied when the store is cloned.
+    ///
+    /// ```rust
+    /// use sourisdb::{store::{ChangeEvent, Store}, values::Value};
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// let events = Arc::new(Mutex::new(vec![]));
+    /// let mut store = Store::default();
+    ///
+    /// let events_in_callback = events.clone();
+    /// store.on_change(Box::new(move |event| {
+    ///     let description = match event {
+    ///         ChangeEvent::Inserted { key, .. } => format!("inserted {key}"),
+    ///         ChangeEvent::Updated { key, .. } => format!("updated {key}"),
+    ///         ChangeEvent::Removed { key, .. } => format!("removed {key}"),
+    ///     };
+    ///     events_in_callback.lock().unwrap().push(description);
+    /// }));
+    ///
+    /// store.insert("a".into(), Value::from(1_u8));
+    /// store.upsert("a", 2_u8);
+    /// store.remove("a");
+    /// store.remove("b"); //nothing to remove, so no event
+    ///
+    /// assert_eq!(*events.lock().unwrap(), ["inserted a", "updated a", "removed a"]);
+    /// ```
+    pub fn on_change(&mut self, f: ChangeCallback) {
+        self.1 .0.push(f);
+    }
+
+    ///Inserts a value into the store, returning the value that was there before if there was one. This calls any callbacks added using [`Store::on_change`].
+    pub fn insert(&mut self, key: String, value: Value) -> Option<Value> {
+        if self.1 .0.is_empty() {
+            return self.0.insert(key, value);
+        }
+
+        let old = self.0.insert(key.clone(), value);
+        let new = &self.0[&key];
+        let event = match &old {
+            Some(old) => ChangeEvent::Updated {
+                key: &key,
+                old,
+                new,
+            },
+            None => ChangeEvent::Inserted {
+                key: &key,
+                value: new,
+            },
+        };
+        self.1.notify(&event);
+
+        old
+    }
+
+    ///Removes a value from the store, returning it if it was there. This calls any callbacks added using [`Store::on_change`].
+    pub fn remove<Q: Hash + Equivalent<String> + ?Sized>(&mut self, key: &Q) -> Option<Value> {
+        let (key, value) = self.0.remove_entry(key)?;
+        self.1.notify(&ChangeEvent::Removed {
+            key: &key,
+            value: &value,
+        });
+        Some(value)
+    }
+
+    ///Iterates over the entries of the store sorted by key, rather than in the arbitrary order of the underlying [`HashMap`].
+    pub fn iter_sorted(&self) -> impl Iterator<Item = (&String, &Value)> {
+        let mut entries: Vec<_> = self.0.iter().collect();
+        entries.sort_unstable_by_key(|(k, _)| *k);
+        entries.into_iter()
+    }
+
+    ///Iterates over all of the keys that start with `prefix`, which is useful for treating `/`-delimited keys (like `user/1/name`) as a namespace.
+    ///
+    /// The keys are stored in a [`HashMap`], so this is a linear scan over every key and the keys come out in an arbitrary order.
+    pub fn keys_with_prefix<'a>(&'a self, prefix: &'a str) -> impl Iterator<Item = &'a String> {
+        self.0.keys().filter(move |k| k.starts_with(prefix))
+    }
+
+    ///Removes every entry whose key starts with `prefix`, returning how many entries were removed.
+    ///
+    /// Like [`Store::keys_with_prefix`], this is a linear scan over every key.
+    ///
+    /// ```rust
+    /// use sourisdb::{store::Store, values::Value};
+    ///
+    /// let mut store = Store::default();
+    /// store.insert("user/1/name".into(), Value::String("alice".into()));
+    /// store.insert("user/1/age".into(), Value::from(30_u8));
+    /// store.insert("user/2/name".into(), Value::String("bob".into()));
+    ///
+    /// assert_eq!(store.keys_with_prefix("user/1/").count(), 2);
+    /// assert_eq!(store.remove_prefix("user/1/"), 2);
+    /// assert_eq!(store.len(), 1);
+    /// ```
+    pub fn remove_prefix(&mut self, prefix: &str) -> usize {
+        let before = self.0.len();
+        let observers = &mut self.1;
+        self.0.retain(|key, value| {
+            if !key.starts_with(prefix) {
+                return true;
+            }
+            observers.notify(&ChangeEvent::Removed { key, value });
+            false
+        });
+        before - self.0.len()
+    }
+
+    ///Removes every entry and frees the memory that held them, unlike [`HashMap::clear`] (available through [`DerefMut`]) which keeps the capacity around to be reused.
+    ///
+    /// This is worth it for stores which were large and are going to stay small, but inserting into the store afterwards has to allocate all over again.
+    ///
+    /// ```rust
+    /// use sourisdb::{store::Store, values::Value};
+    ///
+    /// let mut store: Store = (0..1000)
+    ///     .map(|i| (i.to_string(), Value::from(i)))
+    ///     .collect();
+    ///
+    /// store.clear_and_shrink();
+    /// assert!(store.is_empty());
+    /// assert_eq!(store.capacity(), 0);
+    /// ```
+    pub fn clear_and_shrink(&mut self) {
+        if self.1 .0.is_empty() {
+            self.0.clear();
+        } else {
+            for (key, value) in self.0.drain() {
+                self.1.notify(&ChangeEvent::Removed {
+                    key: &key,
+                    value: &value,
+                });
+            }
+        }
+        self.0.shrink_to_fit();
+    }
+
+    ///Clones the entries for the given keys into a new store. Keys which aren't in this store are skipped.
+    ///
+    /// ```rust
+    /// use sourisdb::{store::Store, values::Value};
+    ///
+    /// let mut store = Store::default();
+    /// store.insert("name".into(), Value::String("alice".into()));
+    /// store.insert("age".into(), Value::from(30_u8));
+    /// store.insert("email".into(), Value::String("alice@example.com".into()));
+    ///
+    /// let subset = store.subset(&["name", "age", "missing"]);
+    /// assert_eq!(subset.len(), 2);
+    /// assert_eq!(subset.get("name"), store.get("name"));
+    /// assert!(subset.get("email").is_none());
+    /// ```
+    #[must_use]
+    pub fn subset(&self, keys: &[&str]) -> Store {
+        keys.iter()
+            .filter_map(|&k| self.0.get_key_value(k))
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect()
+    }
+
+    ///Clones every entry whose key starts with `prefix` into a new store.
+    ///
+    /// Like [`Store::keys_with_prefix`], this is a linear scan over every key.
+    ///
+    /// ```rust
+    /// use sourisdb::{store::Store, values::Value};
+    ///
+    /// let mut store = Store::default();
+    /// store.insert("user/1/name".into(), Value::String("alice".into()));
+    /// store.insert("user/1/age".into(), Value::from(30_u8));
+    /// store.insert("user/2/name".into(), Value::String("bob".into()));
+    ///
+    /// let user = store.subset_with_prefix("user/1/");
+    /// assert_eq!(user.len(), 2);
+    /// assert_eq!(store.len(), 3);
+    /// ```
+    #[must_use]
+    pub fn subset_with_prefix(&self, prefix: &str) -> Store {
+        self.0
+            .iter()
+            .filter(|(k, _)| k.starts_with(prefix))
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect()
+    }
+
+    ///Moves the value at `from` to `to`, without cloning it.
+    ///
+    /// ```rust
+    /// use sourisdb::{store::{RenameError, Store}, values::Value};
+    ///
+    /// let mut store = Store::default();
+    /// store.insert("colour".into(), Value::String("red".into()));
+    /// store.insert("color".into(), Value::String("blue".into()));
+    ///
+    /// assert_eq!(store.rename_key("colour", "color", false), Err(RenameError::DestinationExists));
+    /// assert_eq!(store.rename_key("colour", "color", true), Ok(()));
+    /// assert_eq!(store.get("color"), Some(&Value::String("red".into())));
+    /// assert!(store.get("colour").is_none());
+    /// ```
+    ///
+    /// # Errors
+    /// - [`RenameError::SourceMissing`] if there's nothing at `from`.
+    /// - [`RenameError::DestinationExists`] if there's already a value at `to` and `overwrite` isn't set. The store is left untouched.
+    pub fn rename_key(&mut self, from: &str, to: &str, overwrite: bool) -> Result<(), RenameError> {
+        if !self.0.contains_key(from) {
+            return Err(RenameError::SourceMissing);
+        }
+        if from == to {
+            return Ok(());
+        }
+        if !overwrite && self.0.contains_key(to) {
+            return Err(RenameError::DestinationExists);
+        }
+
+        if let Some(value) = self.remove(from) {
+            self.insert(to.to_string(), value);
+        }
+        Ok(())
+    }
+
+    ///Replaces `old` with `new` at the start of every key that starts with `old`, returning how many entries were renamed. This is useful for migrating a namespace of `/`-delimited keys.
+    ///
+    /// If a renamed key is the same as a key already in the store (that didn't start with `old`), the renamed entry overwrites it. Like [`Store::keys_with_prefix`], this is a linear scan over every key.
+    ///
+    /// ```rust
+    /// use sourisdb::{store::Store, values::Value};
+    ///
+    /// let mut store = Store::default();
+    /// store.insert("user/1/name".into(), Value::String("alice".into()));
+    /// store.insert("user/1/age".into(), Value::from(30_u8));
+    /// store.insert("user/2/name".into(), Value::String("bob".into()));
+    ///
+    /// assert_eq!(store.rename_prefix("user/", "account/"), 3);
+    /// assert_eq!(store.keys_with_prefix("account/").count(), 3);
+    /// assert_eq!(store.get("account/2/name"), Some(&Value::String("bob".into())));
+    /// ```
+    pub fn rename_prefix(&mut self, old: &str, new: &str) -> usize {
+        if old == new {
+            return self.keys_with_prefix(old).count();
+        }
+
+        //take every entry out first, so that keys which become other matching keys (eg. `a` -> `ab`) aren't renamed twice
+        let matching: Vec<String> = self.keys_with_prefix(old).cloned().collect();
+        let renamed: Vec<(String, Value)> = matching
+            .into_iter()
+            .filter_map(|k| {
+                let value = self.remove(&k)?;
+                Some((format!("{new}{}", &k[old.len()..]), value))
+            })
+            .collect();
+
+        let count = renamed.len();
+        self.extend(renamed);
+        count
+    }
+
+    ///Iterates over the entries whose keys are integers within `lo..=hi`, in numeric order - see [`Value::map_range`].
+    ///
+    /// Returns [`None`] if either bound isn't an integer.
+    ///
+    /// ```rust
+    /// use sourisdb::{store::Store, values::Value};
+    ///
+    /// let mut store = Store::default();
+    /// for ts in [1_700_000_300_u64, 1_700_000_000, 1_700_000_600] {
+    ///     store.insert(ts.to_string(), Value::from(ts % 7));
+    /// }
+    ///
+    /// let window: Vec<_> = store
+    ///     .range_by_int_key("1700000000", "1700000300")
+    ///     .unwrap()
+    ///     .map(|(k, _)| k.as_str())
+    ///     .collect();
+    /// assert_eq!(window, ["1700000000", "1700000300"]);
+    /// ```
+    #[must_use]
+    pub fn range_by_int_key<'a>(
+        &'a self,
+        lo: &str,
+        hi: &str,
+    ) -> Option<impl Iterator<Item = (&'a String, &'a Value)>> {
+        int_key_range(self.0.iter().collect(), lo, hi)
+    }
+
+    ///Checks that the store has all of the keys required by `schema`, with values of the right types.
+    ///
+    /// # Errors
+    /// Every [`SchemaError`] found, sorted by path - see [`Schema::validate_map`].
+    pub fn validate(&self, schema: &Schema) -> Result<(), Vec<SchemaError>> {
+        schema.validate_map(&self.0)
+    }
+
+    ///Converts every [`Value::JSON`] in the store into native [`Value`]s using [`Value::flatten_json`], which is useful after importing JSON-heavy data to make it serialise more compactly.
+    ///
+    /// This calls any callbacks added using [`Store::on_change`] with an update for every value that changed.
+    pub fn flatten_all_json(&mut self) {
+        if self.1 .0.is_empty() {
+            for v in self.0.values_mut() {
+                *v = core::mem::replace(v, Value::Null(())).flatten_json();
+            }
+            return;
+        }
+
+        for (key, v) in &mut self.0 {
+            let old = v.clone();
+            *v = core::mem::replace(v, Value::Null(())).flatten_json();
+            if old != *v {
+                self.1.notify(&ChangeEvent::Updated {
+                    key,
+                    old: &old,
+                    new: v,
+                });
+            }
+        }
+    }
+
+    ///Hashes the contents of the store using [`Fnv1a`].
+    ///
+    /// Keys are sorted before hashing, so two equal stores will always have the same hash regardless of insertion order, and the hash will be the same between runs. This makes it useful for things like caching.
+    #[must_use]
+    pub fn content_hash(&self) -> u64 {
+        let mut hasher = Fnv1a::default();
+        for (k, v) in self.iter_sorted() {
+            k.hash(&mut hasher);
+            v.hash(&mut hasher);
+        }
+        hasher.finish()
+    }
+
+    ///Gets a store back from bytes that represent JSON.
+    ///
+    /// # Errors
+    ///
+    /// - [`serde_json::Error`] if we cannot parse the JSON.
+    pub fn from_json_bytes(json: &[u8]) -> Result<Self, StoreSerError> {
+        let val = serde_json::from_slice(json)?;
+        Self::from_json(val)
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn from_bytes<T: serde::de::DeserializeOwned>(bytes: &[u8]) -> Result<T, StoreSerError> {
+        let s = Self::deser(bytes)?;
+        let v = s
+            .to_json(false)
+            .ok_or(StoreSerError::UnableToConvertToJson)?;
+        Ok(serde_json::from_value(v)?)
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn to_bytes(t: &impl serde::Serialize) -> Result<Vec<u8>, StoreSerError> {
+        let v = serde_json::to_value(t)?;
+        let s = Self::from_json(v)?;
+        s.ser()
+    }
+
+    ///Converts the store to JSON, consuming it - see [`Store::to_json_value`] for a version which borrows the store instead.
+    ///
+    /// Each value is converted using [`Value::convert_to_json`], so `souris_type`s are only added if `add_souris_types` is set. A store with only a `JSON` key (like one made by [`Store::from_json`] from something other than an object) becomes that value rather than an object.
+    ///
+    /// Returns [`None`] if an integer is out of range for JSON, or a float is NaN or infinite.
+    #[must_use]
+    pub fn to_json(mut self, add_souris_types: bool) -> Option<SJValue> {
+        if self.len() == 1 {
+            if let Some(v) = self.0.remove("JSON") {
+                return v.convert_to_json(add_souris_types);
+            }
+        }
+
+        Some(SJValue::Object(
+            self.0
+                .into_iter()
+                .map(|(k, v)| v.convert_to_json(add_souris_types).map(|v| (k, v)))
+                .collect::<Option<_>>()?,
+        ))
+    }
+
+    ///Converts the store to JSON without consuming it, so the JSON can be looked at before it gets turned into a string without having to clone the store first. This gives the same JSON as [`Store::to_json`].
+    ///
+    /// NB: [`Store`] doesn't implement `serde::Serialize`, and the implementation for [`Value`] never adds `souris_type`s as it is for round-tripping through any `serde` format - use this (or [`Store::to_json`]) to get plain JSON.
+    ///
+    /// ```rust
+    /// use sourisdb::{serde_json::json, store::Store, values::Value};
+    ///
+    /// let mut store = Store::default();
+    /// store.insert("name".into(), Value::String("souris".into()));
+    ///
+    /// let json = store.to_json_value(false).unwrap();
+    /// assert_eq!(json, json!({"name": "souris"}));
+    /// assert_eq!(store.to_json(false).unwrap(), json);
+    /// ```
+    ///
+    /// Returns [`None`] if an integer is out of range for JSON, or a float is NaN or infinite.
+    #[must_use]
+    pub fn to_json_value(&self, add_souris_types: bool) -> Option<SJValue> {
+        if self.len() == 1 {
+            if let Some(v) = self.0.get("JSON") {
+                return v.to_json_value(add_souris_types);
+            }
+        }
+
+        Some(SJValue::Object(
+            self.0
+                .iter()
+                .map(|(k, v)| v.to_json_value(add_souris_types).map(|v| (k.clone(), v)))
+                .collect::<Option<_>>()?,
+        ))
+    }
+
+    pub fn from_json(val: SJValue) -> Result<Self, StoreSerError> {
+        Ok(Self(
+            match Value::convert_from_json(val)? {
+                Value::Map(m) => m,
+                v => {
+                    let mut map = HashMap::new();
+                    map.insert("JSON".into(), v);
+                    map
+                }
+            },
+            Observers::default(),
+        ))
+    }
+}
+
+impl TryFrom<Value> for Store {
+    type Error = StoreSerError;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        let ty = value.as_ty();
+        let Some(db) = value.to_map() else {
+            return Err(StoreSerError::ExpectedMap(ty));
+        };
+        Ok(Self(db, Observers::default()))
+    }
+}
+
+impl Deref for Store {
+    type Target = HashMap<String, Value>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+impl DerefMut for Store {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+///Collects key-value pairs into a new [`Store`]. If a key appears more than once, the last value wins.
+///
+/// ```rust
+/// use sourisdb::{store::Store, values::Value};
+///
+/// let pairs = vec![
+///     ("name".to_string(), Value::String("souris".into())),
+///     ("is_cool".to_string(), Value::Boolean(true)),
+/// ];
+///
+/// let store: Store = pairs.into_iter().collect();
+/// assert_eq!(store.len(), 2);
+/// assert_eq!(store.get("is_cool"), Some(&Value::Boolean(true)));
+/// ```
+impl FromIterator<(String, Value)> for Store {
+    fn from_iter<T: IntoIterator<Item = (String, Value)>>(iter: T) -> Self {
+        Self(iter.into_iter().collect(), Observers::default())
+    }
+}
+
+impl Extend<(String, Value)> for Store {
+    fn extend<T: IntoIterator<Item = (String, Value)>>(&mut self, iter: T) {
+        if self.1 .0.is_empty() {
+            self.0.extend(iter);
+            return;
+        }
+
+        for (k, v) in iter {
+            self.insert(k, v);
+        }
+    }
+}
+
+impl Display for Store {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", Value::Map(self.0.clone()))
+    }
+}
+
+///The reasons that [`Store::rename_key`] can fail.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum RenameError {
+    ///There wasn't a value at the key being renamed.
+    SourceMissing,
+    ///There was already a value at the new key, and overwriting wasn't requested.
+    DestinationExists,
+}
+
+impl Display for RenameError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::SourceMissing => write!(f, "Unable to find key to rename"),
+            Self::DestinationExists => write!(f, "New key already exists"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for RenameError {}
+
+///The version of the format that [`Store::ser`] writes, stored in the byte after the magic bytes. [`Store::deser`] rejects any stores with a newer version, as it can't know how to read them.
+///
+/// Version `0` is used for stores from before the version byte existed, version `2` added the checksum at the end, version `3` limited how long a run of one repeated byte in huffman-compressed binary can be (see [`MAX_RUN_LEN`]), version `4` added payloads which are compressed in frames (see [`Store::ser_to_writer`]), and version `5` added a [`Dict`] for compressing the binary values in the store (see [`Store::ser`]).
+pub const STORE_FORMAT_VERSION: u8 = 5;
+
+///Set in the version byte, so that it can be told apart from the compression byte that came straight after the magic bytes in version `0` - that only ever uses the top bit and the bottom two bits.
+const VERSION_MARKER: u8 = 0b0100_0000;
+
+///Set in the compression byte (from version `4`) when the payload is split into frames, each starting with its own [`BinaryCompression`] and ending with an empty frame. The compression in the rest of the byte is then [`BinaryCompression::Nothing`].
+const FRAMED_MARKER: u8 = 0b0100_0000;
+
+///Set in the compression byte (from version `5`) when the payload starts with a [`Dict`], which the binary values in the store may be compressed with. The compression is then in the bottom 5 bits of the byte.
+const DICT_MARKER: u8 = 0b0010_0000;
+
+///Binary values longer than this aren't used to train the [`Dict`] for a store, as they usually compress well enough by themselves.
+const MAX_DICT_SAMPLE_LEN: usize = 4 * 1024;
+
+///The most bytes of binary values used to train the [`Dict`] for a store, so that big stores don't take too long to train on.
+const MAX_DICT_TRAINING_LEN: usize = 1024 * 1024;
+
+///The most uncompressed bytes in one frame from [`Store::ser_to_writer`].
+pub const FRAME_LEN: usize = 1024 * 1024;
+
+///The parts of the header of a serialised [`Store`], from [`Store::read_header`].
+struct Header {
+    version: u8,
+    ///The number of bytes in the header, including the magic bytes.
+    len: usize,
+    is_huffman_encoded: bool,
+    ///Whether the payload is split into frames - see [`FRAMED_MARKER`].
+    is_framed: bool,
+    ///Whether the payload starts with a [`Dict`] - see [`DICT_MARKER`].
+    has_dict: bool,
+    compression: BinaryCompression,
+}
+
+impl Header {
+    ///The longest run of one repeated byte that huffman-compressed binary can ask for in this store - older versions wrote runs of any length, so they can't be limited.
+    fn max_run_len(&self) -> usize {
+        if self.version < 3 {
+            usize::MAX
+        } else {
+            MAX_RUN_LEN
+        }
+    }
+
+    ///Decompresses the payload of a store, which is everything between the header and the checksum.
+    fn decompress(&self, body: &[u8]) -> Result<Vec<u8>, StoreSerError> {
+        let mut cursor = Cursor::new(&body);
+        if !self.is_framed {
+            return Ok(BinaryData::deser_with_max_run_len(
+                self.compression,
+                &mut cursor,
+                self.max_run_len(),
+            )?
+            .0);
+        }
+
+        let mut payload = vec![];
+        loop {
+            let Some(compression) = cursor.next().copied() else {
                 return Err(StoreSerError::NotEnoughBytes);
             };
-            if magic_bytes != b"SOURISDB" {
-                return Err(StoreSerError::ExpectedMagicBytes);
+            let frame = BinaryData::deser_with_max_run_len(
+                BinaryCompression::try_from(compression)?,
+                &mut cursor,
+                self.max_run_len(),
+            )?;
+            if frame.is_empty() {
+                return Ok(payload);
             }
+            payload.extend(frame.0);
         }
-        let Some(compression) = bytes.next().copied() else {
-            return Err(StoreSerError::NotEnoughBytes);
-        };
-        let is_huffman_encoded = (compression & 0b1000_0000) != 0;
-        let compression_ty = BinaryCompression::try_from(compression & 0b0111_1111)?;
-
-        let bytes = BinaryData::deser(compression_ty, &mut bytes)?.0;
-        let mut bytes = Cursor::new(&bytes);
-
-        let huffman = if is_huffman_encoded {
-            Some(Huffman::<char>::deser(&mut bytes)?)
-        } else {
-            None
-        };
-
-        let val = Value::deser(&mut Cursor::new(&bytes), huffman.as_ref())?;
-        let ty = val.as_ty();
-        let Some(map) = val.to_map() else {
-            return Err(StoreSerError::ExpectedMap(ty));
-        };
-        Ok(Self(map))
     }
+}
 
-    ///Gets a store back from bytes that represent JSON.
-    ///
-    /// # Errors
-    ///
-    /// - [`serde_json::Error`] if we cannot parse the JSON.
-    pub fn from_json_bytes(json: &[u8]) -> Result<Self, StoreSerError> {
-        let val = serde_json::from_slice(json)?;
-        Self::from_json(val)
+///Compresses and writes the payload of a store a frame at a time, for [`Store::ser_to_writer`].
+#[cfg(feature = "std")]
+struct FrameWriter<'a, W> {
+    w: &'a mut W,
+    ///The checksum of everything written so far.
+    crc: Crc32,
+    ///Bytes waiting to fill up the next frame.
+    frame: Vec<u8>,
+}
+
+#[cfg(feature = "std")]
+impl<'a, W: std::io::Write> FrameWriter<'a, W> {
+    fn new(w: &'a mut W) -> Self {
+        Self {
+            w,
+            crc: Crc32::default(),
+            frame: Vec::new(),
+        }
     }
 
-    #[cfg(feature = "serde")]
-    pub fn from_bytes<T: serde::de::DeserializeOwned>(bytes: &[u8]) -> Result<T, StoreSerError> {
-        let s = Self::deser(bytes)?;
-        let v = s
-            .to_json(false)
-            .ok_or(StoreSerError::UnableToConvertToJson)?;
-        Ok(serde_json::from_value(v)?)
+    ///Writes bytes straight through, without putting them into a frame.
+    fn write(&mut self, bytes: &[u8]) -> std::io::Result<()> {
+        self.crc.write(bytes);
+        self.w.write_all(bytes)
     }
 
-    #[cfg(feature = "serde")]
-    pub fn to_bytes(t: &impl serde::Serialize) -> Result<Vec<u8>, StoreSerError> {
-        let v = serde_json::to_value(t)?;
-        let s = Self::from_json(v)?;
-        s.ser()
+    ///Compresses and writes one frame.
+    fn write_frame(&mut self, frame: &[u8]) -> std::io::Result<()> {
+        let (compression, bytes) = BinaryData::from(frame).ser();
+        self.write(&[u8::from(compression)])?;
+        self.write(&bytes)
     }
 
-    ///fails if integer out of range, or float is NaN or infinite
-    #[must_use]
-    pub fn to_json(mut self, add_souris_types: bool) -> Option<SJValue> {
-        if self.len() == 1 {
-            if let Some(v) = self.0.remove("JSON") {
-                return v.convert_to_json(add_souris_types);
+    ///Adds bytes to the payload, writing out any frames which are now full. Whole frames are compressed straight from `bytes` rather than being copied into the buffer first.
+    fn push(&mut self, mut bytes: &[u8]) -> std::io::Result<()> {
+        if !self.frame.is_empty() {
+            let take = (FRAME_LEN - self.frame.len()).min(bytes.len());
+            self.frame.extend_from_slice(&bytes[..take]);
+            bytes = &bytes[take..];
+            if self.frame.len() < FRAME_LEN {
+                return Ok(());
             }
+
+            let frame = core::mem::take(&mut self.frame);
+            self.write_frame(&frame)?;
+            self.frame = frame;
+            self.frame.clear();
         }
 
-        Some(SJValue::Object(
-            self.0
-                .into_iter()
-                .map(|(k, v)| v.convert_to_json(add_souris_types).map(|v| (k, v)))
-                .collect::<Option<_>>()?,
-        ))
+        let mut frames = bytes.chunks_exact(FRAME_LEN);
+        for frame in &mut frames {
+            self.write_frame(frame)?;
+        }
+        self.frame.extend_from_slice(frames.remainder());
+        Ok(())
     }
 
-    pub fn from_json(val: SJValue) -> Result<Self, StoreSerError> {
-        Ok(Self(match Value::convert_from_json(val)? {
-            Value::Map(m) => m,
-            v => {
-                let mut map = HashMap::new();
-                map.insert("JSON".into(), v);
-                map
-            }
-        }))
+    ///Writes out the last frame, then the empty frame which marks the end, and then the checksum.
+    fn finish(mut self) -> std::io::Result<()> {
+        if !self.frame.is_empty() {
+            let frame = core::mem::take(&mut self.frame);
+            self.write_frame(&frame)?;
+        }
+        self.write(&[u8::from(BinaryCompression::Nothing)])?;
+        self.write(&BinaryData(vec![]).ser_with(BinaryCompression::Nothing))?;
+
+        self.w.write_all(&self.crc.finish().to_le_bytes())
     }
 }
 
-impl TryFrom<Value> for Store {
-    type Error = StoreSerError;
+///Where each section of a serialised [`Store`] is, found using [`Store::layout`].
+///
+/// After the header (the magic bytes, the version and then the compression), the rest of the store is compressed, apart from the checksum in the last 4 bytes. Once decompressed, that payload holds the [`Dict`] for binary values (if there is one), then the huffman tree (if there is one), followed by the values.
+#[derive(Debug, Clone)]
+pub struct StoreLayout {
+    ///The version of the format - see [`STORE_FORMAT_VERSION`].
+    pub version: u8,
+    ///The number of bytes before the compressed payload.
+    pub header_len: usize,
+    ///Whether the payload is split into frames which are each compressed separately, as written by [`Store::ser_to_writer`].
+    pub is_framed: bool,
+    ///The compression used on the payload, which is always [`BinaryCompression::Nothing`] if it is split into frames.
+    pub compression: BinaryCompression,
+    ///The decompressed payload.
+    pub payload: Vec<u8>,
+    ///Where the [`Dict`] for binary values is in the payload, if the store has one.
+    pub dict: Option<Range<usize>>,
+    ///Where the huffman tree is in the payload, if the store has one.
+    pub huffman: Option<Range<usize>>,
+    ///Where the values (serialised as a [`Value::Map`]) are in the payload.
+    pub values: Range<usize>,
+}
 
-    fn try_from(value: Value) -> Result<Self, Self::Error> {
-        let ty = value.as_ty();
-        let Some(db) = value.to_map() else {
-            return Err(StoreSerError::ExpectedMap(ty));
+///Statistics about a database in `sourisd`, which can be gathered without transferring the whole database.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DbStats {
+    ///The number of keys in the database.
+    pub key_count: usize,
+    ///The number of bytes the database takes up when serialised.
+    pub serialized_size: usize,
+    ///How many of the top-level values are of each type.
+    pub value_types: HashMap<ValueTy, usize>,
+}
+
+impl DbStats {
+    ///Converts the stats into JSON, with the `value_types` keyed by the name of each [`ValueTy`].
+    #[must_use]
+    pub fn to_json(&self) -> SJValue {
+        let value_types = self
+            .value_types
+            .iter()
+            .map(|(ty, count)| (format!("{ty:?}"), SJValue::Number(Number::from(*count))))
+            .collect();
+
+        let mut obj = SJMap::new();
+        obj.insert(
+            "key_count".into(),
+            SJValue::Number(Number::from(self.key_count)),
+        );
+        obj.insert(
+            "serialized_size".into(),
+            SJValue::Number(Number::from(self.serialized_size)),
+        );
+        obj.insert("value_types".into(), SJValue::Object(value_types));
+        SJValue::Object(obj)
+    }
+
+    ///Gets the stats back from JSON created by [`DbStats::to_json`].
+    ///
+    /// # Errors
+    /// - [`StoreSerError::InvalidDbStats`] if any of the fields are missing or invalid.
+    pub fn from_json(json: &SJValue) -> Result<Self, StoreSerError> {
+        let get_usize = |v: &SJValue| {
+            v.as_u64()
+                .and_then(|n| usize::try_from(n).ok())
+                .ok_or(StoreSerError::InvalidDbStats)
+        };
+        let field = |name: &str| json.get(name).ok_or(StoreSerError::InvalidDbStats);
+
+        let key_count = get_usize(field("key_count")?)?;
+        let serialized_size = get_usize(field("serialized_size")?)?;
+
+        let SJValue::Object(types_obj) = field("value_types")? else {
+            return Err(StoreSerError::InvalidDbStats);
         };
-        Ok(Self(db))
+
+        let mut value_types = HashMap::new();
+        for (name, count) in types_obj {
+            let ty = ValueTy::from_name(name).ok_or(StoreSerError::InvalidDbStats)?;
+            value_types.insert(ty, get_usize(count)?);
+        }
+
+        Ok(Self {
+            key_count,
+            serialized_size,
+            value_types,
+        })
     }
 }
 
-impl Deref for Store {
-    type Target = HashMap<String, Value>;
+///The result of inserting one key into a database in `sourisd`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum CreationResult {
+    ///The key didn't exist before, and now does.
+    InsertedNew,
+    ///The key already existed, and the value was overwritten.
+    Overwritten,
+    ///The key already existed, and overwriting wasn't requested so the old value was kept.
+    LeftAlone,
+}
 
-    fn deref(&self) -> &Self::Target {
-        &self.0
+impl From<CreationResult> for u8 {
+    fn from(value: CreationResult) -> Self {
+        match value {
+            CreationResult::InsertedNew => 0,
+            CreationResult::Overwritten => 1,
+            CreationResult::LeftAlone => 2,
+        }
     }
 }
-impl DerefMut for Store {
-    fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.0
+impl TryFrom<u8> for CreationResult {
+    type Error = StoreSerError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        Ok(match value {
+            0 => Self::InsertedNew,
+            1 => Self::Overwritten,
+            2 => Self::LeftAlone,
+            _ => return Err(StoreSerError::InvalidCreationResult(value)),
+        })
     }
 }
 
-impl Display for Store {
-    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
-        write!(f, "{}", Value::Map(self.0.clone()))
+impl From<CreationResult> for Value {
+    fn from(value: CreationResult) -> Self {
+        Value::Integer(Integer::u8(value.into()))
+    }
+}
+impl TryFrom<Value> for CreationResult {
+    type Error = StoreSerError;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        let discriminant: u8 = value.try_into()?;
+        Self::try_from(discriminant)
     }
 }
 
@@ -230,6 +1494,26 @@ pub enum StoreSerError {
     UnsupportedCompression(u8),
     Huffman(HuffmanSerError),
     Binary(BinarySerError),
+    InvalidCreationResult(u8),
+    InvalidDbStats,
+    ///The store was serialised with a newer format version than this version of the crate supports - see [`STORE_FORMAT_VERSION`].
+    UnsupportedVersion(u8),
+    ///The checksum at the end of the store didn't match the rest of it, so it must have been corrupted.
+    ChecksumMismatch {
+        ///The checksum stored at the end of the store.
+        expected: u32,
+        ///The checksum of the bytes we actually found.
+        found: u32,
+    },
+    ///A frame in an entry stream was longer than the decoder allows - see [`stream::EntryStreamDecoder::with_max_frame_len`].
+    FrameTooLong {
+        ///How long the frame said it was.
+        len: usize,
+        ///The longest frame the decoder allows.
+        max: usize,
+    },
+    #[cfg(feature = "std")]
+    Io(std::io::Error),
 }
 
 impl Display for StoreSerError {
@@ -250,6 +1534,24 @@ impl Display for StoreSerError {
             }
             StoreSerError::Huffman(h) => write!(f, "Error with huffman: {h}"),
             StoreSerError::Binary(b) => write!(f, "Error with binary compression: {b}"),
+            StoreSerError::InvalidCreationResult(b) => {
+                write!(f, "Invalid creation result discriminant found: {b}")
+            }
+            StoreSerError::InvalidDbStats => write!(f, "Unable to read database stats"),
+            StoreSerError::UnsupportedVersion(v) => write!(
+                f,
+                "Store uses format version {v}, but only versions up to {STORE_FORMAT_VERSION} are supported"
+            ),
+            StoreSerError::ChecksumMismatch { expected, found } => write!(
+                f,
+                "Store is corrupted - expected checksum {expected:#010x}, found {found:#010x}"
+            ),
+            StoreSerError::FrameTooLong { len, max } => write!(
+                f,
+                "Streamed entry is {len} bytes long, but at most {max} bytes are allowed"
+            ),
+            #[cfg(feature = "std")]
+            StoreSerError::Io(e) => write!(f, "Error with IO: {e}"),
         }
     }
 }
@@ -269,6 +1571,11 @@ impl From<IntegerSerError> for StoreSerError {
         Self::Integer(value)
     }
 }
+impl From<CursorError> for StoreSerError {
+    fn from(_: CursorError) -> Self {
+        Self::NotEnoughBytes
+    }
+}
 impl From<HuffmanSerError> for StoreSerError {
     fn from(value: HuffmanSerError) -> Self {
         Self::Huffman(value)
@@ -279,6 +1586,12 @@ impl From<BinarySerError> for StoreSerError {
         Self::Binary(value)
     }
 }
+#[cfg(feature = "std")]
+impl From<std::io::Error> for StoreSerError {
+    fn from(value: std::io::Error) -> Self {
+        Self::Io(value)
+    }
+}
 
 #[cfg(feature = "std")]
 impl std::error::Error for StoreSerError {
@@ -288,7 +1601,697 @@ impl std::error::Error for StoreSerError {
             Self::Value(e) => Some(e),
             Self::SerdeJson(e) => Some(e),
             Self::Huffman(h) => Some(h),
-            _ => None,
+            Self::Binary(b) => Some(b),
+            Self::Io(e) => Some(e),
+            Self::ExpectedMap(_)
+            | Self::ExpectedMagicBytes
+            | Self::NotEnoughBytes
+            | Self::UnableToConvertToJson
+            | Self::UnsupportedCompression(_)
+            | Self::UnsupportedVersion(_)
+            | Self::ChecksumMismatch { .. }
+            | Self::FrameTooLong { .. }
+            | Self::InvalidCreationResult(_)
+            | Self::InvalidDbStats => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{boxed::Box, string::ToString, vec, vec::Vec};
+
+    use hashbrown::HashMap;
+    use serde_json::Value as SJValue;
+
+    use super::{DbStats, RenameError, Store, StoreSerError, STORE_FORMAT_VERSION, VERSION_MARKER};
+    use crate::utilities::{
+        crc32::Crc32,
+        cursor::Cursor,
+        huffman::{Huffman, HuffmanSerError},
+    };
+    use crate::{
+        types::{
+            binary::{BinaryCompression, BinaryData, BinarySerError},
+            imaginary::Imaginary,
+            integer::Integer,
+        },
+        values::{Value, ValueSerError, ValueTy},
+    };
+    use proptest::proptest;
+
+    #[test]
+    #[cfg(feature = "mmap")]
+    fn test_deser_mmap() {
+        let mut store = Store::default();
+        store.insert("key".to_string(), Value::String("value".to_string()));
+        store.insert("other".to_string(), Value::Boolean(true));
+
+        let path =
+            std::env::temp_dir().join(alloc::format!("souris_mmap_{}.sdb", std::process::id()));
+        std::fs::write(&path, store.ser().unwrap()).unwrap();
+
+        //SAFETY: nothing else knows about this file
+        let read = unsafe { Store::deser_mmap(&path) };
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(read.unwrap(), store);
+    }
+
+    #[test]
+    fn test_single_character_text_round_trips() {
+        //all of the text uses one character, so the huffman tree only has one leaf and can't be used
+        let mut store = Store::default();
+        store.insert("a".to_string(), Value::String("aaaa".to_string()));
+
+        assert_eq!(Store::deser(&store.ser().unwrap()).unwrap(), store);
+    }
+
+    #[test]
+    fn test_content_hash_is_order_independent() {
+        let keys = [
+            "zebra", "apple", "mango", "banana", "kiwi", "cherry", "fig", "date",
+        ];
+
+        let mut forwards = Store::default();
+        let mut backwards = Store::default();
+        for (i, k) in keys.iter().enumerate() {
+            let nested: HashMap<_, _> = keys
+                .iter()
+                .take(i)
+                .map(|k| (k.to_string(), Value::from(i)))
+                .collect();
+            forwards.insert(k.to_string(), Value::Map(nested));
+        }
+        for (i, k) in keys.iter().enumerate().rev() {
+            let nested: HashMap<_, _> = keys
+                .iter()
+                .take(i)
+                .rev()
+                .map(|k| (k.to_string(), Value::from(i)))
+                .collect();
+            backwards.insert(k.to_string(), Value::Map(nested));
+        }
+
+        assert_eq!(forwards, backwards);
+        assert_eq!(forwards.content_hash(), backwards.content_hash());
+
+        backwards.insert("extra".to_string(), Value::Null(()));
+        assert_ne!(forwards.content_hash(), backwards.content_hash());
+    }
+
+    #[test]
+    fn test_stats_json_round_trip() {
+        let mut store = Store::default();
+        store.insert("a".to_string(), Value::from(1_u8));
+        store.insert("b".to_string(), Value::from(2_u8));
+        store.insert("c".to_string(), Value::Null(()));
+
+        let stats = store.stats().unwrap();
+        assert_eq!(stats.key_count, 3);
+        assert_eq!(stats.serialized_size, store.ser().unwrap().len());
+        assert_eq!(stats.value_types[&ValueTy::Integer], 2);
+        assert_eq!(stats.value_types[&ValueTy::Null], 1);
+
+        assert_eq!(DbStats::from_json(&stats.to_json()).unwrap(), stats);
+    }
+
+    #[test]
+    fn test_iter_sorted() {
+        let mut store = Store::default();
+        for k in ["c", "a", "d", "b"] {
+            store.insert(k.to_string(), Value::Null(()));
+        }
+
+        let keys: Vec<_> = store.iter_sorted().map(|(k, _)| k.as_str()).collect();
+        assert_eq!(keys, ["a", "b", "c", "d"]);
+    }
+
+    #[test]
+    fn test_deser_rejects_impossible_lz_length() {
+        let mut bytes = b"SOURISDB".to_vec();
+        bytes.push(2); //LZ, no huffman
+        bytes.extend(Integer::usize(usize::MAX).ser().1); //uncompressed length
+        bytes.extend(Integer::usize(1).ser().1); //compressed length
+        bytes.push(0);
+
+        assert!(Store::deser(&bytes).is_err());
+    }
+
+    #[test]
+    fn test_format_version() {
+        let mut store = Store::default();
+        store.insert("key".to_string(), Value::String("value".to_string()));
+        let bytes = store.ser().unwrap();
+
+        //stores from before the checksum are the same, just without the last 4 bytes
+        let mut unchecked = bytes[..bytes.len() - 4].to_vec();
+        unchecked[8] = VERSION_MARKER | 1;
+        assert_eq!(Store::deser(&unchecked).unwrap(), store);
+        assert_eq!(Store::layout(&unchecked).unwrap().version, 1);
+
+        //and stores from before the version byte are the same again, just without that byte
+        let mut legacy = unchecked;
+        legacy.remove(8);
+        assert_eq!(Store::deser(&legacy).unwrap(), store);
+        assert_eq!(Store::layout(&legacy).unwrap().version, 0);
+
+        let mut future = bytes;
+        future[8] = VERSION_MARKER | (STORE_FORMAT_VERSION + 1);
+        assert!(matches!(
+            Store::deser(&future),
+            Err(StoreSerError::UnsupportedVersion(v)) if v == STORE_FORMAT_VERSION + 1
+        ));
+    }
+
+    #[test]
+    fn test_long_runs_only_read_from_old_versions() {
+        use crate::types::binary::huffman::MAX_RUN_LEN;
+
+        //older versions stored any run of one repeated byte as just the length and the byte
+        let mut run = vec![
+            ValueTy::Binary.to_type_byte() | u8::from(BinaryCompression::Huffman),
+            1,
+        ];
+        run.extend(Integer::usize(MAX_RUN_LEN + 1).ser().1);
+        run.push(7);
+        //replace the null at the end of a single-key map with the run
+        let mut map = Value::Map([("run".into(), Value::Null(()))].into_iter().collect()).ser(None);
+        map.pop();
+        map.extend(run);
+
+        let with_version = |version: u8| {
+            let mut bytes = b"SOURISDB".to_vec();
+            bytes.push(VERSION_MARKER | version);
+            bytes.push(u8::from(BinaryCompression::Nothing));
+            bytes.extend(BinaryData(map.clone()).ser_with(BinaryCompression::Nothing));
+            bytes.extend(Crc32::checksum(&bytes).to_le_bytes());
+            bytes
+        };
+
+        let store = Store::deser(&with_version(2)).unwrap();
+        assert_eq!(
+            store["run"],
+            Value::Binary(BinaryData(vec![7; MAX_RUN_LEN + 1]))
+        );
+
+        assert!(matches!(
+            Store::deser(&with_version(3)),
+            Err(StoreSerError::Value(ValueSerError::BinarySerError(BinarySerError::Huffman(
+                HuffmanSerError::RunTooLong(n)
+            )))) if n == MAX_RUN_LEN + 1
+        ));
+    }
+
+    #[test]
+    fn test_to_json_value_matches_to_json() {
+        let mut store = Store::default();
+        store.insert(
+            "ordered".to_string(),
+            Value::OrderedMap(vec![
+                ("b".to_string(), Value::from(1_u8)),
+                ("a".to_string(), Value::Array(vec![Value::Null(())])),
+            ]),
+        );
+        store.insert(
+            "imaginary".to_string(),
+            Value::Imaginary(Imaginary::CartesianForm {
+                real: Integer::u8(1),
+                imaginary: Integer::i8(-1),
+            }),
+        );
+
+        for add_souris_types in [true, false] {
+            assert_eq!(
+                store.to_json_value(add_souris_types),
+                store.clone().to_json(add_souris_types)
+            );
+        }
+
+        let mut json_only = Store::default();
+        json_only.insert("JSON".to_string(), Value::from(5_u8));
+        assert_eq!(json_only.to_json_value(false), Some(SJValue::from(5_u8)));
+
+        store.insert("nan".to_string(), Value::DoubleFloat(f64::NAN));
+        assert_eq!(store.to_json_value(false), None);
+    }
+
+    #[test]
+    fn test_checksum() {
+        let mut store = Store::default();
+        store.insert("key".to_string(), Value::String("value".to_string()));
+        let bytes = store.ser().unwrap();
+
+        let mut flipped = bytes.clone();
+        let last_payload_byte = flipped.len() - 5;
+        flipped[last_payload_byte] ^= 0b1000;
+        assert!(matches!(
+            Store::deser(&flipped),
+            Err(StoreSerError::ChecksumMismatch { .. })
+        ));
+
+        assert!(matches!(
+            Store::deser(&bytes[..bytes.len() - 1]),
+            Err(StoreSerError::ChecksumMismatch { .. })
+        ));
+        assert!(matches!(
+            Store::deser(&bytes[..10]),
+            Err(StoreSerError::NotEnoughBytes)
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_error_source_chain() {
+        use crate::types::{binary::BinarySerError, integer::IntegerSerError};
+        use std::error::Error;
+
+        let mut bytes = b"SOURISDB".to_vec();
+        bytes.push(0); //no compression, no huffman - and then no length for the bytes
+
+        let err = Store::deser(&bytes).unwrap_err();
+        let mut chain = vec![];
+        let mut current: Option<&(dyn Error + 'static)> = Some(&err);
+        while let Some(e) = current {
+            chain.push(e);
+            current = e.source();
+        }
+
+        assert_eq!(chain.len(), 3);
+        assert!(chain[0].is::<StoreSerError>());
+        assert!(chain[1].is::<BinarySerError>());
+        assert!(matches!(
+            chain[2].downcast_ref::<IntegerSerError>(),
+            Some(IntegerSerError::NotEnoughBytes)
+        ));
+    }
+
+    #[test]
+    fn test_flatten_all_json() {
+        let mut store = Store::default();
+        store.insert(
+            "json".to_string(),
+            Value::JSON(serde_json::json!({"a": [1, 2.5, "b", null]})),
+        );
+        store.insert(
+            "nested".to_string(),
+            Value::Array([Value::JSON(serde_json::json!(true))].into()),
+        );
+        store.insert("plain".to_string(), Value::from(1_u8));
+
+        store.flatten_all_json();
+
+        let mut expected_json = HashMap::new();
+        expected_json.insert(
+            "a".to_string(),
+            Value::Array(
+                [
+                    Value::from(1_u8),
+                    Value::DoubleFloat(2.5),
+                    Value::String("b".to_string()),
+                    Value::Null(()),
+                ]
+                .into(),
+            ),
+        );
+        assert_eq!(store.get("json"), Some(&Value::Map(expected_json)));
+        assert_eq!(
+            store.get("nested"),
+            Some(&Value::Array([Value::Boolean(true)].into()))
+        );
+        assert_eq!(store.get("plain"), Some(&Value::from(1_u8)));
+    }
+
+    #[test]
+    fn test_huffman_only_used_when_worth_it() {
+        let has_huffman = |bytes: &[u8]| bytes[9] & 0b1000_0000 != 0;
+
+        let mut tiny = Store::default();
+        tiny.insert("key".to_string(), Value::String("value".to_string()));
+
+        let bytes = tiny.ser().unwrap();
+        assert!(!has_huffman(&bytes));
+        assert_eq!(Store::deser(&bytes).unwrap(), tiny);
+
+        let mut repetitive = Store::default();
+        for i in 0..100 {
+            repetitive.insert(
+                alloc::format!("key number {i}"),
+                Value::String("the quick brown fox jumps over the lazy dog".repeat(4)),
+            );
+        }
+
+        let bytes = repetitive.ser().unwrap();
+        assert!(has_huffman(&bytes));
+        assert_eq!(Store::deser(&bytes).unwrap(), repetitive);
+
+        #[cfg(feature = "std")]
+        {
+            let mut written = vec![];
+            repetitive.ser_to_writer(&mut written).unwrap();
+            assert!(has_huffman(&written));
+        }
+    }
+
+    #[test]
+    fn test_layout_with_huffman() {
+        let mut store = Store::default();
+        for i in 0..100 {
+            store.insert(
+                alloc::format!("key number {i}"),
+                Value::String("the quick brown fox jumps over the lazy dog".repeat(4)),
+            );
         }
+
+        let layout = Store::layout(&store.ser().unwrap()).unwrap();
+        let huffman = layout.huffman.expect("store is big enough for a tree");
+        assert_eq!(huffman.start, 0);
+        assert_eq!(huffman.end, layout.values.start);
+        assert_eq!(layout.values.end, layout.payload.len());
+
+        let tree = Huffman::<char>::deser(&mut Cursor::new(&&layout.payload[huffman])).unwrap();
+        let values = Value::deser(
+            &mut Cursor::new(&&layout.payload[layout.values]),
+            Some(&tree),
+        )
+        .unwrap();
+        assert_eq!(values.to_map().unwrap(), store.0);
+    }
+
+    #[test]
+    fn test_deser_headerless() {
+        let mut store = Store::default();
+        for i in 0..100 {
+            store.insert(
+                alloc::format!("key number {i}"),
+                Value::String("the quick brown fox jumps over the lazy dog".repeat(4)),
+            );
+        }
+
+        let bytes = store.ser().unwrap();
+        let framing = bytes[9];
+        let compression = BinaryCompression::try_from(framing & 0b0111_1111).unwrap();
+        let body = &bytes[10..bytes.len() - 4];
+
+        let got_back = Store::deser_headerless(body, framing & 0b1000_0000 != 0, compression);
+        assert_eq!(got_back.unwrap(), store);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_ser_to_writer_in_frames() {
+        use super::FRAME_LEN;
+
+        let mut store = Store::default();
+        for i in 0..5_000 {
+            store.insert(
+                alloc::format!("key number {i}"),
+                Value::String(alloc::format!("the quick brown fox number {i}").repeat(10)),
+            );
+        }
+        //one value which doesn't compress, and has to be split over several frames by itself
+        let mut seed = 0x2545_f491_u32;
+        let noise = (0..FRAME_LEN * 2)
+            .map(|_| {
+                seed ^= seed << 13;
+                seed ^= seed >> 17;
+                seed ^= seed << 5;
+                seed.to_le_bytes()[0]
+            })
+            .collect();
+        store.insert("big".into(), Value::Binary(BinaryData(noise)));
+
+        let mut written = vec![];
+        store.ser_to_writer(&mut written).unwrap();
+        assert!(written.len() < store.ser_uncompressed().unwrap().len());
+        assert_eq!(Store::deser(&written).unwrap(), store);
+
+        let layout = Store::layout(&written).unwrap();
+        assert!(layout.is_framed);
+        assert!(layout.huffman.is_some());
+        assert!(layout.payload.len() > FRAME_LEN * 2);
+
+        let mut empty = vec![];
+        Store::default().ser_to_writer(&mut empty).unwrap();
+        assert_eq!(Store::deser(&empty).unwrap(), Store::default());
+    }
+
+    #[test]
+    fn test_dict_used_for_similar_binary() {
+        use super::DICT_MARKER;
+
+        let has_dict = |bytes: &[u8]| bytes[9] & DICT_MARKER != 0;
+        let fragment = |i: usize| {
+            Value::Binary(BinaryData::from(alloc::format!(
+                r#"{{"id":{i},"username":"user_{i}","email":"user_{i}@example.com","roles":["reader","writer"]}}"#
+            )))
+        };
+
+        let mut store = Store::default();
+        for i in 0..100 {
+            store.insert(alloc::format!("user {i}"), fragment(i));
+        }
+        store.insert(
+            "nested".into(),
+            Value::Array(vec![
+                fragment(100),
+                Value::Map([("inner".into(), fragment(101))].into()),
+            ]),
+        );
+        //extended types can't use the dictionary, but still have to round-trip next to one
+        store.insert(
+            "tagged".into(),
+            Value::Tagged {
+                tag: "fragment".into(),
+                value: Box::new(fragment(102)),
+            },
+        );
+
+        let bytes = store.ser().unwrap();
+        assert!(has_dict(&bytes));
+        assert_eq!(Store::deser(&bytes).unwrap(), store);
+
+        let layout = Store::layout(&bytes).unwrap();
+        let dict = layout.dict.expect("store has lots of similar binary");
+        assert_eq!(dict.start, 0);
+        assert_eq!(
+            layout
+                .huffman
+                .as_ref()
+                .map_or(layout.values.start, |h| h.start),
+            dict.end
+        );
+        //the values can't be read without the dictionary
+        let huffman = layout
+            .huffman
+            .map(|h| Huffman::<char>::deser(&mut Cursor::new(&&layout.payload[h])).unwrap());
+        assert!(matches!(
+            Value::deser(
+                &mut Cursor::new(&&layout.payload[layout.values]),
+                huffman.as_ref()
+            ),
+            Err(ValueSerError::BinarySerError(
+                BinarySerError::MissingDictionary
+            ))
+        ));
+
+        #[cfg(feature = "std")]
+        {
+            let mut written = vec![];
+            store.ser_to_writer(&mut written).unwrap();
+            assert!(has_dict(&written));
+            assert_eq!(Store::deser(&written).unwrap(), store);
+        }
+
+        let uncompressed = store.ser_uncompressed().unwrap();
+        assert!(has_dict(&uncompressed));
+        assert_eq!(Store::deser(&uncompressed).unwrap(), store);
+
+        //one blob has nothing to share a dictionary with
+        let mut single = Store::default();
+        single.insert("user".into(), fragment(0));
+        let bytes = single.ser().unwrap();
+        assert!(!has_dict(&bytes));
+        assert!(Store::layout(&bytes).unwrap().dict.is_none());
+        assert_eq!(Store::deser(&bytes).unwrap(), single);
+    }
+
+    #[test]
+    fn test_dict_marker_not_read_from_version_4() {
+        let mut store = Store::default();
+        store.insert("key".into(), Value::String("value".into()));
+
+        //version 4 only had the framed marker, so the dictionary bit is part of the compression
+        let mut bytes = store.ser().unwrap();
+        bytes.truncate(bytes.len() - 4);
+        bytes[8] = VERSION_MARKER | 4;
+        bytes[9] |= super::DICT_MARKER;
+        bytes.extend(Crc32::checksum(&bytes).to_le_bytes());
+
+        assert!(matches!(
+            Store::deser(&bytes),
+            Err(StoreSerError::Binary(
+                BinarySerError::NoCompressionTypeFound(0b0010_0000)
+            ))
+        ));
+
+        bytes.truncate(bytes.len() - 4);
+        bytes[9] &= !super::DICT_MARKER;
+        bytes.extend(Crc32::checksum(&bytes).to_le_bytes());
+        assert_eq!(Store::deser(&bytes).unwrap(), store);
+    }
+
+    #[test]
+    fn test_ser_uncompressed() {
+        let mut store = Store::default();
+        for i in 0..100 {
+            store.insert(
+                alloc::format!("key number {i}"),
+                Value::String("the quick brown fox jumps over the lazy dog".repeat(4)),
+            );
+        }
+
+        let raw = store.ser_uncompressed().unwrap();
+        assert_eq!(raw[9], 0b1000_0000 | u8::from(BinaryCompression::Nothing));
+        assert!(raw.len() > store.ser().unwrap().len());
+        assert_eq!(Store::deser(&raw).unwrap(), store);
+    }
+
+    proptest! {
+        #[test]
+        fn deser_arbitrary_bytes_doesnt_panic (magic_ty: u8, body: Vec<u8>) {
+            let mut bytes = b"SOURISDB".to_vec();
+            bytes.push(magic_ty);
+            bytes.extend(body);
+
+            let _ = Store::deser(&bytes);
+            let _ = Store::deser_skip_unknown(&bytes);
+        }
+
+        #[test]
+        fn deser_mutated_store_doesnt_panic (index: usize, byte: u8, truncate: bool) {
+            let mut store = Store::default();
+            store.insert("string".to_string(), Value::String("hello world".to_string()));
+            store.insert("integer".to_string(), Value::from(-1066_i32));
+            store.insert("binary".to_string(), Value::Binary(BinaryData(b"abababababab".to_vec())));
+            store.insert("array".to_string(), Value::Array([Value::Boolean(true), Value::Null(())].into()));
+
+            let mut bytes = store.ser().unwrap();
+            let index = index % bytes.len();
+            if truncate {
+                bytes.truncate(index);
+            } else {
+                bytes[index] = byte;
+            }
+
+            let _ = Store::deser(&bytes);
+        }
+    }
+
+    #[test]
+    fn test_rename_collisions() {
+        let mut store: Store = [
+            ("a".to_string(), Value::from(1_u8)),
+            ("b".to_string(), Value::from(2_u8)),
+        ]
+        .into_iter()
+        .collect();
+
+        assert_eq!(
+            store.rename_key("missing", "c", true),
+            Err(RenameError::SourceMissing)
+        );
+        assert_eq!(
+            store.rename_key("a", "b", false),
+            Err(RenameError::DestinationExists)
+        );
+        assert_eq!(store.get("a"), Some(&Value::from(1_u8)));
+        assert_eq!(store.get("b"), Some(&Value::from(2_u8)));
+
+        assert_eq!(store.rename_key("a", "a", false), Ok(()));
+        assert_eq!(store.get("a"), Some(&Value::from(1_u8)));
+
+        assert_eq!(store.rename_key("a", "b", true), Ok(()));
+        assert_eq!(store.len(), 1);
+        assert_eq!(store.get("b"), Some(&Value::from(1_u8)));
+    }
+
+    #[test]
+    fn test_rename_prefix_collisions() {
+        let mut store: Store = [
+            ("old/x".to_string(), Value::from(1_u8)),
+            ("old/y".to_string(), Value::from(2_u8)),
+            ("new/x".to_string(), Value::from(3_u8)),
+            ("other".to_string(), Value::from(4_u8)),
+        ]
+        .into_iter()
+        .collect();
+
+        assert_eq!(store.rename_prefix("old/", "new/"), 2);
+        assert_eq!(store.len(), 3);
+        assert_eq!(store.get("new/x"), Some(&Value::from(1_u8)));
+        assert_eq!(store.get("new/y"), Some(&Value::from(2_u8)));
+        assert_eq!(store.get("other"), Some(&Value::from(4_u8)));
+
+        //renamed keys which also match the prefix are only renamed once
+        assert_eq!(store.rename_prefix("new/", "new/new/"), 2);
+        assert_eq!(store.get("new/new/x"), Some(&Value::from(1_u8)));
+        assert_eq!(store.get("new/new/y"), Some(&Value::from(2_u8)));
+        assert_eq!(store.rename_prefix("missing/", "new/"), 0);
+    }
+
+    #[test]
+    fn test_bulk_methods_call_observers() {
+        use super::ChangeEvent;
+        use alloc::sync::Arc;
+        use core::sync::atomic::{AtomicUsize, Ordering};
+
+        //inserted, updated, removed
+        let counts = Arc::new([
+            AtomicUsize::new(0),
+            AtomicUsize::new(0),
+            AtomicUsize::new(0),
+        ]);
+        let mut store = Store::default();
+        let counts_in_callback = counts.clone();
+        store.on_change(alloc::boxed::Box::new(move |event| {
+            let index = match event {
+                ChangeEvent::Inserted { .. } => 0,
+                ChangeEvent::Updated { .. } => 1,
+                ChangeEvent::Removed { .. } => 2,
+            };
+            counts_in_callback[index].fetch_add(1, Ordering::Relaxed);
+        }));
+        let counts = || counts.each_ref().map(|c| c.load(Ordering::Relaxed));
+
+        store.extend([
+            ("old/x".to_string(), Value::from(1_u8)),
+            ("old/y".to_string(), Value::from(2_u8)),
+            ("new/x".to_string(), Value::from(3_u8)),
+            ("other".to_string(), Value::from(4_u8)),
+        ]);
+        assert_eq!(counts(), [4, 0, 0]);
+
+        //two removals, then one insert and one update (over `new/x`)
+        assert_eq!(store.rename_prefix("old/", "new/"), 2);
+        assert_eq!(counts(), [5, 1, 2]);
+
+        assert_eq!(store.remove_prefix("new/"), 2);
+        assert_eq!(counts(), [5, 1, 4]);
+
+        //only the value that actually holds JSON changes
+        store.insert(
+            "json".into(),
+            Value::JSON(serde_json::json!({"a": [1, true]})),
+        );
+        store.insert("plain".into(), Value::from(5_u8));
+        assert_eq!(counts(), [7, 1, 4]);
+        store.flatten_all_json();
+        assert_eq!(counts(), [7, 2, 4]);
+        assert_eq!(store["json"].as_ty(), crate::values::ValueTy::Map);
+
+        store.clear_and_shrink();
+        assert_eq!(counts(), [7, 2, 7]);
+        assert!(store.is_empty());
     }
 }
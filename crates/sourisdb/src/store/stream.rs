@@ -0,0 +1,287 @@
+//! A format for sending a [`Store`] one entry at a time, so that huge stores can be processed as they arrive rather than after they have all been buffered.
+//!
+//! [`Store::ser`] compresses the whole store at once (and puts a checksum at the end), so nothing can be read until every byte has arrived. In this format, each entry instead gets its own length-prefixed frame holding the key and the value, and a zero length marks the end. Nothing is compressed or huffman-encoded, so streamed stores are bigger than ones from [`Store::ser`].
+//!
+//! As the number of entries isn't needed up-front, the frames don't have to come from one [`Store`] - see [`entry_chunk`] and [`end_chunk`].
+//!
+//! ```rust
+//! use sourisdb::{store::{stream::EntryStreamDecoder, Store}, values::Value};
+//!
+//! let mut store = Store::default();
+//! store.insert("a".into(), Value::from(1_u8));
+//! store.insert("b".into(), Value::String("two".into()));
+//!
+//! let mut decoder = EntryStreamDecoder::default();
+//! let mut entries = vec![];
+//! //feed the bytes in one at a time, as if they were arriving slowly over the network
+//! for byte in store.clone().into_entry_stream().flatten() {
+//!     decoder.push(&[byte]);
+//!     while let Some(entry) = decoder.next_entry().unwrap() {
+//!         entries.push(entry);
+//!     }
+//! }
+//!
+//! assert!(decoder.is_finished());
+//! assert_eq!(entries.into_iter().collect::<Store>(), store);
+//! ```
+
+use crate::{
+    store::{Store, StoreSerError},
+    types::integer::{Integer, IntegerSerError, SignedState},
+    utilities::cursor::Cursor,
+    values::{Value, ValueSerError, ValueTy},
+};
+use alloc::{string::String, vec::Vec};
+use core::iter;
+
+impl Store {
+    ///Serialises the store into chunks which can be sent one at a time and read back using an [`EntryStreamDecoder`].
+    ///
+    /// There is one [`entry_chunk`] for each entry, and then an [`end_chunk`]. Entries are sorted by key, so that equal stores always give the same chunks.
+    pub fn into_entry_stream(self) -> impl Iterator<Item = Vec<u8>> {
+        let mut entries: Vec<(String, Value)> = self.0.into_iter().collect();
+        entries.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+
+        entries
+            .into_iter()
+            .map(|(k, v)| entry_chunk(&k, &v))
+            .chain(iter::once(end_chunk()))
+    }
+}
+
+///Serialises one entry into a chunk for an entry stream, so that a stream can be built up without having the whole [`Store`] at once. It has to be followed by an [`end_chunk`] once every entry has been sent.
+#[must_use]
+pub fn entry_chunk(key: &str, value: &Value) -> Vec<u8> {
+    let mut frame = Value::String(key.into()).ser(None);
+    frame.extend(value.ser(None));
+
+    let mut chunk = Integer::usize(frame.len()).ser().1;
+    chunk.extend(frame);
+    chunk
+}
+
+///The chunk which marks the end of an entry stream. Every frame holds at least a key, so a frame length of zero can't be mistaken for an entry.
+#[must_use]
+pub fn end_chunk() -> Vec<u8> {
+    Integer::usize(0).ser().1
+}
+
+///The default for the longest frame an [`EntryStreamDecoder`] will wait for, in bytes. This matches the default for the largest body `sourisd` accepts, so any entry it can be sent can be streamed back out.
+pub const DEFAULT_MAX_FRAME_LEN: usize = 256 * 1024 * 1024;
+
+///Reads entries back from [`Store::into_entry_stream`] as the bytes arrive.
+///
+/// Bytes can be pushed in however they are split up - entries only come out of [`EntryStreamDecoder::next_entry`] once all of their bytes are in.
+#[derive(Debug)]
+pub struct EntryStreamDecoder {
+    ///Bytes which have arrived, some of which may have been read already.
+    buffer: Vec<u8>,
+    ///How many bytes at the start of `buffer` have been read. They are only removed once they are at least half of `buffer`, so that removing them doesn't take longer than reading them did.
+    read: usize,
+    ///The longest frame which is allowed, in bytes.
+    max_frame_len: usize,
+    ///Whether the end of the stream has been read.
+    finished: bool,
+}
+
+impl Default for EntryStreamDecoder {
+    fn default() -> Self {
+        Self::with_max_frame_len(DEFAULT_MAX_FRAME_LEN)
+    }
+}
+
+impl EntryStreamDecoder {
+    ///Creates a decoder which returns an error for any frame longer than `max_frame_len` bytes, rather than buffering everything the other side claims to be about to send. [`EntryStreamDecoder::default`] uses [`DEFAULT_MAX_FRAME_LEN`].
+    #[must_use]
+    pub fn with_max_frame_len(max_frame_len: usize) -> Self {
+        Self {
+            buffer: Vec::new(),
+            read: 0,
+            max_frame_len,
+            finished: false,
+        }
+    }
+
+    ///Adds bytes which have just arrived.
+    pub fn push(&mut self, bytes: &[u8]) {
+        if self.read > 0 && self.read >= self.buffer.len() / 2 {
+            self.buffer.drain(..self.read);
+            self.read = 0;
+        }
+        self.buffer.extend_from_slice(bytes);
+    }
+
+    ///Whether every entry has been read. If the bytes stop before this is `true`, then the stream was cut off.
+    #[must_use]
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    ///Reads the next entry, if all of its bytes have arrived. Returns `Ok(None)` if more bytes are needed, or if every entry has already been read.
+    ///
+    /// # Errors
+    /// - [`StoreSerError::Integer`] if we couldn't read the length of an entry.
+    /// - [`StoreSerError::FrameTooLong`] if an entry is longer than the decoder allows.
+    /// - [`StoreSerError::Value`] if we couldn't read the key or the value of an entry, or the key wasn't a [`Value::String`].
+    pub fn next_entry(&mut self) -> Result<Option<(String, Value)>, StoreSerError> {
+        if self.finished {
+            return Ok(None);
+        }
+
+        let unread = &self.buffer[self.read..];
+        let mut cursor = Cursor::new(&unread);
+        let Some(frame_len) = read_len(&mut cursor)? else {
+            return Ok(None);
+        };
+        if frame_len == 0 {
+            self.read += cursor.pos();
+            self.finished = true;
+            return Ok(None);
+        }
+        if frame_len > self.max_frame_len {
+            return Err(StoreSerError::FrameTooLong {
+                len: frame_len,
+                max: self.max_frame_len,
+            });
+        }
+        let Some(frame) = cursor.read(frame_len) else {
+            return Ok(None);
+        };
+
+        //anything after the value in the frame is ignored, so that more can be added to entries later
+        let mut frame = Cursor::new(&frame);
+        let key = Value::deser(&mut frame, None)?;
+        let Value::String(key) = key else {
+            return Err(ValueSerError::UnexpectedValueType {
+                found: key.as_ty(),
+                expected: ValueTy::String,
+            }
+            .into());
+        };
+        let value = Value::deser(&mut frame, None)?;
+
+        self.read += cursor.pos();
+
+        Ok(Some((key, value)))
+    }
+}
+
+///Reads a length, returning `Ok(None)` if not all of its bytes have arrived yet.
+fn read_len(cursor: &mut Cursor<u8>) -> Result<Option<usize>, StoreSerError> {
+    match Integer::deser(SignedState::Unsigned, cursor) {
+        Ok(len) => Ok(Some(len.try_into()?)),
+        Err(IntegerSerError::NotEnoughBytes) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::{string::ToString, vec};
+
+    fn example_store() -> Store {
+        (1..=50_u32)
+            .map(|i| {
+                (
+                    i.to_string(),
+                    Value::Array(vec![Value::from(i), Value::String("x".repeat(i as usize))]),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_entry_stream_chunk_sizes() {
+        let store = example_store();
+        let bytes: Vec<u8> = store.clone().into_entry_stream().flatten().collect();
+
+        for chunk_size in [1, 2, 7, 100, bytes.len()] {
+            let mut decoder = EntryStreamDecoder::default();
+            let mut got_back = Store::default();
+            for chunk in bytes.chunks(chunk_size) {
+                decoder.push(chunk);
+                while let Some((k, v)) = decoder.next_entry().unwrap() {
+                    got_back.insert(k, v);
+                }
+            }
+
+            assert!(decoder.is_finished());
+            assert_eq!(got_back, store);
+        }
+    }
+
+    #[test]
+    fn test_entry_stream_empty_and_cut_off() {
+        let mut decoder = EntryStreamDecoder::default();
+        assert!(decoder.next_entry().unwrap().is_none());
+        for chunk in Store::default().into_entry_stream() {
+            decoder.push(&chunk);
+        }
+        assert!(decoder.next_entry().unwrap().is_none());
+        assert!(decoder.is_finished());
+
+        let bytes: Vec<u8> = example_store().into_entry_stream().flatten().collect();
+        let mut decoder = EntryStreamDecoder::default();
+        decoder.push(&bytes[..bytes.len() - 1]);
+        while decoder.next_entry().unwrap().is_some() {}
+        assert!(!decoder.is_finished());
+    }
+
+    #[test]
+    fn test_entry_stream_key_must_be_string() {
+        let mut frame = Value::Boolean(true).ser(None);
+        frame.extend(Value::Null(()).ser(None));
+
+        let mut decoder = EntryStreamDecoder::default();
+        decoder.push(&Integer::usize(frame.len()).ser().1);
+        decoder.push(&frame);
+
+        assert!(matches!(
+            decoder.next_entry(),
+            Err(StoreSerError::Value(ValueSerError::UnexpectedValueType {
+                found: ValueTy::Boolean,
+                expected: ValueTy::String
+            }))
+        ));
+    }
+
+    #[test]
+    fn test_entry_stream_frame_too_long() {
+        let chunk = entry_chunk("key", &Value::String("x".repeat(100)));
+
+        let mut decoder = EntryStreamDecoder::with_max_frame_len(50);
+        //only the length has arrived, but that's enough to know not to wait for the rest
+        decoder.push(&chunk[..1]);
+        assert!(matches!(
+            decoder.next_entry(),
+            Err(StoreSerError::FrameTooLong { len, max: 50 }) if len == chunk.len() - 1
+        ));
+
+        let mut decoder = EntryStreamDecoder::with_max_frame_len(chunk.len());
+        decoder.push(&chunk);
+        decoder.push(&end_chunk());
+        assert!(decoder.next_entry().unwrap().is_some());
+        assert!(decoder.next_entry().unwrap().is_none());
+        assert!(decoder.is_finished());
+    }
+
+    #[test]
+    fn test_entry_stream_read_bytes_are_dropped() {
+        let mut decoder = EntryStreamDecoder::default();
+        let mut longest = 0;
+        for chunk in example_store().into_entry_stream() {
+            decoder.push(&chunk);
+            while decoder.next_entry().unwrap().is_some() {}
+            longest = longest.max(decoder.buffer.len());
+        }
+
+        assert!(decoder.is_finished());
+        let biggest_chunk = example_store()
+            .into_entry_stream()
+            .map(|c| c.len())
+            .max()
+            .unwrap();
+        assert!(longest <= biggest_chunk * 2);
+    }
+}
@@ -2,23 +2,30 @@ use crate::{
     display_bytes_as_hex_array,
     types::{
         binary::{
-            huffman::{huffman, un_huffman},
+            dictionary::{lz_with_dict, un_lz_with_dict, Dict},
+            huffman::{huffman, un_huffman_with_max_run_len, MAX_RUN_LEN},
             lz::{lz, un_lz},
             rle::{rle, un_rle},
         },
         integer::{Integer, IntegerSerError, SignedState},
     },
-    utilities::{cursor::Cursor, huffman::HuffmanSerError},
+    utilities::{
+        cursor::{Cursor, CursorError},
+        fnv::Fnv1a,
+        huffman::HuffmanSerError,
+    },
     values::ValueTy,
 };
 use alloc::vec::Vec;
 use core::{
     fmt::{Debug, Display, Formatter},
+    hash::Hasher,
     ops::{Deref, DerefMut},
 };
 use lz4_flex::block::DecompressError;
 use serde_json::{Map as SJMap, Number, Value as SJValue};
 
+pub mod dictionary;
 pub mod huffman;
 pub mod lz;
 pub mod rle;
@@ -56,6 +63,9 @@ impl TryFrom<u8> for BinaryCompression {
     }
 }
 
+///The compression bits in the type byte of a [`Value::Binary`](crate::values::Value::Binary) which was compressed using the [`Dict`] stored at the start of its [`Store`](crate::store::Store). This isn't one of the [`BinaryCompression`]s, as it can't be read without the store's dictionary.
+pub(crate) const STORE_DICT_COMPRESSION: u8 = 4;
+
 #[derive(Debug)]
 pub enum BinarySerError {
     NoCompressionTypeFound(u8),
@@ -63,6 +73,22 @@ pub enum BinarySerError {
     NotEnoughBytes,
     LzFlex(DecompressError),
     Huffman(HuffmanSerError),
+    ///The uncompressed length given for LZ-compressed data was bigger than the compressed bytes could possibly produce.
+    ImpossibleLength {
+        ///The uncompressed length we read
+        claimed: usize,
+        ///The number of compressed bytes
+        compressed: usize,
+    },
+    ///The data was compressed using a different [`Dict`] to the one given for decompressing it.
+    DictionaryMismatch {
+        ///The [`Dict::id`] of the dictionary we were given
+        expected: u64,
+        ///The [`Dict::id`] stored with the data
+        found: u64,
+    },
+    ///The data was compressed using the dictionary of a store, but there wasn't one.
+    MissingDictionary,
 }
 
 impl Display for BinarySerError {
@@ -75,6 +101,18 @@ impl Display for BinarySerError {
             Self::NotEnoughBytes => write!(f, "Not enough bytes to deserialize."),
             Self::LzFlex(e) => write!(f, "Error decompressing LZ: {e}"),
             Self::Huffman(e) => write!(f, "Error decompressing huffman: {e}"),
+            Self::ImpossibleLength { claimed, compressed } => write!(
+                f,
+                "Claimed uncompressed length of {claimed} is impossible with {compressed} compressed bytes"
+            ),
+            Self::DictionaryMismatch { expected, found } => write!(
+                f,
+                "Data was compressed with dictionary {found:#018x}, but dictionary {expected:#018x} was given"
+            ),
+            Self::MissingDictionary => write!(
+                f,
+                "Data was compressed with a store's dictionary, but there was no dictionary"
+            ),
         }
     }
 }
@@ -83,7 +121,11 @@ impl Display for BinarySerError {
 impl std::error::Error for BinarySerError {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self {
-            Self::NoCompressionTypeFound(_) | Self::NotEnoughBytes => None,
+            Self::NoCompressionTypeFound(_)
+            | Self::NotEnoughBytes
+            | Self::ImpossibleLength { .. }
+            | Self::DictionaryMismatch { .. }
+            | Self::MissingDictionary => None,
             Self::Integer(i) => Some(i),
             Self::LzFlex(e) => Some(e),
             Self::Huffman(e) => Some(e),
@@ -96,6 +138,11 @@ impl From<IntegerSerError> for BinarySerError {
         Self::Integer(value)
     }
 }
+impl From<CursorError> for BinarySerError {
+    fn from(_: CursorError) -> Self {
+        Self::NotEnoughBytes
+    }
+}
 impl From<DecompressError> for BinarySerError {
     fn from(value: DecompressError) -> Self {
         Self::LzFlex(value)
@@ -107,6 +154,29 @@ impl From<HuffmanSerError> for BinarySerError {
     }
 }
 
+///Details about how well some [`BinaryData`] compressed, from [`BinaryData::ser_with_report`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct CompressionReport {
+    ///The number of bytes before compression.
+    pub original_size: usize,
+    ///The number of bytes after compression, including any length or compression metadata.
+    pub compressed_size: usize,
+}
+
+impl CompressionReport {
+    ///The compressed size divided by the original size - smaller is better, and anything above `1.0` means that the compressed form is bigger than the original.
+    ///
+    /// Empty data has a ratio of `1.0`.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn ratio(&self) -> f64 {
+        if self.original_size == 0 {
+            return 1.0;
+        }
+        self.compressed_size as f64 / self.original_size as f64
+    }
+}
+
 #[derive(Clone, PartialEq, Eq, Hash)]
 pub struct BinaryData(pub Vec<u8>);
 
@@ -150,6 +220,24 @@ impl Display for BinaryData {
     }
 }
 impl BinaryData {
+    ///Hashes the bytes using [`Fnv1a`], which always gives the same hash for the same bytes (between runs and between machines) and doesn't depend on how the bytes are serialised.
+    ///
+    /// This is useful for deduplicating lots of large blobs - hash each blob once, and then only compare blobs with matching hashes. It isn't cached (as the bytes can be changed at any time through the public field), so comparing two blobs directly with `==` is faster than hashing them both.
+    ///
+    /// ```rust
+    /// use sourisdb::types::binary::BinaryData;
+    ///
+    /// let a = BinaryData(vec![1, 2, 3]);
+    /// assert_eq!(a.content_hash(), BinaryData(vec![1, 2, 3]).content_hash());
+    /// assert_ne!(a.content_hash(), BinaryData(vec![3, 2, 1]).content_hash());
+    /// ```
+    #[must_use]
+    pub fn content_hash(&self) -> u64 {
+        let mut hasher = Fnv1a::default();
+        hasher.write(&self.0);
+        hasher.finish()
+    }
+
     #[must_use]
     pub fn to_json(self, add_souris_types: bool) -> SJValue {
         let mut obj = SJMap::new();
@@ -173,30 +261,136 @@ impl BinaryData {
         SJValue::Object(obj)
     }
 
+    ///Serialises the bytes using whichever [`BinaryCompression`] gives the smallest output.
     #[must_use]
-    #[allow(clippy::missing_panics_doc)]
     pub fn ser(&self) -> (BinaryCompression, Vec<u8>) {
-        let vanilla = {
-            let mut backing = Integer::usize(self.0.len()).ser().1;
-            backing.extend(&self.0);
-            backing
-        };
-        let rle = rle(&self.0);
-        let lz = lz(&self.0);
-        let huffman = huffman(&self.0);
-
-        [
-            (BinaryCompression::Nothing, vanilla),
-            (BinaryCompression::RunLengthEncoding, rle),
-            (BinaryCompression::LempelZiv, lz),
-            (BinaryCompression::Huffman, huffman),
+        let (compression, bytes, _) = self.ser_with_report();
+        (compression, bytes)
+    }
+
+    ///Serialises the bytes using exactly the given [`BinaryCompression`], rather than picking the smallest like [`BinaryData::ser`] does. The output can be read back using [`BinaryData::deser`] with the same compression.
+    ///
+    /// [`BinaryData::ser`] runs every compression method over the bytes, so forcing [`BinaryCompression::Nothing`] saves a lot of CPU time for data that is known not to compress well (like images or already-compressed files). Forcing a specific method can also be useful for reproducible output.
+    ///
+    /// ```rust
+    /// use sourisdb::{
+    ///     types::binary::{BinaryCompression, BinaryData},
+    ///     utilities::cursor::Cursor,
+    /// };
+    ///
+    /// let data = BinaryData(vec![0xAB; 1_000]);
+    /// let raw = data.ser_with(BinaryCompression::Nothing);
+    /// assert!(raw.len() > 1_000);
+    ///
+    /// let got_back = BinaryData::deser(BinaryCompression::Nothing, &mut Cursor::new(&raw)).unwrap();
+    /// assert_eq!(got_back, data);
+    /// ```
+    #[must_use]
+    pub fn ser_with(&self, compression: BinaryCompression) -> Vec<u8> {
+        match compression {
+            BinaryCompression::Nothing => {
+                let mut backing = Integer::usize(self.0.len()).ser().1;
+                backing.extend(&self.0);
+                backing
+            }
+            BinaryCompression::RunLengthEncoding => rle(&self.0),
+            BinaryCompression::LempelZiv => lz(&self.0),
+            BinaryCompression::Huffman => huffman(&self.0),
+        }
+    }
+
+    ///Serialises the bytes using LZ4 with a shared [`Dict`], which can be read back using [`BinaryData::deser_with_dict`] with the same dictionary.
+    ///
+    /// This is much better than [`BinaryData::ser`] for lots of small similar blobs, as long as the dictionary is only stored once (eg. using [`Dict::ser`]) rather than alongside every blob. See [`Dict::train`] for making a dictionary.
+    ///
+    /// This isn't one of the [`BinaryCompression`]s, so it is never picked by [`BinaryData::ser`] - the bytes (and the dictionary) have to be stored separately. [`Store::ser`](crate::store::Store::ser) does this automatically when it is worth it, training one dictionary from the store's binary values and storing it once at the start of the store.
+    ///
+    /// ```rust
+    /// use sourisdb::{
+    ///     types::binary::{dictionary::Dict, BinaryData},
+    ///     utilities::cursor::Cursor,
+    /// };
+    ///
+    /// let dict = Dict::new(br#"{"name":"","email":"@example.com"}"#.to_vec());
+    /// let data = BinaryData::from(r#"{"name":"Jack","email":"jack@example.com"}"#);
+    ///
+    /// let bytes = data.ser_with_dict(&dict);
+    /// assert!(bytes.len() < data.ser().1.len());
+    ///
+    /// let got_back = BinaryData::deser_with_dict(&mut Cursor::new(&bytes), &dict).unwrap();
+    /// assert_eq!(got_back, data);
+    /// ```
+    #[must_use]
+    pub fn ser_with_dict(&self, dict: &Dict) -> Vec<u8> {
+        lz_with_dict(&self.0, dict)
+    }
+
+    ///Serialises the bytes in the same way as [`BinaryData::ser`], but also returns a [`CompressionReport`] detailing how well the bytes compressed.
+    ///
+    /// ```rust
+    /// use sourisdb::types::binary::BinaryData;
+    ///
+    /// let data = BinaryData(vec![0xAB; 1_000]);
+    /// let (_, bytes, report) = data.ser_with_report();
+    ///
+    /// assert_eq!(report.original_size, 1_000);
+    /// assert_eq!(report.compressed_size, bytes.len());
+    /// assert!(report.ratio() < 0.1);
+    /// ```
+    #[must_use]
+    #[allow(clippy::missing_panics_doc)]
+    pub fn ser_with_report(&self) -> (BinaryCompression, Vec<u8>, CompressionReport) {
+        let (compression, bytes) = [
+            BinaryCompression::Nothing,
+            BinaryCompression::RunLengthEncoding,
+            BinaryCompression::LempelZiv,
+            BinaryCompression::Huffman,
         ]
         .into_iter()
+        .map(|compression| (compression, self.ser_with(compression)))
         .min_by_key(|(_, v)| v.len())
-        .unwrap()
+        .unwrap();
+
+        let report = CompressionReport {
+            original_size: self.0.len(),
+            compressed_size: bytes.len(),
+        };
+
+        (compression, bytes, report)
+    }
+
+    ///Reads everything left in a reader into a new [`BinaryData`], which avoids having to read files in chunks by hand.
+    ///
+    /// ```rust
+    /// use sourisdb::types::binary::BinaryData;
+    ///
+    /// let mut reader: &[u8] = &[0xDE, 0xAD, 0xBE, 0xEF];
+    /// let data = BinaryData::from_reader(&mut reader).unwrap();
+    ///
+    /// let mut written = vec![];
+    /// data.write_to(&mut written).unwrap();
+    /// assert_eq!(written, [0xDE, 0xAD, 0xBE, 0xEF]);
+    /// ```
+    ///
+    /// # Errors
+    /// - [`std::io::Error`] if the reader fails.
+    #[cfg(feature = "std")]
+    pub fn from_reader<R: std::io::Read>(r: &mut R) -> std::io::Result<Self> {
+        let mut bytes = Vec::new();
+        r.read_to_end(&mut bytes)?;
+        Ok(Self(bytes))
     }
 
-    ///Uncompresses bytes using the specified method.
+    ///Writes all of the bytes into a writer, without any compression - see [`BinaryData::ser`] for that.
+    ///
+    /// # Errors
+    /// - [`std::io::Error`] if the writer fails.
+    #[cfg(feature = "std")]
+    pub fn write_to<W: std::io::Write>(&self, w: &mut W) -> std::io::Result<()> {
+        w.write_all(&self.0)
+    }
+
+    ///Uncompresses bytes using the specified method. Bytes from [`BinaryData::ser_with_dict`] have to be read using [`BinaryData::deser_with_dict`] instead.
     ///
     /// # Errors
     /// - [`IntegerSerError`] if we cannot deserialise the length
@@ -205,22 +399,37 @@ impl BinaryData {
     pub fn deser(
         compression: BinaryCompression,
         cursor: &mut Cursor<u8>,
+    ) -> Result<Self, BinarySerError> {
+        Self::deser_with_max_run_len(compression, cursor, MAX_RUN_LEN)
+    }
+
+    ///Uncompresses bytes like [`BinaryData::deser`], but lets huffman-compressed bytes hold runs of one repeated byte up to `max_run_len` long, for data written before [`MAX_RUN_LEN`] existed.
+    pub(crate) fn deser_with_max_run_len(
+        compression: BinaryCompression,
+        cursor: &mut Cursor<u8>,
+        max_run_len: usize,
     ) -> Result<Self, BinarySerError> {
         Ok(match compression {
             BinaryCompression::Nothing => {
                 let length = Integer::deser(SignedState::Unsigned, cursor)?.try_into()?;
-                Self(
-                    cursor
-                        .read(length)
-                        .ok_or(BinarySerError::NotEnoughBytes)?
-                        .to_vec(),
-                )
+                Self(cursor.read_checked(length)?.to_vec())
             }
             BinaryCompression::RunLengthEncoding => Self(un_rle(cursor)?),
             BinaryCompression::LempelZiv => Self(un_lz(cursor)?),
-            BinaryCompression::Huffman => Self(un_huffman(cursor)?),
+            BinaryCompression::Huffman => Self(un_huffman_with_max_run_len(cursor, max_run_len)?),
         })
     }
+
+    ///Uncompresses bytes from [`BinaryData::ser_with_dict`], using the same [`Dict`] that they were compressed with.
+    ///
+    /// # Errors
+    /// - [`IntegerSerError`] if we cannot deserialise one of the lengths
+    /// - [`BinarySerError::DictionaryMismatch`] if the data was compressed using a different [`Dict`]
+    /// - [`BinarySerError::NotEnoughBytes`] if there are not enough bytes
+    /// - [`BinarySerError`] if there are any issues with decompressing
+    pub fn deser_with_dict(cursor: &mut Cursor<u8>, dict: &Dict) -> Result<Self, BinarySerError> {
+        Ok(Self(un_lz_with_dict(cursor, dict)?))
+    }
 }
 
 #[cfg(test)]
@@ -247,3 +456,41 @@ where
     let decoded = d(&mut cursor).unwrap();
     assert_eq!(v, decoded);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ser_with_each_compression() {
+        for case in CASES {
+            let data = BinaryData(case.to_vec());
+            for compression in [
+                BinaryCompression::Nothing,
+                BinaryCompression::RunLengthEncoding,
+                BinaryCompression::LempelZiv,
+                BinaryCompression::Huffman,
+            ] {
+                let bytes = data.ser_with(compression);
+                let got_back = BinaryData::deser(compression, &mut Cursor::new(&bytes)).unwrap();
+                assert_eq!(got_back, data, "{compression:?} failed to round-trip");
+            }
+        }
+    }
+
+    #[test]
+    fn test_compression_report() {
+        for case in CASES {
+            let data = BinaryData(case.to_vec());
+            let (compression, bytes, report) = data.ser_with_report();
+
+            assert_eq!(report.original_size, case.len());
+            assert_eq!(report.compressed_size, bytes.len());
+
+            let mut cursor = Cursor::new(&bytes);
+            assert_eq!(BinaryData::deser(compression, &mut cursor).unwrap(), data);
+        }
+
+        assert!((BinaryData(Vec::new()).ser_with_report().2.ratio() - 1.0).abs() < f64::EPSILON);
+    }
+}
@@ -0,0 +1,328 @@
+//! A module containing shared dictionaries for LZ4, which make compressing lots of small similar blobs (like JSON fragments) far more effective.
+//!
+//! Small blobs don't compress well by themselves, as there is very little for LZ4 to refer back to. A [`Dict`] holds bytes that are common across many blobs, and then every blob can refer back into the dictionary instead. The dictionary is only stored once, and each blob only stores the [`Dict::id`] of the dictionary it was compressed with.
+//!
+//! [`Store::ser`](crate::store::Store::ser) trains and stores a dictionary by itself when a store has lots of similar binary values - this module is for using one elsewhere.
+//!
+//! ```rust
+//! use sourisdb::{
+//!     types::binary::{dictionary::Dict, BinaryData},
+//!     utilities::cursor::Cursor,
+//! };
+//!
+//! let blobs: Vec<BinaryData> = (0..100)
+//!     .map(|i| BinaryData::from(format!(r#"{{"id":{i},"name":"user_{i}","active":true}}"#)))
+//!     .collect();
+//! let dict = Dict::train(blobs.iter().map(|b| b.as_slice()), 1_024);
+//!
+//! let bytes = blobs[42].ser_with_dict(&dict);
+//! let got_back = BinaryData::deser_with_dict(&mut Cursor::new(&bytes), &dict).unwrap();
+//! assert_eq!(got_back, blobs[42]);
+//! ```
+
+use crate::{
+    types::{
+        binary::{lz::MAX_EXPANSION_RATIO, BinarySerError},
+        integer::{Integer, SignedState},
+    },
+    utilities::{cursor::Cursor, fnv::Fnv1a},
+};
+use alloc::{vec, vec::Vec};
+use core::{cmp::Reverse, hash::Hasher};
+use hashbrown::{HashMap, HashSet};
+use lz4_flex::block::{compress_with_dict, decompress_with_dict};
+
+///LZ4 can only refer back 64KiB, so any more of a dictionary than this would never be used.
+pub const MAX_DICT_LEN: usize = 1 << 16;
+
+///The number of bytes in each chunk that [`Dict::train`] looks for - this is a bit longer than the shortest LZ4 match, so that each chunk is actually worth referring back to.
+const WINDOW_LEN: usize = 8;
+
+///A shared dictionary for compressing lots of similar [`BinaryData`](super::BinaryData)s, using [`BinaryData::ser_with_dict`](super::BinaryData::ser_with_dict).
+///
+/// The same dictionary must be used for decompressing, so it has to be stored alongside the blobs - [`Dict::ser`] can be used for that.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Dict {
+    bytes: Vec<u8>,
+}
+
+impl Dict {
+    ///Creates a dictionary from some bytes. If there are more than [`MAX_DICT_LEN`] bytes, only the last [`MAX_DICT_LEN`] are kept, as LZ4 can't refer any further back than that.
+    #[must_use]
+    pub fn new(mut bytes: Vec<u8>) -> Self {
+        if bytes.len() > MAX_DICT_LEN {
+            bytes.drain(..bytes.len() - MAX_DICT_LEN);
+        }
+        Self { bytes }
+    }
+
+    ///Trains a dictionary of at most `max_len` bytes (capped at [`MAX_DICT_LEN`]) from some samples.
+    ///
+    /// This looks for chunks of bytes that appear in the most samples, and then joins them together (overlapping them where possible). Chunks which only appear in one sample are never added, as they wouldn't help with compressing any other blobs.
+    #[must_use]
+    pub fn train<'a>(samples: impl IntoIterator<Item = &'a [u8]>, max_len: usize) -> Self {
+        let max_len = max_len.min(MAX_DICT_LEN);
+
+        let mut sample_counts: HashMap<&[u8], usize> = HashMap::new();
+        for sample in samples {
+            let unique: HashSet<&[u8]> = sample.windows(WINDOW_LEN).collect();
+            for window in unique {
+                *sample_counts.entry(window).or_default() += 1;
+            }
+        }
+
+        let mut candidates: Vec<(&[u8], usize)> = sample_counts
+            .into_iter()
+            .filter(|(_, count)| *count > 1)
+            .collect();
+        //sort by the chunk as well so that the same samples always give the same dictionary
+        candidates.sort_unstable_by_key(|(window, count)| (Reverse(*count), *window));
+
+        let mut bytes: Vec<u8> = vec![];
+        //every chunk in the dictionary so far, including ones which span two added chunks
+        let mut already_in: HashSet<[u8; WINDOW_LEN]> = HashSet::new();
+        for (window, _) in candidates {
+            if bytes.len() >= max_len {
+                break;
+            }
+            if already_in.contains(window) {
+                continue;
+            }
+
+            //if the end of the dictionary is the start of this chunk, then we only need the last byte
+            let added = if bytes.ends_with(&window[..WINDOW_LEN - 1]) {
+                bytes.push(window[WINDOW_LEN - 1]);
+                1
+            } else {
+                bytes.extend_from_slice(window);
+                WINDOW_LEN
+            };
+
+            let new_start = bytes.len().saturating_sub(added + WINDOW_LEN - 1);
+            for new_window in bytes[new_start..].windows(WINDOW_LEN) {
+                let mut chunk = [0; WINDOW_LEN];
+                chunk.copy_from_slice(new_window);
+                already_in.insert(chunk);
+            }
+        }
+
+        bytes.truncate(max_len);
+        Self { bytes }
+    }
+
+    ///The bytes in the dictionary.
+    #[must_use]
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    ///Identifies the dictionary, so that blobs can check that they are being decompressed with the same dictionary that they were compressed with.
+    ///
+    /// This is the [`Fnv1a`] hash of the bytes, so it is always the same for the same bytes.
+    #[must_use]
+    pub fn id(&self) -> u64 {
+        let mut hasher = Fnv1a::default();
+        hasher.write(&self.bytes);
+        hasher.finish()
+    }
+
+    ///Serialises the dictionary so that it can be stored once alongside all of the blobs that use it.
+    #[must_use]
+    pub fn ser(&self) -> Vec<u8> {
+        let mut output = Integer::usize(self.bytes.len()).ser().1;
+        output.extend(&self.bytes);
+        output
+    }
+
+    ///Deserialises a dictionary from [`Dict::ser`].
+    ///
+    /// # Errors
+    /// - [`crate::types::integer::IntegerSerError`] if we cannot deserialise the length
+    /// - [`BinarySerError::NotEnoughBytes`] if there aren't enough bytes
+    pub fn deser(cursor: &mut Cursor<u8>) -> Result<Self, BinarySerError> {
+        let len: usize = Integer::deser(SignedState::Unsigned, cursor)?.try_into()?;
+        let bytes = cursor.read_checked(len)?;
+        Ok(Self::new(bytes.to_vec()))
+    }
+}
+
+///Compresses bytes with LZ4 using a shared dictionary.
+#[must_use]
+pub fn lz_with_dict(input: &[u8], dict: &Dict) -> Vec<u8> {
+    let mut output = Integer::u64(dict.id()).ser().1;
+    output.extend(lz_with_known_dict(input, dict));
+    output
+}
+
+///Compresses bytes like [`lz_with_dict`], but without the [`Dict::id`] - for when the dictionary is always stored alongside the bytes (like in a [`Store`](crate::store::Store)), so it can't be the wrong one.
+pub(crate) fn lz_with_known_dict(input: &[u8], dict: &Dict) -> Vec<u8> {
+    let mut output = Integer::usize(input.len()).ser().1; //size of input
+    if input.is_empty() {
+        return output;
+    }
+
+    let compressed = compress_with_dict(input, dict.as_bytes());
+    output.extend(Integer::usize(compressed.len()).ser().1); //size of compressed
+    output.extend(compressed); //compressed
+
+    output
+}
+
+///Decompresses bytes compressed with [`lz_with_dict`].
+///
+/// # Errors
+/// - [`crate::types::integer::IntegerSerError`] if we cannot deserialise an integer
+/// - [`BinarySerError::DictionaryMismatch`] if the bytes were compressed using a different dictionary
+/// - [`BinarySerError::NotEnoughBytes`] if there aren't enough bytes
+/// - [`BinarySerError::ImpossibleLength`] if the uncompressed length couldn't have come from the compressed bytes
+/// - [`lz4_flex::block::DecompressError`] if we fail to decompress the bytes
+pub fn un_lz_with_dict(cursor: &mut Cursor<u8>, dict: &Dict) -> Result<Vec<u8>, BinarySerError> {
+    let found: u64 = Integer::deser(SignedState::Unsigned, cursor)?.try_into()?;
+    let expected = dict.id();
+    if found != expected {
+        return Err(BinarySerError::DictionaryMismatch { expected, found });
+    }
+
+    un_lz_with_known_dict(cursor, dict)
+}
+
+///Decompresses bytes compressed with [`lz_with_known_dict`] - see [`un_lz_with_dict`] for the errors.
+pub(crate) fn un_lz_with_known_dict(
+    cursor: &mut Cursor<u8>,
+    dict: &Dict,
+) -> Result<Vec<u8>, BinarySerError> {
+    let input_len: usize = Integer::deser(SignedState::Unsigned, cursor)?.try_into()?;
+    if input_len == 0 {
+        return Ok(vec![]);
+    }
+
+    let compressed_len = Integer::deser(SignedState::Unsigned, cursor)?.try_into()?;
+    let compressed = cursor.read_checked(compressed_len)?;
+
+    //matches can refer into the dictionary, but each byte of a match can still only expand so far
+    if input_len > compressed_len.saturating_mul(MAX_EXPANSION_RATIO) {
+        return Err(BinarySerError::ImpossibleLength {
+            claimed: input_len,
+            compressed: compressed_len,
+        });
+    }
+
+    Ok(decompress_with_dict(
+        compressed,
+        input_len,
+        dict.as_bytes(),
+    )?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{super::CASES, *};
+    use crate::types::binary::test_roundtrip;
+    use alloc::{format, string::String};
+    use proptest::proptest;
+
+    fn fragments() -> Vec<String> {
+        (0..200)
+            .map(|i| {
+                format!(
+                    r#"{{"id":{i},"username":"user_{i}","email":"user_{i}@example.com","is_active":{},"roles":["reader","writer"]}}"#,
+                    i % 3 == 0
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_dict_specific_cases() {
+        let dict = Dict::new(b"\xFF\xFF\xFF\x00\x00\xDE\xAD\xBE\xEF".to_vec());
+        for case in CASES {
+            test_roundtrip(
+                case,
+                |b| lz_with_dict(b, &dict),
+                |c| un_lz_with_dict(c, &dict),
+            );
+            test_roundtrip(
+                case,
+                |b| lz_with_dict(b, &Dict::default()),
+                |c| un_lz_with_dict(c, &Dict::default()),
+            );
+        }
+    }
+
+    #[test]
+    fn test_trained_dict_beats_per_blob() {
+        let fragments = fragments();
+        let dict = Dict::train(fragments.iter().map(String::as_bytes), 1_024);
+        assert!(!dict.as_bytes().is_empty());
+        assert!(dict.as_bytes().len() <= 1_024);
+
+        let mut per_blob = 0;
+        let mut with_dict = 0;
+        for fragment in &fragments {
+            per_blob += super::super::lz::lz(fragment.as_bytes()).len();
+
+            let bytes = lz_with_dict(fragment.as_bytes(), &dict);
+            with_dict += bytes.len();
+            test_roundtrip(
+                fragment.as_bytes(),
+                |b| lz_with_dict(b, &dict),
+                |c| un_lz_with_dict(c, &dict),
+            );
+        }
+
+        assert!(
+            with_dict + dict.ser().len() < per_blob,
+            "dictionary: {with_dict} + {}, per-blob: {per_blob}",
+            dict.ser().len()
+        );
+    }
+
+    #[test]
+    fn test_train_is_deterministic() {
+        let fragments = fragments();
+        let a = Dict::train(fragments.iter().map(String::as_bytes), 512);
+        let b = Dict::train(fragments.iter().rev().map(String::as_bytes), 512);
+        assert_eq!(a, b);
+        assert!(Dict::train([&b"only one sample"[..]], 512)
+            .as_bytes()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_dict_mismatch() {
+        let dict = Dict::new(b"some dictionary".to_vec());
+        let other = Dict::new(b"another dictionary".to_vec());
+
+        let bytes = lz_with_dict(b"some bytes", &dict);
+        let Err(BinarySerError::DictionaryMismatch { expected, found }) =
+            un_lz_with_dict(&mut Cursor::new(&bytes), &other)
+        else {
+            panic!("decompressed with the wrong dictionary");
+        };
+        assert_eq!(expected, other.id());
+        assert_eq!(found, dict.id());
+    }
+
+    #[test]
+    fn test_dict_ser() {
+        let dict = Dict::new(vec![0xAB; MAX_DICT_LEN + 10]);
+        assert_eq!(dict.as_bytes().len(), MAX_DICT_LEN);
+
+        let bytes = dict.ser();
+        assert_eq!(Dict::deser(&mut Cursor::new(&bytes)).unwrap(), dict);
+    }
+
+    proptest! {
+        #[test]
+        fn proptest_dict_10 (v: [u8; 10], d: [u8; 16]) {
+            let dict = Dict::new(d.to_vec());
+            test_roundtrip(&v, |b| lz_with_dict(b, &dict), |c| un_lz_with_dict(c, &dict));
+        }
+
+        #[test]
+        fn proptest_dict_256 (v: [u8; 256], d: [u8; 64]) {
+            let dict = Dict::new(d.to_vec());
+            test_roundtrip(&v, |b| lz_with_dict(b, &dict), |c| un_lz_with_dict(c, &dict));
+        }
+    }
+}
@@ -9,18 +9,31 @@ use crate::{
 use alloc::{vec, vec::Vec};
 use itertools::Itertools;
 
+///The longest run of one repeated byte which is stored as just its length and the byte. This is the only case where the output isn't limited by the size of the input, so it needs a limit to stop a few bytes of input from asking for gigabytes of output.
+///
+/// Older versions didn't have this limit, so longer runs are still allowed when reading stores from before [`STORE_FORMAT_VERSION`](crate::store::STORE_FORMAT_VERSION) `3` - see [`un_huffman_with_max_run_len`].
+pub const MAX_RUN_LEN: usize = 1 << 24;
+
 pub fn huffman(input: &[u8]) -> Vec<u8> {
-    if input.is_empty() {
+    let huffman = if input.is_empty() {
         return vec![0];
     } else if input.iter().all_equal() {
-        let mut n = Integer::usize(input.len()).ser().1;
-        n.insert(0, 1);
-        n.push(input[0]);
-        return n;
-    }
+        if input.len() <= MAX_RUN_LEN {
+            let mut n = Integer::usize(input.len()).ser().1;
+            n.insert(0, 1);
+            n.push(input[0]);
+            return n;
+        }
 
-    let (huffman, encoded) =
-        Huffman::new_and_encode(input.iter().copied()).expect("already checked for empty list");
+        //trees need two symbols, so add one which never gets used - this still only takes one bit per byte
+        Huffman::new_bytes(&[input[0], input[0].wrapping_add(1)])
+            .expect("list has two distinct bytes")
+    } else {
+        Huffman::new_bytes(input).expect("already checked for empty list")
+    };
+    let encoded = huffman
+        .encode_bytes(input)
+        .expect("already checked for only one distinct byte");
 
     let serialised_huffman = huffman.ser();
     let serialised_bits = encoded.ser();
@@ -33,6 +46,20 @@ pub fn huffman(input: &[u8]) -> Vec<u8> {
 }
 
 pub fn un_huffman(cursor: &mut Cursor<u8>) -> Result<Vec<u8>, HuffmanSerError> {
+    un_huffman_with_max_run_len(cursor, MAX_RUN_LEN)
+}
+
+///Reads bytes from [`huffman`], allowing runs of one repeated byte to be at most `max_run_len` long.
+///
+/// If the limit is above [`MAX_RUN_LEN`], then it is only there for data written by older versions and the run could still be far too big to allocate - that gets returned as [`HuffmanSerError::RunTooLong`] rather than panicking.
+///
+/// # Errors
+/// - [`HuffmanSerError::RunTooLong`] if a run is longer than `max_run_len`, or can't be allocated.
+/// - [`HuffmanSerError`] if there aren't enough bytes, or the tree or bits can't be deserialised.
+pub fn un_huffman_with_max_run_len(
+    cursor: &mut Cursor<u8>,
+    max_run_len: usize,
+) -> Result<Vec<u8>, HuffmanSerError> {
     let Some(first_byte) = cursor.next().copied() else {
         return Err(HuffmanSerError::NotEnoughBytes);
     };
@@ -44,13 +71,22 @@ pub fn un_huffman(cursor: &mut Cursor<u8>) -> Result<Vec<u8>, HuffmanSerError> {
                 .next()
                 .copied()
                 .ok_or(HuffmanSerError::NotEnoughBytes)?;
-            vec![element; count]
+
+            //`count` comes straight from the input, so check it before allocating anything
+            if count > max_run_len {
+                return Err(HuffmanSerError::RunTooLong(count));
+            }
+            let mut run = Vec::new();
+            run.try_reserve_exact(count)
+                .map_err(|_| HuffmanSerError::RunTooLong(count))?;
+            run.resize(count, element);
+            run
         }
         _ => {
             let huffman = Huffman::<u8>::deser(cursor)?;
             let bits = Bits::deser(cursor)?;
 
-            huffman.decode(bits)?
+            huffman.decode_bytes(&bits)?
         }
     })
 }
@@ -58,10 +94,18 @@ pub fn un_huffman(cursor: &mut Cursor<u8>) -> Result<Vec<u8>, HuffmanSerError> {
 #[cfg(test)]
 mod tests {
     use super::super::CASES;
-    use crate::types::binary::{
-        huffman::{huffman, un_huffman},
-        test_roundtrip,
+    use super::MAX_RUN_LEN;
+    use crate::{
+        types::{
+            binary::{
+                huffman::{huffman, un_huffman},
+                test_roundtrip,
+            },
+            integer::Integer,
+        },
+        utilities::{cursor::Cursor, huffman::HuffmanSerError},
     };
+    use alloc::vec;
     use proptest::proptest;
 
     #[test]
@@ -71,6 +115,23 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_huff_long_runs() {
+        let run = vec![7; MAX_RUN_LEN + 1];
+        let encoded = huffman(&run);
+        assert!(encoded.len() < run.len() / 7);
+        test_roundtrip(&run, huffman, un_huffman);
+
+        //a few bytes claiming a huge run shouldn't get allocated
+        let mut malicious = vec![1];
+        malicious.extend(Integer::usize(usize::MAX / 2).ser().1);
+        malicious.push(0);
+        assert!(matches!(
+            un_huffman(&mut Cursor::new(&malicious)),
+            Err(HuffmanSerError::RunTooLong(_))
+        ));
+    }
+
     proptest! {
         #[test]
         fn proptest_huffman_1 (v: [u8; 1]) {
@@ -8,6 +8,9 @@ use crate::{
 use alloc::{vec, vec::Vec};
 use lz4_flex::{compress, decompress};
 
+///LZ4 can never expand a block by more than this factor, as a single byte of a match length can at most add 255 bytes to the output.
+pub(super) const MAX_EXPANSION_RATIO: usize = 255;
+
 #[must_use]
 pub fn lz(input: &[u8]) -> Vec<u8> {
     let size = Integer::usize(input.len()).ser().1;
@@ -28,6 +31,7 @@ pub fn lz(input: &[u8]) -> Vec<u8> {
 /// # Errors
 /// - [`crate::types::integer::IntegerSerError`] if we cannot deserialise an integer
 /// - [`BinarySerError::NotEnoughBytes`] if there aren't enough bytes
+/// - [`BinarySerError::ImpossibleLength`] if the uncompressed length couldn't have come from the compressed bytes
 /// - [`lz4_flex::block::DecompressError`] if we fail to decompress the bytes
 pub fn un_lz(cursor: &mut Cursor<u8>) -> Result<Vec<u8>, BinarySerError> {
     let input_len: usize = Integer::deser(SignedState::Unsigned, cursor)?.try_into()?;
@@ -36,9 +40,15 @@ pub fn un_lz(cursor: &mut Cursor<u8>) -> Result<Vec<u8>, BinarySerError> {
     }
 
     let compressed_len = Integer::deser(SignedState::Unsigned, cursor)?.try_into()?;
-    let compressed = cursor
-        .read(compressed_len)
-        .ok_or(BinarySerError::NotEnoughBytes)?;
+    let compressed = cursor.read_checked(compressed_len)?;
+
+    //check before decompressing, as `decompress` allocates the whole claimed length up front
+    if input_len > compressed_len.saturating_mul(MAX_EXPANSION_RATIO) {
+        return Err(BinarySerError::ImpossibleLength {
+            claimed: input_len,
+            compressed: compressed_len,
+        });
+    }
 
     Ok(decompress(compressed, input_len)?)
 }
@@ -53,13 +53,7 @@ pub fn un_rle(cursor: &mut Cursor<u8>) -> Result<Vec<u8>, BinarySerError> {
 
     let mut output = vec![];
 
-    for (count, byte) in cursor
-        .read(len)
-        .ok_or(BinarySerError::NotEnoughBytes)?
-        .iter()
-        .copied()
-        .tuples()
-    {
+    for (count, byte) in cursor.read_checked(len)?.iter().copied().tuples() {
         (0..count).for_each(|_| output.push(byte));
     }
 
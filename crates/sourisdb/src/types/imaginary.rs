@@ -191,6 +191,58 @@ impl Imaginary {
         Imaginary::PolarForm { modulus, argument }
     }
 
+    ///Gets the complex conjugate, which has the same real part but the opposite imaginary part.
+    ///
+    /// - In cartesian form, the imaginary coefficient is negated. If that wouldn't fit into an [`Integer`] (ie. it is more than [`i128::MAX`]), the polar form is conjugated instead.
+    /// - In polar form, the argument is negated.
+    ///
+    ///```rust
+    /// use sourisdb::types::imaginary::Imaginary;
+    ///
+    /// let number = Imaginary::CartesianForm { real: 3.into(), imaginary: 4.into() };
+    /// assert_eq!(number.conjugate(), Imaginary::CartesianForm { real: 3.into(), imaginary: (-4).into() });
+    /// ```
+    #[must_use]
+    pub fn conjugate(self) -> Self {
+        match self {
+            Self::CartesianForm { real, imaginary } => {
+                let fits_when_negated = imaginary.is_negative()
+                    || u128::try_from(imaginary).is_ok_and(|n| i128::try_from(n).is_ok());
+                if fits_when_negated {
+                    Self::CartesianForm {
+                        real,
+                        imaginary: -imaginary,
+                    }
+                } else {
+                    self.to_polar_form().conjugate()
+                }
+            }
+            Self::PolarForm { modulus, argument } => Self::PolarForm {
+                modulus,
+                argument: -argument,
+            },
+        }
+    }
+
+    ///Gets the magnitude (or absolute value) of the number.
+    ///
+    /// - In polar form, this is just the modulus.
+    /// - In cartesian form, this is calculated using [`f64::hypot`].
+    ///
+    ///```rust
+    /// use sourisdb::types::imaginary::Imaginary;
+    ///
+    /// let number = Imaginary::CartesianForm { real: 3.into(), imaginary: (-4).into() };
+    /// assert!((number.magnitude() - 5.0).abs() < f64::EPSILON);
+    /// ```
+    #[must_use]
+    pub fn magnitude(self) -> f64 {
+        match self {
+            Self::CartesianForm { real, imaginary } => f64::from(real).hypot(f64::from(imaginary)),
+            Self::PolarForm { modulus, .. } => modulus,
+        }
+    }
+
     ///Serialises the floating point number into 4 magic bits and bytes.
     ///
     /// The 4 magic bits are kept inside the range `0b0000_0000` to `0b0000_1111`.
@@ -277,12 +329,72 @@ impl Imaginary {
             let imaginary = Integer::deser(imaginary_signed_state, bytes)?;
             Ok(Self::CartesianForm { real, imaginary })
         } else {
-            let modulus =
-                f64::from_le_bytes(*bytes.read_exact().ok_or(IntegerSerError::NotEnoughBytes)?);
-            let argument =
-                f64::from_le_bytes(*bytes.read_exact().ok_or(IntegerSerError::NotEnoughBytes)?);
+            let modulus = f64::from_le_bytes(*bytes.read_exact_checked()?);
+            let argument = f64::from_le_bytes(*bytes.read_exact_checked()?);
 
             Ok(Self::PolarForm { modulus, argument })
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Imaginary;
+    use crate::types::integer::Integer;
+    use core::f64::consts::PI;
+
+    #[test]
+    fn test_conjugate() {
+        let cartesian = Imaginary::CartesianForm {
+            real: 1.into(),
+            imaginary: (-2).into(),
+        };
+        assert_eq!(
+            cartesian.conjugate(),
+            Imaginary::CartesianForm {
+                real: 1.into(),
+                imaginary: 2.into()
+            }
+        );
+        assert_eq!(cartesian.conjugate().conjugate(), cartesian);
+
+        let polar = Imaginary::PolarForm {
+            modulus: 2.0,
+            argument: PI / 3.0,
+        };
+        assert_eq!(
+            polar.conjugate(),
+            Imaginary::PolarForm {
+                modulus: 2.0,
+                argument: -PI / 3.0
+            }
+        );
+
+        //can't be negated as an integer, so we get the polar form instead
+        let huge = Imaginary::CartesianForm {
+            real: 0.into(),
+            imaginary: Integer::from(u128::MAX),
+        };
+        let Imaginary::PolarForm { argument, .. } = huge.conjugate() else {
+            panic!("expected polar form");
+        };
+        assert!((argument + PI / 2.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_magnitude() {
+        let cartesian = Imaginary::CartesianForm {
+            real: (-5).into(),
+            imaginary: 12.into(),
+        };
+        assert!((cartesian.magnitude() - 13.0).abs() < f64::EPSILON);
+        assert!((cartesian.conjugate().magnitude() - 13.0).abs() < f64::EPSILON);
+
+        let polar = Imaginary::PolarForm {
+            modulus: 2.5,
+            argument: -1.0,
+        };
+        assert!((polar.magnitude() - 2.5).abs() < f64::EPSILON);
+        assert!((cartesian.to_polar_form().magnitude() - 13.0).abs() < f64::EPSILON);
+    }
+}
@@ -6,16 +6,23 @@ use alloc::{
     vec::Vec,
 };
 use core::{
+    cmp::Ordering,
     fmt::{Debug, Display, Formatter},
     hash::{Hash, Hasher},
     num::ParseIntError,
-    ops::{Add, Div, Mul, Sub},
+    ops::{Add, Div, Mul, Neg, Sub},
     str::FromStr,
 };
 
 use serde_json::{Number, Value as SJValue};
 
-use crate::{display_bytes_as_hex_array, utilities::cursor::Cursor};
+use crate::{
+    display_bytes_as_hex_array,
+    utilities::{
+        bits::Bits,
+        cursor::{Cursor, CursorError},
+    },
+};
 
 ///This represents whether a number is signed or unsigned. There are conversions to/from [`u8`]s which use two bytes.
 #[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
@@ -64,7 +71,8 @@ pub const ONE_BYTE_MAX_SIZE: u8 = u8::MAX - (INTEGER_MAX_SIZE as u8);
 ///
 /// When converting to a floating point number, precision can be lost. When converting from a floating number, it can fail if:
 /// - The floating point number was too large.
-/// - The floating point number had a decimal part (currently checked using [`f64::fract`], [`f64::EPSILON`] and the [`f32`] equivalents).
+/// - The floating point number had a decimal part (checked using [`f64::fract`], so even tiny decimal parts fail).
+/// - The floating point number wasn't finite.
 #[derive(Copy, Clone)]
 pub struct Integer {
     signed_state: SignedState,
@@ -73,34 +81,59 @@ pub struct Integer {
     number_of_bytes_used: usize,
 }
 
+///Compares the numbers that the integers represent, regardless of how they were created - so `0_u8` and `0_i8` are equal. This agrees with [`Ord`].
 impl PartialEq for Integer {
     fn eq(&self, other: &Self) -> bool {
-        if self.content[0..self.number_of_bytes_used]
-            != other.content[0..other.number_of_bytes_used]
-        {
-            return false;
-        }
-
-        match self.signed_state {
-            SignedState::Unsigned | SignedState::SignedPositive => {
-                other.signed_state != SignedState::SignedNegative
-            }
-            SignedState::SignedNegative => other.signed_state == SignedState::SignedNegative,
-        }
+        self.cmp(other) == Ordering::Equal
     }
 }
 impl Eq for Integer {}
 
 impl Hash for Integer {
     fn hash<H: Hasher>(&self, state: &mut H) {
-        let ss_to_be_hashed = if self.signed_state == SignedState::SignedNegative {
-            SignedState::SignedNegative
+        //hashes the number rather than how it is stored, to agree with `PartialEq`
+        if self.is_negative() {
+            let Ok(n) = BiggestIntButSigned::try_from(*self) else {
+                unreachable!("negative integers always fit into an i128")
+            };
+            n.hash(state);
         } else {
-            SignedState::SignedPositive
-        };
-        ss_to_be_hashed.hash(state);
-        self.content[0..self.number_of_bytes_used].hash(state);
-        self.number_of_bytes_used.hash(state);
+            let Ok(n) = BiggestInt::try_from(*self) else {
+                unreachable!("positive integers always fit into a u128")
+            };
+            n.hash(state);
+        }
+    }
+}
+
+///Compares the numbers that the integers represent, regardless of how they were created.
+impl PartialOrd for Integer {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl Ord for Integer {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.is_negative(), other.is_negative()) {
+            (true, false) => Ordering::Less,
+            (false, true) => Ordering::Greater,
+            (true, true) => {
+                let (Ok(a), Ok(b)) = (
+                    BiggestIntButSigned::try_from(*self),
+                    BiggestIntButSigned::try_from(*other),
+                ) else {
+                    unreachable!("negative integers always fit into an i128")
+                };
+                a.cmp(&b)
+            }
+            (false, false) => {
+                let (Ok(a), Ok(b)) = (BiggestInt::try_from(*self), BiggestInt::try_from(*other))
+                else {
+                    unreachable!("positive integers always fit into a u128")
+                };
+                a.cmp(&b)
+            }
+        }
     }
 }
 
@@ -117,6 +150,276 @@ impl Integer {
         self.signed_state != SignedState::SignedNegative
     }
 
+    ///Gets the absolute value of the number.
+    ///
+    /// The result is always unsigned, which means that the absolute value of [`i128::MIN`] (which doesn't fit into an [`i128`]) is represented exactly rather than overflowing or saturating.
+    ///
+    /// ```rust
+    /// use sourisdb::types::integer::Integer;
+    ///
+    /// assert_eq!(Integer::i8(-5).abs(), Integer::u8(5));
+    /// assert_eq!(Integer::i128(i128::MIN).abs(), Integer::u128(1 << 127));
+    /// ```
+    #[must_use]
+    #[allow(clippy::missing_panics_doc)]
+    pub fn abs(self) -> Integer {
+        if !self.is_negative() {
+            return self;
+        }
+
+        let Ok(n) = BiggestIntButSigned::try_from(self) else {
+            unreachable!("negative integers always fit into an i128")
+        };
+        n.unsigned_abs().into()
+    }
+
+    ///Negates the number, returning [`None`] if the result is too small to fit into an [`i128`] - ie. if the number is positive and bigger than `i128::MAX + 1`.
+    ///
+    /// Negative numbers are made positive using [`Integer::abs`], so this never fails for them. [`Neg`] does the same, but panics instead of returning [`None`].
+    ///
+    /// ```rust
+    /// use sourisdb::types::integer::Integer;
+    ///
+    /// assert_eq!(Integer::u8(5).checked_neg(), Some(Integer::i8(-5)));
+    /// assert_eq!(Integer::i128(i128::MIN).checked_neg(), Some(Integer::u128(1 << 127)));
+    /// assert_eq!(Integer::u128(1 << 127).checked_neg(), Some(Integer::i128(i128::MIN)));
+    /// assert_eq!(Integer::u128(u128::MAX).checked_neg(), None);
+    /// ```
+    #[must_use]
+    pub fn checked_neg(self) -> Option<Integer> {
+        if self.is_negative() {
+            return Some(self.abs());
+        }
+
+        let Ok(n) = BiggestInt::try_from(self) else {
+            unreachable!("positive integers always fit into a u128")
+        };
+        BiggestIntButSigned::checked_sub_unsigned(0, n).map(Integer::from)
+    }
+
+    ///Gets the sign of the number - `-1` if it is negative, `0` if it is zero, and `1` if it is positive.
+    #[must_use]
+    pub fn signum(&self) -> i8 {
+        if self.is_negative() {
+            return -1;
+        }
+
+        i8::from(
+            self.content[0..self.number_of_bytes_used]
+                .iter()
+                .any(|b| *b != 0),
+        )
+    }
+
+    ///The number of bytes [`Integer::ser`] stores after the size byte when the number doesn't fit into one byte.
+    ///
+    /// Negative numbers are stored in two's complement with the leading `0xFF` bytes skipped, so (for example) `-129` only takes one byte as the sign fills in the rest.
+    ///
+    /// ```rust
+    /// use sourisdb::types::integer::Integer;
+    ///
+    /// assert_eq!(Integer::u32(0xFF).byte_length(), 1);
+    /// assert_eq!(Integer::u32(0x100).byte_length(), 2);
+    /// assert_eq!(Integer::i32(-257).byte_length(), 2);
+    /// ```
+    #[must_use]
+    pub fn byte_length(&self) -> usize {
+        self.number_of_bytes_used
+    }
+
+    ///The number of significant bits in the number.
+    ///
+    /// For positive numbers, this is the position of the highest set bit, so `0` has a bit length of `0`. For negative numbers, this is the position of the highest bit that isn't a copy of the sign bit in two's complement, so `-1` has a bit length of `0` and `-128` has a bit length of `7` (the same as `127`).
+    ///
+    /// ```rust
+    /// use sourisdb::types::integer::Integer;
+    ///
+    /// assert_eq!(Integer::u8(0).bit_length(), 0);
+    /// assert_eq!(Integer::u8(5).bit_length(), 3);
+    /// assert_eq!(Integer::i8(-128).bit_length(), 7);
+    /// assert_eq!(Integer::i8(-1).bit_length(), 0);
+    /// assert_eq!(Integer::u128(u128::MAX).bit_length(), 128);
+    /// ```
+    #[must_use]
+    #[allow(clippy::missing_panics_doc)]
+    pub fn bit_length(&self) -> usize {
+        let leading_zeros = if self.is_negative() {
+            let Ok(n) = BiggestIntButSigned::try_from(*self) else {
+                unreachable!("negative integers always fit into an i128")
+            };
+            (!n).leading_zeros()
+        } else {
+            let Ok(n) = BiggestInt::try_from(*self) else {
+                unreachable!("positive integers always fit into a u128")
+            };
+            n.leading_zeros()
+        };
+
+        (BiggestInt::BITS - leading_zeros) as usize
+    }
+
+    ///Raises the number to the power of `exp`, returning `None` if the result would overflow.
+    ///
+    /// The result keeps the signedness of `self` - unsigned numbers are raised using [`u128::checked_pow`] and signed numbers using [`i128::checked_pow`], so a signed number must stay within the range of an [`i128`].
+    ///
+    /// ```rust
+    /// use sourisdb::types::integer::Integer;
+    ///
+    /// assert_eq!(Integer::u8(2).pow(10), Some(Integer::u16(1024)));
+    /// assert_eq!(Integer::i8(-3).pow(3), Some(Integer::i8(-27)));
+    /// assert_eq!(Integer::u8(2).pow(128), None);
+    /// ```
+    #[must_use]
+    pub fn pow(self, exp: u32) -> Option<Integer> {
+        if self.signed_state == SignedState::Unsigned {
+            let n = BiggestInt::try_from(self).ok()?;
+            n.checked_pow(exp).map(Integer::from)
+        } else {
+            let n = BiggestIntButSigned::try_from(self).ok()?;
+            n.checked_pow(exp).map(Integer::from)
+        }
+    }
+
+    ///Whether the number is a power of two. Negative numbers and zero are never powers of two.
+    ///
+    /// ```rust
+    /// use sourisdb::types::integer::Integer;
+    ///
+    /// assert!(Integer::u32(4096).is_power_of_two());
+    /// assert!(!Integer::i8(-4).is_power_of_two());
+    /// assert!(!Integer::u8(0).is_power_of_two());
+    /// ```
+    #[must_use]
+    pub fn is_power_of_two(&self) -> bool {
+        if self.is_negative() {
+            return false;
+        }
+
+        BiggestInt::try_from(*self).is_ok_and(BiggestInt::is_power_of_two)
+    }
+
+    ///Gets the big-endian bytes of the `Integer`, skipping any leading bytes which only hold the sign (`0x00` for positive numbers, and `0xFF` for negative numbers). At least one byte is always returned.
+    ///
+    /// Negative numbers use two's complement, so use [`Integer::is_negative`] to get the [`SignedState`] needed to read them back using [`Integer::from_be_bytes`].
+    ///
+    /// NB: This is purely for interoperability - the crate's own serialisation format always uses little-endian bytes.
+    ///
+    /// ```rust
+    /// use sourisdb::types::integer::{Integer, SignedState};
+    ///
+    /// assert_eq!(Integer::u32(0x0102).to_be_bytes(), [0x01, 0x02]);
+    /// assert_eq!(Integer::i32(-2).to_be_bytes(), [0xFE]);
+    ///
+    /// let read_back = Integer::from_be_bytes(&[0x00, 0x00, 0x01, 0x02], SignedState::Unsigned).unwrap();
+    /// assert_eq!(u32::try_from(read_back).unwrap(), 0x0102);
+    /// ```
+    #[must_use]
+    pub fn to_be_bytes(&self) -> Vec<u8> {
+        if self.number_of_bytes_used == 0 {
+            return vec![0];
+        }
+
+        self.content[0..self.number_of_bytes_used]
+            .iter()
+            .rev()
+            .copied()
+            .collect()
+    }
+
+    ///Reads an `Integer` from big-endian bytes, as produced by [`Integer::to_be_bytes`] or something like [`u32::to_be_bytes`].
+    ///
+    /// If the [`SignedState`] is [`SignedState::SignedNegative`], then the bytes are treated as being two's complement.
+    ///
+    /// NB: This is purely for interoperability - the crate's own serialisation format always uses little-endian bytes.
+    ///
+    /// # Errors
+    /// - [`IntegerSerError::TooBigToFit`] if, after skipping leading sign bytes, there are too many bytes to fit into an `Integer`.
+    pub fn from_be_bytes(bytes: &[u8], signed_state: SignedState) -> Result<Self, IntegerSerError> {
+        let filler = if signed_state == SignedState::SignedNegative {
+            u8::MAX
+        } else {
+            0
+        };
+
+        let first_non_filler = bytes
+            .iter()
+            .position(|b| *b != filler)
+            .unwrap_or(bytes.len());
+        let bytes = &bytes[first_non_filler..];
+        if bytes.len() > INTEGER_MAX_SIZE {
+            return Err(IntegerSerError::TooBigToFit);
+        }
+
+        let mut content = [filler; INTEGER_MAX_SIZE];
+        for (i, b) in bytes.iter().rev().copied().enumerate() {
+            content[i] = b;
+        }
+
+        //mirror the `From` implementations, where only signed positive numbers can use 0 bytes
+        let number_of_bytes_used = if signed_state == SignedState::SignedPositive {
+            bytes.len()
+        } else {
+            bytes.len().max(1)
+        };
+
+        Ok(Self {
+            signed_state,
+            content,
+            number_of_bytes_used,
+        })
+    }
+
+    ///Gets the significant bits of the `Integer` (see [`Integer::bit_length`]), least-significant first.
+    ///
+    /// Like [`Integer::to_be_bytes`], negative numbers use two's complement with the leading `1`s skipped, so use [`Integer::is_negative`] to get the [`SignedState`] needed to read them back using [`Integer::from_bits`].
+    ///
+    /// ```rust
+    /// use sourisdb::{types::integer::{Integer, SignedState}, utilities::bits::Bits};
+    ///
+    /// assert_eq!(Integer::u8(6).to_bits(), Bits::from([false, true, true]));
+    /// assert_eq!(Integer::i8(-6).to_bits(), Bits::from([false, true, false]));
+    ///
+    /// let read_back = Integer::from_bits(&Bits::from([false, true, false]), SignedState::SignedNegative).unwrap();
+    /// assert_eq!(i8::try_from(read_back).unwrap(), -6);
+    /// ```
+    #[must_use]
+    #[allow(clippy::missing_panics_doc, clippy::cast_sign_loss)]
+    pub fn to_bits(&self) -> Bits {
+        let n = if self.is_negative() {
+            let Ok(n) = BiggestIntButSigned::try_from(*self) else {
+                unreachable!("negative integers always fit into an i128")
+            };
+            n as BiggestInt
+        } else {
+            let Ok(n) = BiggestInt::try_from(*self) else {
+                unreachable!("positive integers always fit into a u128")
+            };
+            n
+        };
+
+        (0..self.bit_length()).map(|i| n & (1 << i) != 0).collect()
+    }
+
+    ///Reads an `Integer` from bits, least-significant first, as produced by [`Integer::to_bits`].
+    ///
+    /// If the [`SignedState`] is [`SignedState::SignedNegative`], then the bits are treated as being two's complement, with any bits past the end being `1`s.
+    ///
+    /// # Errors
+    /// - [`IntegerSerError::TooBigToFit`] if, after skipping leading sign bits, there are too many bits to fit into an `Integer`.
+    pub fn from_bits(bits: &Bits, signed_state: SignedState) -> Result<Self, IntegerSerError> {
+        let filler = signed_state == SignedState::SignedNegative;
+
+        let mut bytes = vec![if filler { u8::MAX } else { 0 }; bits.len().div_ceil(8)];
+        for (i, bit) in bits.iter_bits().enumerate() {
+            if bit != filler {
+                bytes[i / 8] ^= 1 << (i % 8);
+            }
+        }
+        bytes.reverse();
+
+        Self::from_be_bytes(&bytes, signed_state)
+    }
+
     ///Converts the `Integer` to a [`serde_json::Value`].
     ///
     /// This can fail if the integer doesn't fit into i64 or u64 as those are the limits for [`Number`].
@@ -190,6 +493,20 @@ macro_rules! new_x {
     };
 }
 
+macro_rules! as_x {
+    ($($t:ty => $name:ident),+) => {
+        impl Integer {
+            $(
+                ///Converts the `Integer` to the target type, returning [`None`] if it doesn't fit rather than an error like the [`TryFrom`] implementation does.
+                #[must_use]
+                pub fn $name(&self) -> Option<$t> {
+                    <$t>::try_from(*self).ok()
+                }
+            )+
+        }
+    };
+}
+
 macro_rules! from_signed {
     ($($t:ty),+) => {
         $(
@@ -339,6 +656,7 @@ macro_rules! from_unsigned {
 }
 
 new_x!(u8 => u8, i8 => i8, u16 => u16, i16 => i16, u32 => u32, i32 => i32, usize => usize, isize => isize, u64 => u64, i64 => i64, u128 => u128, i128 => i128);
+as_x!(u8 => as_u8, i8 => as_i8, u16 => as_u16, i16 => as_i16, u32 => as_u32, i32 => as_i32, usize => as_usize, isize => as_isize, u64 => as_u64, i64 => as_i64, u128 => as_u128, i128 => as_i128);
 
 from_signed!(i8, i16, i32, i64, isize, i128);
 from_unsigned!(u8, u16, u32, u64, usize, u128);
@@ -412,7 +730,7 @@ impl TryFrom<f64> for Integer {
         if !value.is_finite() {
             return Err(FloatToIntegerConversionError::NotFinite);
         }
-        if value.fract() > f64::EPSILON {
+        if value.fract() != 0.0 {
             return Err(FloatToIntegerConversionError::DecimalsNotSupported(
                 value.fract(),
             ));
@@ -420,7 +738,7 @@ impl TryFrom<f64> for Integer {
 
         let floored = value.floor();
         if floored < 0.0 {
-            if floored > BiggestIntButSigned::MIN as f64 {
+            if floored >= BiggestIntButSigned::MIN as f64 {
                 Ok((floored as BiggestIntButSigned).into())
             } else {
                 Err(FloatToIntegerConversionError::TooLarge)
@@ -437,33 +755,9 @@ impl TryFrom<f64> for Integer {
 impl TryFrom<f32> for Integer {
     type Error = FloatToIntegerConversionError;
 
-    #[allow(
-        clippy::collapsible_else_if,
-        clippy::cast_precision_loss,
-        clippy::cast_possible_truncation,
-        clippy::cast_sign_loss
-    )]
     fn try_from(value: f32) -> Result<Self, Self::Error> {
-        if value.fract() > f32::EPSILON {
-            return Err(FloatToIntegerConversionError::DecimalsNotSupported(
-                f64::from(value.fract()),
-            ));
-        }
-
-        let floored = value.floor();
-        if floored < 0.0 {
-            if floored > i128::MIN as f32 {
-                Ok((floored as i128).into())
-            } else {
-                Err(FloatToIntegerConversionError::TooLarge)
-            }
-        } else {
-            if floored > u128::MAX as f32 {
-                Ok((floored as u128).into())
-            } else {
-                Err(FloatToIntegerConversionError::TooLarge)
-            }
-        }
+        //every f32 can be represented exactly as an f64
+        Self::try_from(f64::from(value))
     }
 }
 
@@ -506,6 +800,21 @@ integer_trait_impl!(Sub, sub);
 integer_trait_impl!(Mul, mul);
 integer_trait_impl!(Div, div);
 
+impl Neg for Integer {
+    type Output = Self;
+
+    ///Negates the number - see [`Integer::abs`] for how negative numbers are made positive.
+    ///
+    /// # Panics
+    /// Panics if the number is positive and its negation is too small to fit into an [`i128`] (eg. `-Integer::u128(u128::MAX)`) - use [`Integer::checked_neg`] to handle that instead.
+    fn neg(self) -> Self::Output {
+        let Some(negated) = self.checked_neg() else {
+            panic!("negated integer too small to fit into i128")
+        };
+        negated
+    }
+}
+
 #[cfg(feature = "serde")]
 impl serde::Serialize for Integer {
     fn serialize<S>(&self, serialiser: S) -> Result<S::Ok, S::Error>
@@ -659,6 +968,11 @@ impl From<ParseIntError> for IntegerSerError {
         Self::IntegerParseError(value)
     }
 }
+impl From<CursorError> for IntegerSerError {
+    fn from(_: CursorError) -> Self {
+        Self::NotEnoughBytes
+    }
+}
 
 impl Display for IntegerSerError {
     fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
@@ -749,9 +1063,7 @@ impl Integer {
         }
 
         let number_of_bytes_used = (first_byte - ONE_BYTE_MAX_SIZE) as usize;
-        let Some(bytes_stored) = reader.read(number_of_bytes_used) else {
-            return Err(IntegerSerError::NotEnoughBytes);
-        };
+        let bytes_stored = reader.read_checked(number_of_bytes_used)?;
 
         let mut content = if signed_state == SignedState::SignedNegative {
             [u8::MAX; INTEGER_MAX_SIZE]
@@ -779,8 +1091,8 @@ mod tests {
 
     #[allow(unused_imports)]
     use crate::{
-        types::integer::{BiggestInt, BiggestIntButSigned, Integer},
-        utilities::cursor::Cursor,
+        types::integer::{BiggestInt, BiggestIntButSigned, Integer, IntegerSerError, SignedState},
+        utilities::{bits::Bits, cursor::Cursor},
     };
 
     #[test]
@@ -805,7 +1117,96 @@ mod tests {
         }
     }
 
+    #[test]
+    fn be_bytes_too_big() {
+        let mut bytes = [0_u8; 17];
+        bytes[0] = 1;
+        assert!(matches!(
+            Integer::from_be_bytes(&bytes, SignedState::Unsigned),
+            Err(IntegerSerError::TooBigToFit)
+        ));
+
+        let mut padded = [0_u8; 20];
+        padded[19] = 5;
+        assert_eq!(
+            Integer::from_be_bytes(&padded, SignedState::Unsigned).unwrap(),
+            Integer::u8(5)
+        );
+    }
+
+    #[test]
+    fn as_x_conversions() {
+        assert_eq!(Integer::u32(300).as_u16(), Some(300));
+        assert_eq!(Integer::u32(300).as_u8(), None);
+        assert_eq!(Integer::i8(-1).as_i64(), Some(-1));
+        assert_eq!(Integer::i8(-1).as_usize(), None);
+        assert_eq!(Integer::u128(u128::MAX).as_u64(), None);
+        assert_eq!(Integer::u128(u128::MAX).as_u128(), Some(u128::MAX));
+    }
+
+    #[test]
+    fn eq_ignores_how_integers_were_made() {
+        use alloc::collections::BTreeMap;
+
+        assert_eq!(Integer::u8(0), Integer::i8(0));
+        assert_eq!(Integer::u64(300), Integer::i16(300));
+        assert_ne!(Integer::i8(-1), Integer::u128(u128::MAX));
+
+        let mut map = BTreeMap::new();
+        map.insert(Integer::u8(5), "u8");
+        map.insert(Integer::i64(5), "i64");
+        assert_eq!(map.len(), 1);
+        assert_eq!(map.get(&Integer::u128(5)), Some(&"i64"));
+    }
+
     proptest! {
+        #[test]
+        fn bits_round_trip_signed (i in any::<BiggestIntButSigned>()) {
+            let int = Integer::from(i);
+            let bits = int.to_bits();
+            prop_assert_eq!(bits.len(), int.bit_length());
+
+            let signed_state = if int.is_negative() { SignedState::SignedNegative } else { SignedState::SignedPositive };
+            let read_back = Integer::from_bits(&bits, signed_state).expect("unable to read integer from bits");
+            prop_assert_eq!(BiggestIntButSigned::try_from(read_back).expect("unable to get i128 from integer"), i);
+        }
+
+        #[test]
+        fn bits_round_trip_unsigned (u in any::<BiggestInt>(), extra_zeros in 0_usize..16) {
+            let mut bits = Integer::from(u).to_bits();
+            for _ in 0..extra_zeros {
+                bits.push(false);
+            }
+
+            let read_back = Integer::from_bits(&bits, SignedState::Unsigned).expect("unable to read integer from bits");
+            prop_assert_eq!(BiggestInt::try_from(read_back).expect("unable to get u128 from integer"), u);
+        }
+
+        #[test]
+        fn ord_matches_i128 (a in any::<BiggestIntButSigned>(), b in any::<BiggestIntButSigned>()) {
+            prop_assert_eq!(Integer::from(a).cmp(&Integer::from(b)), a.cmp(&b));
+        }
+
+        #[test]
+        fn ord_across_signedness (a in any::<BiggestIntButSigned>(), b in any::<u64>()) {
+            prop_assert_eq!(Integer::from(a).cmp(&Integer::from(b)), a.cmp(&BiggestIntButSigned::from(b)));
+        }
+
+        #[test]
+        fn eq_and_hash_agree_with_ord (a in any::<i64>(), b in any::<u64>()) {
+            use core::hash::BuildHasher;
+            let builder = hashbrown::DefaultHashBuilder::default();
+            let hash = |i: &Integer| builder.hash_one(i);
+
+            let (a_unsigned, a) = (u64::try_from(a).map(Integer::from), Integer::from(a));
+            let b = Integer::from(b);
+            prop_assert_eq!(a == b, a.cmp(&b).is_eq());
+            if let Ok(a_unsigned) = a_unsigned {
+                prop_assert_eq!(a_unsigned, a);
+                prop_assert_eq!(hash(&a_unsigned), hash(&a));
+            }
+        }
+
         #[test]
         fn doesnt_crash (s in "\\PC*") {
             let _ = Integer::from_str(&s);
@@ -847,6 +1248,117 @@ mod tests {
             prop_assert_eq!(u32::try_from(got_back).expect("unable to get u32 from integer"), u32::from(i));
         }
 
+        #[test]
+        fn be_bytes_round_trip_signed (i in any::<BiggestIntButSigned>()) {
+            let int = Integer::from(i);
+            let bytes = int.to_be_bytes();
+            let got_back = Integer::from_be_bytes(&bytes, int.signed_state).expect("unable to read integer from be bytes");
+
+            prop_assert_eq!(int, got_back);
+            prop_assert_eq!(BiggestIntButSigned::try_from(got_back).expect("unable to get BIBS from integer"), i);
+
+            let full_width = Integer::from_be_bytes(&i.to_be_bytes(), int.signed_state).expect("unable to read integer from be bytes");
+            prop_assert_eq!(int, full_width);
+        }
+
+        #[test]
+        fn be_bytes_round_trip_unsigned (i in any::<BiggestInt>()) {
+            let int = Integer::from(i);
+            let bytes = int.to_be_bytes();
+            let got_back = Integer::from_be_bytes(&bytes, SignedState::Unsigned).expect("unable to read integer from be bytes");
+
+            prop_assert_eq!(int, got_back);
+            prop_assert_eq!(BiggestInt::try_from(got_back).expect("unable to get BI from integer"), i);
+
+            let full_width = Integer::from_be_bytes(&i.to_be_bytes(), SignedState::Unsigned).expect("unable to read integer from be bytes");
+            prop_assert_eq!(int, full_width);
+        }
+
+        #[test]
+        fn abs_signum_neg_signed (i in any::<BiggestIntButSigned>()) {
+            let int = Integer::from(i);
+
+            prop_assert_eq!(BiggestInt::try_from(int.abs()).expect("unable to get BI from integer"), i.unsigned_abs());
+            prop_assert_eq!(i128::from(int.signum()), i.signum());
+
+            if let Some(negated) = i.checked_neg() {
+                prop_assert_eq!(BiggestIntButSigned::try_from(-int).expect("unable to get BIBS from integer"), negated);
+            } else {
+                prop_assert_eq!(-int, Integer::u128(1 << 127));
+            }
+        }
+
+        #[test]
+        fn abs_signum_neg_unsigned (i in any::<u64>()) {
+            let int = Integer::from(i);
+
+            prop_assert_eq!(int.abs(), int);
+            prop_assert_eq!(int.signum(), i8::from(i != 0));
+            prop_assert_eq!(BiggestIntButSigned::try_from(-int).expect("unable to get BIBS from integer"), -i128::from(i));
+            prop_assert_eq!(-(-int), int);
+        }
+
+        #[test]
+        fn checked_neg_u128 (u in any::<BiggestInt>()) {
+            let expected = BiggestIntButSigned::checked_sub_unsigned(0, u).map(Integer::from);
+            prop_assert_eq!(Integer::from(u).checked_neg(), expected);
+        }
+
+        #[test]
+        fn bit_and_byte_length_signed (i in any::<BiggestIntButSigned>()) {
+            let int = Integer::from(i);
+            let expected_bits = if i < 0 {
+                128 - (!i).leading_zeros()
+            } else {
+                128 - i.leading_zeros()
+            } as usize;
+            prop_assert_eq!(int.bit_length(), expected_bits);
+            prop_assert!(int.bit_length() <= int.byte_length() * 8);
+
+            let (_, sered) = int.ser();
+            if sered.len() > 1 {
+                prop_assert_eq!(sered.len(), int.byte_length() + 1);
+            }
+        }
+
+        #[test]
+        fn bit_and_byte_length_unsigned (i in any::<BiggestInt>(), shift in 0_u32..128) {
+            let int = Integer::from(i);
+            prop_assert_eq!(int.bit_length(), (128 - i.leading_zeros()) as usize);
+            prop_assert_eq!(int.byte_length(), (128 - i.leading_zeros()).div_ceil(8).max(1) as usize);
+
+            let (_, sered) = int.ser();
+            if sered.len() > 1 {
+                prop_assert_eq!(sered.len(), int.byte_length() + 1);
+            }
+
+            let power = Integer::from(1_u128 << shift);
+            prop_assert_eq!(power.bit_length(), shift as usize + 1);
+            prop_assert_eq!(power.byte_length(), shift as usize / 8 + 1);
+        }
+
+        #[test]
+        fn pow_signed (i in any::<i32>(), exp in 0_u32..8) {
+            let expected = BiggestIntButSigned::from(i).checked_pow(exp);
+            prop_assert_eq!(Integer::from(i).pow(exp).map(|p| BiggestIntButSigned::try_from(p).expect("unable to get BIBS from integer")), expected);
+        }
+
+        #[test]
+        fn pow_unsigned (i in any::<u32>(), exp in 0_u32..8) {
+            let expected = BiggestInt::from(i).checked_pow(exp);
+            let got = Integer::from(i).pow(exp);
+            if let Some(got) = got {
+                prop_assert!(!got.is_negative());
+            }
+            prop_assert_eq!(got.map(|p| BiggestInt::try_from(p).expect("unable to get BI from integer")), expected);
+        }
+
+        #[test]
+        fn is_power_of_two_matches_std (i in any::<BiggestIntButSigned>(), shift in 0_u32..127) {
+            prop_assert_eq!(Integer::from(i).is_power_of_two(), i > 0 && i.unsigned_abs().is_power_of_two());
+            prop_assert!(Integer::from(1_u128 << shift).is_power_of_two());
+        }
+
         #[test]
         #[cfg(feature = "serde")]
         fn serde_works_signed (raw_i in any::<BiggestIntButSigned>()) {
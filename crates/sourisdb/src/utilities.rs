@@ -8,7 +8,15 @@
 //!
 //! ## `huffman`
 //! [`huffman::Huffman`] is a huffman coder.
+//!
+//! ## `fnv`
+//! [`fnv::Fnv1a`] is a stable [`core::hash::Hasher`] for when hashes need to be the same between runs.
+//!
+//! ## `crc32`
+//! [`crc32::Crc32`] is a checksum for detecting corrupted data.
 
 pub mod bits;
+pub mod crc32;
 pub mod cursor;
+pub mod fnv;
 pub mod huffman;
@@ -136,11 +136,87 @@ impl Bits {
         self.valid_bits == 0
     }
 
+    ///Gets the bit at `index`, or `None` if `index` isn't less than [`Bits::len`] - like [`slice::get`], but for bits.
+    ///
+    /// Only the valid bits can be got, even if the backing bytes have more bits in them. For a panicking version, use the [`Index`] implementation.
+    #[must_use]
+    pub fn get(&self, index: usize) -> Option<bool> {
+        if index >= self.valid_bits {
+            return None;
+        }
+
+        let interior_index = index % 8;
+        let backing_index = index / 8;
+        Some(self.backing[backing_index] & (1 << interior_index) > 0)
+    }
+
+    ///Iterates over the valid bits in the order they were pushed, without consuming `self` like the [`IntoIterator`] implementation does.
+    ///
+    /// ```rust
+    /// use sourisdb::utilities::bits::Bits;
+    ///
+    /// let bits = Bits::from([true, false, true]);
+    /// assert_eq!(bits.iter_bits().filter(|b| *b).count(), 2);
+    /// assert_eq!(bits.len(), 3);
+    /// ```
+    pub fn iter_bits(&self) -> impl Iterator<Item = bool> + '_ {
+        (0..self.valid_bits).map(|i| self.backing[i / 8] & (1 << (i % 8)) > 0)
+    }
+
     pub fn clear(&mut self) {
         self.valid_bits = 0;
         self.backing.clear();
     }
 
+    ///Splits the backing bytes into the bytes where all 8 bits are valid, and the final partial byte (with any unused bits set to zero) alongside how many bits of it are valid.
+    fn full_and_partial_bytes(&self) -> (&[u8], Option<(u8, usize)>) {
+        let full = self.valid_bits / 8;
+        let partial_bits = self.valid_bits % 8;
+
+        let partial = (partial_bits != 0).then(|| {
+            let mask = (1_u8 << partial_bits) - 1;
+            (self.backing[full] & mask, partial_bits)
+        });
+
+        (&self.backing[..full], partial)
+    }
+
+    ///The number of valid bits which are `1`.
+    #[must_use]
+    pub fn count_ones(&self) -> usize {
+        let (full, partial) = self.full_and_partial_bytes();
+
+        let full_ones: usize = full.iter().map(|b| b.count_ones() as usize).sum();
+        full_ones + partial.map_or(0, |(b, _)| b.count_ones() as usize)
+    }
+
+    ///The number of valid bits which are `0`.
+    #[must_use]
+    pub fn count_zeros(&self) -> usize {
+        self.valid_bits - self.count_ones()
+    }
+
+    ///The number of `0`s before the first `1`, starting from the first bit that was pushed. If there are no `1`s, this is the length.
+    #[must_use]
+    pub fn leading_zeros(&self) -> usize {
+        let (full, partial) = self.full_and_partial_bytes();
+
+        //bits are stored least-significant first, so the leading bits of the sequence are the trailing bits of each byte
+        let mut zeros = 0;
+        for b in full {
+            if *b != 0 {
+                return zeros + b.trailing_zeros() as usize;
+            }
+            zeros += 8;
+        }
+
+        match partial {
+            Some((0, bits)) => zeros + bits,
+            Some((b, _)) => zeros + b.trailing_zeros() as usize,
+            None => zeros,
+        }
+    }
+
     #[must_use]
     pub fn ser(&self) -> Vec<u8> {
         let (_, mut size) = Integer::usize(self.valid_bits).ser();
@@ -154,17 +230,11 @@ impl Bits {
     /// # Errors
     /// - [`IntegerSerError`] if we cannot find the number of valid bits
     /// - [`IntegerSerError::NotEnoughBytes`] if we do not have enough bytes
-    #[allow(
-        clippy::cast_possible_truncation,
-        clippy::cast_sign_loss,
-        clippy::cast_precision_loss
-    )]
     pub fn deser(bytes: &mut Cursor<u8>) -> Result<Self, IntegerSerError> {
         let valid_bits: usize = Integer::deser(SignedState::Unsigned, bytes)?.try_into()?;
-        let to_be_read = (valid_bits as f32 / 8.0).ceil() as usize;
-        let Some(backing) = bytes.read(to_be_read).map(<[u8]>::to_vec) else {
-            return Err(IntegerSerError::NotEnoughBytes);
-        };
+        //NB: not done with floats, as they lose precision for big lengths and then we'd read fewer bytes than we have valid bits
+        let to_be_read = valid_bits.div_ceil(8);
+        let backing = bytes.read_checked(to_be_read)?.to_vec();
 
         Ok(Self {
             backing,
@@ -267,19 +337,13 @@ impl<T: Into<usize>> Index<T> for Bits {
         static FALSE: bool = false;
 
         let index = index.into();
-        assert!(
-            index < self.valid_bits,
-            "attempted to get index {index} into bits length {}",
-            self.valid_bits
-        );
-
-        let interior_index = index % 8;
-        let backing_index = index / 8;
-
-        if self.backing[backing_index] & (1 << interior_index) > 0 {
-            &TRUE
-        } else {
-            &FALSE
+        match self.get(index) {
+            Some(true) => &TRUE,
+            Some(false) => &FALSE,
+            None => panic!(
+                "attempted to get index {index} into bits length {}",
+                self.valid_bits
+            ),
         }
     }
 }
@@ -287,7 +351,7 @@ impl<T: Into<usize>> Index<T> for Bits {
 #[cfg(test)]
 mod tests {
     use crate::utilities::bits::Bits;
-    use alloc::{format, string::ToString};
+    use alloc::{format, string::ToString, vec};
     #[allow(unused_imports)]
     use proptest::{prop_assert, prop_assert_eq, prop_assert_ne};
 
@@ -342,6 +406,22 @@ mod tests {
         assert_eq!(bits.pop(), None);
     }
 
+    #[test]
+    fn test_get() {
+        //the backing byte has 8 bits, but only 3 are valid
+        let bits = Bits {
+            backing: vec![0b1111_1101],
+            valid_bits: 3,
+        };
+
+        assert_eq!(bits.get(0), Some(true));
+        assert_eq!(bits.get(1), Some(false));
+        assert_eq!(bits.get(2), Some(true));
+        assert_eq!(bits.get(3), None);
+        assert_eq!(bits.get(usize::MAX), None);
+        assert_eq!(Bits::default().get(0), None);
+    }
+
     proptest::proptest! {
         #[test]
         fn test_partialeq (a: u32, b: u32, a_bits in 0..=32_usize, b_bits in 0..=32_usize) {
@@ -364,6 +444,35 @@ mod tests {
             }
         }
 
+        #[test]
+        fn test_bit_counts (bools: alloc::vec::Vec<bool>, garbage: u8) {
+            let mut bits = Bits::from(&bools);
+
+            //fill the unused bits of the last byte to make sure they don't get counted
+            let used_in_last = bools.len() % 8;
+            if used_in_last != 0 {
+                *bits.backing.last_mut().unwrap() |= garbage << used_in_last;
+            }
+
+            prop_assert_eq!(bits.count_ones(), bools.iter().filter(|b| **b).count());
+            prop_assert_eq!(bits.count_zeros(), bools.iter().filter(|b| !**b).count());
+            prop_assert_eq!(bits.leading_zeros(), bools.iter().take_while(|b| !**b).count());
+        }
+
+        #[test]
+        fn test_iter_bits (bools: alloc::vec::Vec<bool>, garbage: u8) {
+            let mut bits = Bits::from(&bools);
+
+            //fill the unused bits of the last byte to make sure they don't get iterated over
+            let used_in_last = bools.len() % 8;
+            if used_in_last != 0 {
+                *bits.backing.last_mut().unwrap() |= garbage << used_in_last;
+            }
+
+            prop_assert_eq!(bits.iter_bits().collect::<alloc::vec::Vec<_>>(), bools.clone());
+            prop_assert_eq!(bits.iter_bits().collect::<Bits>(), Bits::from(&bools));
+        }
+
         #[cfg(feature = "std")]
         #[test]
         fn test_hash (a: u8, b: u8, bits in 0..=8_usize) {
@@ -0,0 +1,88 @@
+//! A module containing a simple table-based implementation of [CRC-32](https://en.wikipedia.org/wiki/Cyclic_redundancy_check) (the IEEE polynomial, as used by zip and PNG).
+//!
+//! This is used to detect corruption in serialised [`Store`](crate::store::Store)s - it isn't cryptographic, so it won't protect against deliberate tampering.
+//!
+//! ```rust
+//! use sourisdb::utilities::crc32::Crc32;
+//!
+//! let mut crc = Crc32::default();
+//! crc.write(b"Hello, ");
+//! crc.write(b"World!");
+//!
+//! assert_eq!(crc.finish(), Crc32::checksum(b"Hello, World!"));
+//! ```
+
+///The reversed IEEE polynomial.
+const POLYNOMIAL: u32 = 0xEDB8_8320;
+
+///The CRC of every possible byte, worked out at compile-time so that each byte only needs one lookup.
+const TABLE: [u32; 256] = {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        #[allow(clippy::cast_possible_truncation)]
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ POLYNOMIAL
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+///A CRC-32 checksum which can be built up over several writes.
+#[derive(Debug, Copy, Clone)]
+pub struct Crc32(u32);
+
+impl Default for Crc32 {
+    fn default() -> Self {
+        Self(u32::MAX)
+    }
+}
+
+impl Crc32 {
+    ///Adds some more bytes to the checksum.
+    pub fn write(&mut self, bytes: &[u8]) {
+        for b in bytes {
+            let index = (self.0 ^ u32::from(*b)) & 0xFF;
+            self.0 = (self.0 >> 8) ^ TABLE[index as usize];
+        }
+    }
+
+    ///Gets the checksum of all the bytes written so far.
+    #[must_use]
+    pub fn finish(&self) -> u32 {
+        !self.0
+    }
+
+    ///Gets the checksum of some bytes in one go.
+    #[must_use]
+    pub fn checksum(bytes: &[u8]) -> u32 {
+        let mut crc = Self::default();
+        crc.write(bytes);
+        crc.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Crc32;
+
+    #[test]
+    fn test_known_values() {
+        for (input, expected) in [
+            (&b""[..], 0_u32),
+            (b"a", 0xe8b7_be43),
+            (b"123456789", 0xcbf4_3926),
+        ] {
+            assert_eq!(Crc32::checksum(input), expected);
+        }
+    }
+}
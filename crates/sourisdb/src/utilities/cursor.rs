@@ -17,6 +17,20 @@
 //!
 //! assert_eq!(cursor.items_remaining(), 6);
 //!```
+//!
+//! Nothing about the cursor is specific to bytes, so it can be used for any kind of element - like a stream of tokens:
+//! ```rust
+//! use sourisdb::utilities::cursor::Cursor;
+//!
+//! let tokens: [u16; 5] = [300, 1, 2, 3, 301];
+//! let mut cursor = Cursor::new(&tokens);
+//!
+//! assert_eq!(cursor.next(), Some(&300));
+//! assert_eq!(cursor.read_until(301), Some([1, 2, 3, 301].as_slice()));
+//! assert!(cursor.is_finished());
+//!```
+
+use core::fmt::{Display, Formatter};
 
 ///An immutable cursor into a borrowed slice of elements.
 pub struct Cursor<'a, T> {
@@ -109,6 +123,116 @@ impl<'a, T> Cursor<'a, T> {
         (&self.backing[start..end]).try_into().ok()
     }
 
+    ///Reads a specified number of elements in the same way as [`Cursor::read`], but returns an error rather than `None` so that it can be used with `?` when deserialising.
+    ///
+    ///```rust
+    /// use sourisdb::utilities::cursor::{Cursor, CursorError};
+    ///
+    /// let bytes = [1, 2, 3];
+    /// let mut cursor = Cursor::new(&bytes);
+    ///
+    /// assert_eq!(cursor.read_checked(2), Ok([1, 2].as_slice()));
+    /// assert_eq!(
+    ///     cursor.read_checked(2),
+    ///     Err(CursorError::NotEnoughElements { wanted: 2, remaining: 1 })
+    /// );
+    /// assert_eq!(cursor.pos(), 2);
+    /// ```
+    ///
+    /// # Errors
+    /// - [`CursorError::NotEnoughElements`] if there aren't `n` elements left. The cursor isn't moved.
+    pub fn read_checked(&mut self, n: usize) -> Result<&'a [T], CursorError> {
+        let remaining = self.items_remaining();
+        self.read(n).ok_or(CursorError::NotEnoughElements {
+            wanted: n,
+            remaining,
+        })
+    }
+
+    ///Reads a generic number of elements in the same way as [`Cursor::read_exact`], but returns an error rather than `None` so that it can be used with `?` when deserialising.
+    ///
+    ///```rust
+    /// use sourisdb::utilities::cursor::Cursor;
+    /// use sourisdb::values::ValueSerError;
+    ///
+    /// fn read_f64(cursor: &mut Cursor<u8>) -> Result<f64, ValueSerError> {
+    ///     Ok(f64::from_le_bytes(*cursor.read_exact_checked()?))
+    /// }
+    ///
+    /// let bytes = 1.5_f64.to_le_bytes();
+    /// assert_eq!(read_f64(&mut Cursor::new(&bytes)).unwrap(), 1.5);
+    /// assert!(matches!(
+    ///     read_f64(&mut Cursor::new(&&bytes[..4])),
+    ///     Err(ValueSerError::NotEnoughBytes)
+    /// ));
+    /// ```
+    ///
+    /// # Errors
+    /// - [`CursorError::NotEnoughElements`] if there aren't `N` elements left. The cursor isn't moved.
+    pub fn read_exact_checked<const N: usize>(&mut self) -> Result<&'a [T; N], CursorError> {
+        let remaining = self.items_remaining();
+        self.read_exact().ok_or(CursorError::NotEnoughElements {
+            wanted: N,
+            remaining,
+        })
+    }
+
+    ///Reads elements up to and including the first element equal to `delimiter`. The cursor is also moved to the next position after the delimiter.
+    ///
+    /// If the delimiter can't be found, `None` is returned and the cursor is not moved.
+    ///```rust
+    /// use sourisdb::utilities::cursor::Cursor;
+    ///
+    /// let bytes = b"key=value";
+    /// let mut cursor = Cursor::new(bytes);
+    ///
+    /// assert_eq!(cursor.read_until(b'='), Some(b"key=".as_slice()));
+    /// assert_eq!(cursor.read_until(b'='), None);
+    /// assert_eq!(cursor.peek_remaining(), b"value");
+    /// ```
+    pub fn read_until(&mut self, delimiter: T) -> Option<&'a [T]>
+    where
+        T: PartialEq + Copy,
+    {
+        let remaining = &self.backing[self.pos.min(self.backing.len())..];
+        let delimiter_index = remaining.iter().position(|t| *t == delimiter)?;
+
+        self.read(delimiter_index + 1)
+    }
+
+    ///Splits off the next `n` elements into their own cursor, which can't read past those `n` elements. This is useful for handing a nested deserialiser only the bytes it is allowed to read.
+    ///
+    /// Returns the elements after the new cursor, and the new cursor. This cursor is not moved.
+    ///
+    /// If there aren't `n` elements remaining, `None` is returned.
+    ///```rust
+    /// use sourisdb::utilities::cursor::Cursor;
+    ///
+    /// let bytes = [1, 2, 3, 4, 5];
+    /// let cursor = Cursor::new(&bytes);
+    ///
+    /// let (rest, mut sub_cursor) = cursor.split_at(2).unwrap();
+    /// assert_eq!(rest, &[3, 4, 5]);
+    /// assert_eq!(sub_cursor.read(2), Some([1, 2].as_slice()));
+    /// assert_eq!(sub_cursor.read(1), None);
+    /// ```
+    #[must_use]
+    pub fn split_at(&self, n: usize) -> Option<(&'a [T], Cursor<'a, T>)> {
+        let remaining = &self.backing[self.pos.min(self.backing.len())..];
+        if n > remaining.len() {
+            return None;
+        }
+        let (sub, rest) = remaining.split_at(n);
+
+        Some((
+            rest,
+            Cursor {
+                backing: sub,
+                pos: 0,
+            },
+        ))
+    }
+
     ///Peeks at a certain number of bytes - follows the exact same behaviour as [`Cursor::read`] but without changing the position of the pointer.
     #[must_use]
     pub fn peek(&self, n: usize) -> Option<&'a [T]> {
@@ -137,7 +261,7 @@ impl<'a, T> Cursor<'a, T> {
     ///Reads all remaining bytes, and finishes the cursor.
     ///
     /// If none are left, it returns an empty slice.
-    pub fn read_remaining(&mut self) -> &[T] {
+    pub fn read_remaining(&mut self) -> &'a [T] {
         if self.pos >= self.backing.len() {
             &[]
         } else {
@@ -151,8 +275,8 @@ impl<'a, T> Cursor<'a, T> {
     ///
     /// If none are left, it returns an empty slice.
     #[must_use]
-    pub fn peek_remaining(&self) -> &[T] {
-        self.as_ref()
+    pub fn peek_remaining(&self) -> &'a [T] {
+        &self.backing[self.pos.min(self.backing.len())..]
     }
 
     #[must_use]
@@ -176,6 +300,45 @@ impl<'a, T> Cursor<'a, T> {
         self.pos = self.backing.len().min(new);
     }
 
+    ///Returns the current zero-indexed position of the pointer - the same as [`Cursor::pos`].
+    ///
+    /// Together with [`Cursor::set_position`], this can be used to checkpoint a position and then rewind back to it.
+    ///
+    ///```rust
+    /// use sourisdb::utilities::cursor::Cursor;
+    ///
+    /// let bytes = [1, 2, 3, 4];
+    /// let mut cursor = Cursor::new(&bytes);
+    /// cursor.move_forwards(1);
+    ///
+    /// let checkpoint = cursor.position();
+    /// assert_eq!(cursor.read(2), Some([2, 3].as_slice()));
+    ///
+    /// cursor.set_position(checkpoint).unwrap();
+    /// assert_eq!(cursor.peek_remaining(), &[2, 3, 4]);
+    /// assert!(cursor.set_position(5).is_err());
+    /// ```
+    #[must_use]
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+
+    ///Sets the position of the pointer, returning an error and leaving the pointer where it was if the new position would be out of bounds.
+    ///
+    /// Unlike [`Cursor::set_pos`], this doesn't clamp the position.
+    ///
+    /// # Errors
+    /// If `pos` is greater than the length of the list - the furthest it can go is the length of the list, which is the same as being finished.
+    #[allow(clippy::result_unit_err)]
+    pub fn set_position(&mut self, pos: usize) -> Result<(), ()> {
+        if pos > self.backing.len() {
+            return Err(());
+        }
+
+        self.pos = pos;
+        Ok(())
+    }
+
     #[must_use]
     ///Returns whether the cursor is finished.
     pub fn is_finished(&self) -> bool {
@@ -183,6 +346,34 @@ impl<'a, T> Cursor<'a, T> {
     }
 }
 
+///An error from reading with a [`Cursor`].
+///
+/// Errors for deserialising (like [`crate::values::ValueSerError`]) can be created from this, which turns it into their `NotEnoughBytes` variant.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CursorError {
+    ///There weren't enough elements left to read.
+    NotEnoughElements {
+        ///The number of elements which were asked for.
+        wanted: usize,
+        ///The number of elements which were left.
+        remaining: usize,
+    },
+}
+
+impl Display for CursorError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::NotEnoughElements { wanted, remaining } => write!(
+                f,
+                "Tried to read {wanted} elements, but only {remaining} were left"
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for CursorError {}
+
 impl<'a, T> AsRef<[T]> for Cursor<'a, T> {
     fn as_ref(&self) -> &'a [T] {
         if self.pos >= self.backing.len() {
@@ -281,4 +472,92 @@ mod tests {
         assert_eq!(cursor.read(1), None);
         assert_eq!(cursor.pos(), 10);
     }
+
+    #[test]
+    fn test_set_position() {
+        let data = [0, 1, 2, 3];
+        let mut cursor = Cursor::new(&data);
+
+        assert_eq!(cursor.set_position(4), Ok(()));
+        assert!(cursor.is_finished());
+
+        assert_eq!(cursor.set_position(5), Err(()));
+        assert_eq!(cursor.position(), 4);
+
+        assert_eq!(cursor.set_position(1), Ok(()));
+        assert_eq!(cursor.position(), 1);
+        assert_eq!(cursor.read(2), Some([1, 2].as_slice()));
+    }
+
+    #[test]
+    fn test_read_until() {
+        let data = [1, 0, 2, 3, 0];
+        let mut cursor = Cursor::new(&data);
+
+        assert_eq!(cursor.read_until(0), Some([1, 0].as_slice()));
+        assert_eq!(cursor.read_until(0), Some([2, 3, 0].as_slice()));
+        assert_eq!(cursor.read_until(0), None);
+        assert!(cursor.is_finished());
+
+        let mut cursor = Cursor::new(&data);
+        assert_eq!(cursor.read_until(4), None);
+        assert_eq!(cursor.pos(), 0);
+
+        let empty: [i32; 0] = [];
+        let mut cursor = Cursor::new(&empty);
+        assert_eq!(cursor.read_until(0), None);
+    }
+
+    #[test]
+    fn test_non_byte_elements() {
+        #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+        enum Token {
+            Open,
+            Word(u16),
+            Close,
+        }
+
+        let tokens = [Token::Open, Token::Word(1), Token::Word(2), Token::Close];
+        let mut cursor = Cursor::new(&tokens);
+
+        assert_eq!(cursor.read_exact::<1>(), Some(&[Token::Open]));
+        let words = cursor.read_until(Token::Close).unwrap();
+        assert_eq!(words, &[Token::Word(1), Token::Word(2), Token::Close]);
+
+        assert!(cursor.move_backwards(2));
+        assert_eq!(cursor.next().copied(), Some(Token::Word(2)));
+
+        //the remaining elements can outlive the cursor, as they're borrowed from the backing slice
+        let remaining = {
+            let mut sub_cursor = Cursor::new(&tokens);
+            sub_cursor.move_forwards(3);
+            sub_cursor.read_remaining()
+        };
+        assert_eq!(remaining, &[Token::Close]);
+    }
+
+    #[test]
+    fn test_split_at() {
+        let data = [0, 1, 2, 3, 4];
+        let mut cursor = Cursor::new(&data);
+        cursor.move_forwards(1);
+
+        let (rest, mut sub) = cursor.split_at(3).unwrap();
+        assert_eq!(rest, &[4]);
+        assert_eq!(sub.items_remaining(), 3);
+        assert_eq!(sub.read(4), None);
+        assert_eq!(sub.read(3), Some([1, 2, 3].as_slice()));
+        assert!(sub.is_finished());
+        assert_eq!(cursor.pos(), 1);
+
+        let (rest, sub) = cursor.split_at(0).unwrap();
+        assert_eq!(rest, &[1, 2, 3, 4]);
+        assert!(sub.is_finished());
+
+        let (rest, sub) = cursor.split_at(4).unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(sub.items_remaining(), 4);
+
+        assert!(cursor.split_at(5).is_none());
+    }
 }
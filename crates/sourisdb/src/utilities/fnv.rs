@@ -0,0 +1,63 @@
+//! A module containing a simple implementation of the [FNV-1a](https://en.wikipedia.org/wiki/Fowler%E2%80%93Noll%E2%80%93Vo_hash_function) hashing algorithm.
+//!
+//! Unlike the hashers used by [`hashbrown::HashMap`], [`Fnv1a`] is not randomly seeded, so hashing the same data will always produce the same hash - this makes it suitable for things like content-addressing.
+//!
+//! ```rust
+//! use core::hash::{Hash, Hasher};
+//! use sourisdb::utilities::fnv::Fnv1a;
+//!
+//! let mut first = Fnv1a::default();
+//! "Hello, World!".hash(&mut first);
+//!
+//! let mut second = Fnv1a::default();
+//! "Hello, World!".hash(&mut second);
+//!
+//! assert_eq!(first.finish(), second.finish());
+//! ```
+
+use core::hash::Hasher;
+
+const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const PRIME: u64 = 0x0100_0000_01b3;
+
+///A 64-bit FNV-1a [`Hasher`] which always starts from the same state.
+#[derive(Debug, Copy, Clone)]
+pub struct Fnv1a(u64);
+
+impl Default for Fnv1a {
+    fn default() -> Self {
+        Self(OFFSET_BASIS)
+    }
+}
+
+impl Hasher for Fnv1a {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for b in bytes {
+            self.0 ^= u64::from(*b);
+            self.0 = self.0.wrapping_mul(PRIME);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Fnv1a;
+    use core::hash::Hasher;
+
+    #[test]
+    fn test_known_values() {
+        for (input, expected) in [
+            (&b""[..], 0xcbf2_9ce4_8422_2325_u64),
+            (b"a", 0xaf63_dc4c_8601_ec8c),
+            (b"foobar", 0x8594_4171_f739_67e8),
+        ] {
+            let mut hasher = Fnv1a::default();
+            hasher.write(input);
+            assert_eq!(hasher.finish(), expected);
+        }
+    }
+}
@@ -77,7 +77,10 @@ use hashbrown::HashMap;
 use crate::{
     display_bytes_as_hex_array,
     types::integer::{Integer, IntegerSerError, SignedState},
-    utilities::{bits::Bits, cursor::Cursor},
+    utilities::{
+        bits::Bits,
+        cursor::{Cursor, CursorError},
+    },
 };
 
 ///A struct to hold the conversions between a `T` and the huffman bits which represent it.
@@ -85,6 +88,8 @@ use crate::{
 pub struct Huffman<T: Hash + Eq + Clone> {
     to_bits: HashMap<T, Bits>,
     root: Node<T>,
+    ///How often each `T` appeared when building the tree. Trees read back using `deser` only have the shape of the tree, so this is `None` for them.
+    frequencies: Option<HashMap<T, usize>>,
 }
 //I tested tree traversal both ways, and in the end it made encoding like 2000% slower (300 nano -> 7 milli), but encoding like 90% faster (1 milli -> 100 nano), so that's the cause for the split approach
 
@@ -138,6 +143,63 @@ impl<T: PartialEq> Node<T> {
         }
     }
 }
+
+impl<T> Node<T> {
+    ///The deepest that a deserialised tree can go. Building a tree this deep would need more than [`usize::MAX`] symbols (each extra level needs roughly the next Fibonacci number's worth), so only malicious trees hit this - and they would otherwise take huge amounts of memory for their codes, and of stack to use or drop.
+    const MAX_DEPTH: usize = 128;
+
+    ///Deserialises a tree which was serialised in pre-order with a `0` byte for each branch, using a stack rather than recursion so that nested branches can't overflow the stack.
+    ///
+    /// `read_leaf` is given the first byte of each leaf (which is never `0`) and reads the rest of it, and `min_leaf_len` is the fewest bytes a leaf can take up. Every branch we are still reading needs at least one more leaf, so if there aren't enough bytes left for them all, we stop early with [`HuffmanSerError::TreeTooLarge`] rather than building up an enormous tree.
+    fn deser_with(
+        cursor: &mut Cursor<u8>,
+        min_leaf_len: usize,
+        mut read_leaf: impl FnMut(u8, &mut Cursor<u8>) -> Result<T, HuffmanSerError>,
+    ) -> Result<Self, HuffmanSerError> {
+        //branches we're part-way through, along with their left child once it's been read
+        let mut branches: Vec<Option<Self>> = vec![];
+        //the number of subtrees we still need to read, starting with the root
+        let mut pending: usize = 1;
+
+        loop {
+            let Some(start) = cursor.next().copied() else {
+                return Err(HuffmanSerError::NotEnoughBytes);
+            };
+
+            if start == 0 {
+                pending += 1;
+                if branches.len() >= Self::MAX_DEPTH
+                    || pending.saturating_mul(min_leaf_len) > cursor.items_remaining()
+                {
+                    return Err(HuffmanSerError::TreeTooLarge);
+                }
+
+                branches.push(None);
+                continue;
+            }
+
+            let mut node = Node::Leaf(read_leaf(start, cursor)?);
+            pending -= 1;
+
+            //finish off every branch that now has both children
+            loop {
+                match branches.pop() {
+                    None => return Ok(node),
+                    Some(None) => {
+                        branches.push(Some(node));
+                        break;
+                    }
+                    Some(Some(left)) => {
+                        node = Node::Branch {
+                            left: Box::new(left),
+                            right: Box::new(node),
+                        };
+                    }
+                }
+            }
+        }
+    }
+}
 impl Node<u8> {
     pub fn ser(&self) -> Vec<u8> {
         match self {
@@ -152,25 +214,16 @@ impl Node<u8> {
     }
 
     pub fn deser(cursor: &mut Cursor<u8>) -> Result<Self, HuffmanSerError> {
-        let Some(start) = cursor.next().copied() else {
-            return Err(HuffmanSerError::NotEnoughBytes);
-        };
-
-        match start {
-            0 => {
-                let left = Box::new(Self::deser(cursor)?);
-                let right = Box::new(Self::deser(cursor)?);
-                Ok(Node::Branch { left, right })
-            }
-            1 => {
-                let Some(byte) = cursor.next().copied() else {
-                    return Err(HuffmanSerError::NotEnoughBytes);
-                };
-
-                Ok(Node::Leaf(byte))
+        //leaves are a one and then the byte
+        Self::deser_with(cursor, 2, |start, cursor| {
+            if start != 1 {
+                return Err(HuffmanSerError::InvalidDiscriminant(start));
             }
-            _ => Err(HuffmanSerError::InvalidDiscriminant(start)),
-        }
+            cursor
+                .next()
+                .copied()
+                .ok_or(HuffmanSerError::NotEnoughBytes)
+        })
     }
 }
 impl Node<char> {
@@ -187,23 +240,16 @@ impl Node<char> {
     }
 
     fn deser(cursor: &mut Cursor<u8>) -> Result<Self, HuffmanSerError> {
-        let Some(ch) = cursor.next() else {
-            return Err(HuffmanSerError::NotEnoughBytes);
-        };
-
-        if *ch == 0 {
-            let left = Box::new(Self::deser(cursor)?);
-            let right = Box::new(Self::deser(cursor)?);
-            Ok(Node::Branch { left, right })
-        } else {
+        //leaves are just an integer, which takes at least one byte
+        Self::deser_with(cursor, 1, |_, cursor| {
             cursor.move_backwards(1);
             let ch: u32 = Integer::deser(SignedState::Unsigned, cursor)?.try_into()?;
-            let Ok(ch) = char::try_from(ch - 1) else {
-                return Err(HuffmanSerError::InvalidCharacter(ch - 1));
+            //an over-long encoding of zero would otherwise underflow here
+            let Some(ch) = ch.checked_sub(1) else {
+                return Err(HuffmanSerError::InvalidCharacter(ch));
             };
-
-            Ok(Node::Leaf(ch))
-        }
+            char::try_from(ch).map_err(|_| HuffmanSerError::InvalidCharacter(ch))
+        })
     }
 }
 
@@ -224,6 +270,12 @@ pub enum HuffmanSerError {
     UnableToCode,
     ///In order to create a node tree, the provided list must not be empty
     UnableToCreateNodeTree,
+    ///We decoded some bits, but the last few bits didn't finish a symbol - this holds how many bits were left over.
+    LeftoverBits(usize),
+    ///The input asked for a longer run of one repeated byte than is allowed - this holds how long the run was.
+    RunTooLong(usize),
+    ///We were deserialising a tree which had more branches than the remaining bytes could possibly hold leaves for, or which was nested far more deeply than any real tree could be.
+    TreeTooLarge,
 }
 
 impl From<IntegerSerError> for HuffmanSerError {
@@ -231,6 +283,11 @@ impl From<IntegerSerError> for HuffmanSerError {
         Self::Integer(value)
     }
 }
+impl From<CursorError> for HuffmanSerError {
+    fn from(_: CursorError) -> Self {
+        Self::NotEnoughBytes
+    }
+}
 
 impl Display for HuffmanSerError {
     fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
@@ -257,6 +314,18 @@ impl Display for HuffmanSerError {
             HuffmanSerError::UnableToCreateNodeTree => {
                 write!(f, "Unable to create node tree with empty input")
             }
+            HuffmanSerError::LeftoverBits(n) => {
+                write!(f, "Found {n} leftover bits that didn't make a full symbol")
+            }
+            HuffmanSerError::RunTooLong(n) => {
+                write!(f, "Run of {n} repeated bytes is too long")
+            }
+            HuffmanSerError::TreeTooLarge => {
+                write!(
+                    f,
+                    "Huffman tree is too large for the bytes it was read from"
+                )
+            }
         }
     }
 }
@@ -309,12 +378,36 @@ impl<T: Eq + Hash + Clone> Huffman<T> {
     ///Convert an iterator into a node tree where less common elements are more left.
     ///
     /// Can return `None` if there aren't any elements.
+    #[cfg(test)]
     fn data_to_node_tree(data: impl Iterator<Item = T>) -> Result<Node<T>, HuffmanSerError> {
+        Self::data_with_frequencies_to_node_tree(&Self::frequency_table(data))
+    }
+
+    ///Counts how many times each `T` appears in an iterator.
+    fn frequency_table(data: impl Iterator<Item = T>) -> HashMap<T, usize> {
         let mut frequency_table = HashMap::new();
         for ch in data {
             *frequency_table.entry(ch).or_default() += 1_usize;
         }
-        Self::data_with_frequencies_to_node_tree(frequency_table)
+        frequency_table
+    }
+
+    ///Builds a tree from the weights, and keeps the weights around for [`Huffman::merge`].
+    ///
+    /// Can return `None` if no elements are provided.
+    pub(crate) fn from_frequencies(
+        frequencies: HashMap<T, usize>,
+    ) -> Result<Self, HuffmanSerError> {
+        let root = Self::data_with_frequencies_to_node_tree(&frequencies)?;
+
+        let mut to_bits = HashMap::new();
+        Self::add_node_to_table(&root, &mut to_bits, Bits::default());
+
+        Ok(Self {
+            to_bits,
+            root,
+            frequencies: Some(frequencies),
+        })
     }
 
     ///Convert an iterator with weights already calculated to a node tree.
@@ -323,14 +416,14 @@ impl<T: Eq + Hash + Clone> Huffman<T> {
     ///
     /// Can return `None` if no elements are provided.
     fn data_with_frequencies_to_node_tree(
-        data: HashMap<T, usize>,
+        data: &HashMap<T, usize>,
     ) -> Result<Node<T>, HuffmanSerError> {
         if data.is_empty() {
             return Err(HuffmanSerError::UnableToCreateNodeTree);
         }
         let mut frequency_table: HashMap<Node<T>, usize> = HashMap::new();
         for (ch, freq) in data {
-            *frequency_table.entry(Node::Leaf(ch)).or_default() += freq;
+            *frequency_table.entry(Node::Leaf(ch.clone())).or_default() += freq;
         }
         //redo HM to ensure that uniqueness is preserved etc
 
@@ -382,13 +475,32 @@ impl<T: Eq + Hash + Clone> Huffman<T> {
     ///Can return `None` if the iterator provided is empty.
     #[must_use]
     pub fn new(data: impl Iterator<Item = T>) -> Result<Self, HuffmanSerError> {
-        let mut to_bits = HashMap::new();
-
-        let root = Self::data_to_node_tree(data)?;
+        Self::from_frequencies(Self::frequency_table(data))
+    }
 
-        Self::add_node_to_table(&root, &mut to_bits, Bits::default());
+    ///Combines two huffman trees into one which is the same as if it had been made from both trees' data together.
+    ///
+    /// Returns `None` if either tree came from `deser`, as only the shape of the tree gets serialised and not the frequencies needed to rebuild it.
+    ///
+    /// ```rust
+    /// use sourisdb::utilities::huffman::Huffman;
+    ///
+    /// let a = Huffman::new_str("aaaab").unwrap();
+    /// let b = Huffman::new_str("bbbbc").unwrap();
+    /// let merged = Huffman::merge(a, b).unwrap();
+    ///
+    /// let expected = Huffman::new_str("aaaabbbbbc").unwrap();
+    /// assert_eq!(merged.encoded_len("abc".chars()), expected.encoded_len("abc".chars()));
+    /// ```
+    #[must_use]
+    pub fn merge(a: Self, b: Self) -> Option<Self> {
+        let mut frequencies = a.frequencies?;
+        for (t, freq) in b.frequencies? {
+            let entry = frequencies.entry(t).or_default();
+            *entry = entry.saturating_add(freq);
+        }
 
-        Ok(Self { to_bits, root })
+        Self::from_frequencies(frequencies).ok()
     }
 
     ///Creates a huffman tree using the given data, and immediately encodes the data using it.
@@ -405,41 +517,86 @@ impl<T: Eq + Hash + Clone> Huffman<T> {
     }
 
     ///Encode a series of `T`s into a [`Bits`]. Will return `None` if any elements found in the iterator were not included in the original [`Huffman::new`] incantation.
+    ///
+    /// Trees made with only one distinct `T` can't be used for encoding, as every `T` would be encoded with zero bits and so couldn't be decoded.
     pub fn encode(&self, from: impl Iterator<Item = T>) -> Result<Bits, HuffmanSerError> {
+        if matches!(self.root, Node::Leaf(_)) {
+            return Err(HuffmanSerError::UnableToCode);
+        }
+
         from.into_iter()
             .map(|x| self.to_bits.get(&x).cloned())
             .collect::<Option<_>>()
             .ok_or(HuffmanSerError::UnableToCode)
     }
 
-    ///Decode a series of `T`s from a [`Bits`]. Will return `None` if a sequence in the `bits` cannot be found in the conversion tables calculated during the original [`Huffman::new`] incantation.
-    #[allow(clippy::missing_panics_doc)]
+    ///Calculates the number of bits that encoding a series of `T`s would take up, without actually building the [`Bits`]. Will return `None` if any elements found in the iterator were not included in the original [`Huffman::new`] incantation.
+    pub fn encoded_len(&self, from: impl Iterator<Item = T>) -> Option<usize> {
+        from.map(|x| self.to_bits.get(&x).map(Bits::len)).sum()
+    }
+
+    ///Calculates the number of bits that encoding all of the data this tree was made from would take up. Returns `None` if the tree came from `deser`, as the frequencies aren't serialised.
+    pub(crate) fn encoded_len_of_frequencies(&self) -> Option<usize> {
+        self.frequencies
+            .as_ref()?
+            .iter()
+            .map(|(t, count)| self.to_bits.get(t).map(|bits| bits.len() * count))
+            .sum()
+    }
+
+    ///Calculates the average number of bits used to represent each `T` in this tree.
+    ///
+    /// NB: this is the unweighted mean across all the different `T`s, not weighted by how often they appeared in the original data.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn average_code_length(&self) -> f64 {
+        let total: usize = self.to_bits.values().map(Bits::len).sum();
+        total as f64 / self.to_bits.len() as f64
+    }
+
+    ///Decode a series of `T`s from a [`Bits`].
+    ///
+    /// # Errors
+    /// - [`HuffmanSerError::LeftoverBits`] if the bits end part-way through a symbol.
+    /// - [`HuffmanSerError::UnableToCode`] if this tree only has one distinct `T` and there are bits to decode - see [`Huffman::encode`].
     pub fn decode(&self, bits: Bits) -> Result<Vec<T>, HuffmanSerError> {
+        if matches!(self.root, Node::Leaf(_)) {
+            return if bits.is_empty() {
+                Ok(Vec::new())
+            } else {
+                Err(HuffmanSerError::UnableToCode)
+            };
+        }
+
         let mut result = Vec::new();
         let mut current_node = &self.root;
+        let mut bits_in_symbol = 0;
 
         for next_direction in bits {
-            let new_node;
-            match current_node {
-                Node::Leaf(_) => unreachable!(),
+            current_node = match current_node {
                 Node::Branch { left, right } => {
-                    let found = if next_direction { left } else { right };
-                    if let Some(t) = found.leaf_contents().cloned() {
-                        new_node = &self.root;
-                        result.push(t);
+                    if next_direction {
+                        left
                     } else {
-                        new_node = found;
+                        right
                     }
                 }
-            }
+                //we always go back to the root after a leaf, and the root is a branch
+                Node::Leaf(_) => return Err(HuffmanSerError::UnableToCode),
+            };
+            bits_in_symbol += 1;
 
-            current_node = new_node;
+            if let Some(t) = current_node.leaf_contents() {
+                result.push(t.clone());
+                current_node = &self.root;
+                bits_in_symbol = 0;
+            }
         }
 
-        if current_node != &self.root {
-            Err(HuffmanSerError::UnableToCode)
-        } else {
+        if bits_in_symbol == 0 {
             Ok(result)
+        } else {
+            Err(HuffmanSerError::LeftoverBits(bits_in_symbol))
         }
     }
 }
@@ -456,13 +613,136 @@ impl Huffman<u8> {
     /// - [`HuffmanSerError::NotEnoughBytes`] if there aren't enough bytes.
     /// - [`IntegerSerError`] if there is an error deserialising one of the [`Integer`]s.
     /// - [`HuffmanSerError::InvalidDiscriminant`] if we find an invalid discriminant in the serialised node tree.
+    /// - [`HuffmanSerError::TreeTooLarge`] if the tree couldn't possibly fit in the remaining bytes, or is nested too deeply.
     pub fn deser(bytes: &mut Cursor<u8>) -> Result<Self, HuffmanSerError> {
         let root = Node::<u8>::deser(bytes)?;
 
         let mut to_bits = HashMap::new();
         Self::add_node_to_table(&root, &mut to_bits, Bits::default());
 
-        Ok(Self { to_bits, root })
+        Ok(Self {
+            to_bits,
+            root,
+            frequencies: None,
+        })
+    }
+}
+
+///Where to go next when decoding using the flattened tree in [`Huffman::decode_bytes`].
+#[derive(Copy, Clone)]
+enum FlatNode {
+    Branch(usize),
+    Leaf(u8),
+}
+
+impl Huffman<u8> {
+    ///Creates a new huffman tree for some bytes.
+    ///
+    /// This gives the same tree as [`Huffman::new`], but counts the bytes using an array rather than a [`HashMap`] so is faster for large inputs.
+    ///
+    /// # Errors
+    /// - [`HuffmanSerError::UnableToCreateNodeTree`] if `data` is empty.
+    pub fn new_bytes(data: &[u8]) -> Result<Self, HuffmanSerError> {
+        let mut frequencies = [0_usize; 256];
+        for b in data {
+            frequencies[usize::from(*b)] += 1;
+        }
+
+        let frequencies = (0..=u8::MAX)
+            .zip(frequencies)
+            .filter(|(_, freq)| *freq > 0)
+            .collect();
+        Self::from_frequencies(frequencies)
+    }
+
+    ///Encodes some bytes - this is the same as [`Huffman::encode`], but looks up each byte's bits in an array rather than a [`HashMap`] and avoids cloning them, which makes it much faster.
+    ///
+    /// ```rust
+    /// use sourisdb::utilities::huffman::Huffman;
+    ///
+    /// let data = b"mississippi";
+    /// let huffman = Huffman::new_bytes(data).unwrap();
+    ///
+    /// let bits = huffman.encode_bytes(data).unwrap();
+    /// assert_eq!(huffman.decode_bytes(&bits).unwrap(), data);
+    /// ```
+    ///
+    /// # Errors
+    /// - [`HuffmanSerError::UnableToCode`] if any bytes weren't in the data used to create this tree, or it only has one distinct byte - see [`Huffman::encode`].
+    pub fn encode_bytes(&self, data: &[u8]) -> Result<Bits, HuffmanSerError> {
+        if matches!(self.root, Node::Leaf(_)) {
+            return Err(HuffmanSerError::UnableToCode);
+        }
+
+        let mut table: [Option<&Bits>; 256] = [None; 256];
+        for (b, bits) in &self.to_bits {
+            table[usize::from(*b)] = Some(bits);
+        }
+
+        let mut output = Bits::default();
+        for b in data {
+            let bits = table[usize::from(*b)].ok_or(HuffmanSerError::UnableToCode)?;
+            for i in 0..bits.len() {
+                output.push(bits[i]);
+            }
+        }
+
+        Ok(output)
+    }
+
+    ///Decodes some bytes - this is the same as [`Huffman::decode`], but walks a flattened copy of the tree stored in a [`Vec`] rather than following [`Box`]es, and doesn't need to take ownership of the [`Bits`].
+    ///
+    /// # Errors
+    /// See [`Huffman::decode`].
+    pub fn decode_bytes(&self, bits: &Bits) -> Result<Vec<u8>, HuffmanSerError> {
+        fn flatten(node: &Node<u8>, table: &mut Vec<[FlatNode; 2]>) -> FlatNode {
+            match node {
+                Node::Leaf(b) => FlatNode::Leaf(*b),
+                Node::Branch { left, right } => {
+                    let index = table.len();
+                    table.push([FlatNode::Branch(0); 2]);
+
+                    let right = flatten(right, table);
+                    let left = flatten(left, table);
+                    table[index] = [right, left]; //indexed by the bit, and `true` means left
+
+                    FlatNode::Branch(index)
+                }
+            }
+        }
+
+        if matches!(self.root, Node::Leaf(_)) {
+            return if bits.is_empty() {
+                Ok(Vec::new())
+            } else {
+                Err(HuffmanSerError::UnableToCode)
+            };
+        }
+
+        let mut table = Vec::new();
+        flatten(&self.root, &mut table);
+
+        let mut result = Vec::new();
+        let mut current = 0;
+        let mut bits_in_symbol = 0;
+
+        for i in 0..bits.len() {
+            bits_in_symbol += 1;
+            match table[current][usize::from(bits[i])] {
+                FlatNode::Branch(next) => current = next,
+                FlatNode::Leaf(b) => {
+                    result.push(b);
+                    current = 0;
+                    bits_in_symbol = 0;
+                }
+            }
+        }
+
+        if bits_in_symbol == 0 {
+            Ok(result)
+        } else {
+            Err(HuffmanSerError::LeftoverBits(bits_in_symbol))
+        }
     }
 }
 
@@ -583,16 +863,7 @@ impl Huffman<char> {
         .map(|(ch, f)| (char::from(ch), f))
         .collect();
 
-        let root = Self::data_with_frequencies_to_node_tree(freqs_map).unwrap();
-
-        let mut chars_to_bits = HashMap::new();
-
-        Self::add_node_to_table(&root, &mut chars_to_bits, Bits::default());
-
-        Self {
-            root,
-            to_bits: chars_to_bits,
-        }
+        Self::from_frequencies(freqs_map).unwrap()
     }
 
     ///Encode a string into a [`Bits`]. Will return `None` if it encounters a new character.
@@ -621,13 +892,18 @@ impl Huffman<char> {
     /// - [`HuffmanSerError::NotEnoughBytes`] if there aren't enough bytes.
     /// - [`IntegerSerError`] if there is an error deserialising one of the [`Integer`]s.
     /// - [`HuffmanSerError::InvalidCharacter`] if we find an invalid character.
+    /// - [`HuffmanSerError::TreeTooLarge`] if the tree couldn't possibly fit in the remaining bytes, or is nested too deeply.
     pub fn deser(bytes: &mut Cursor<u8>) -> Result<Self, HuffmanSerError> {
         let root = Node::<char>::deser(bytes)?;
 
         let mut to_bits = HashMap::new();
         Self::add_node_to_table(&root, &mut to_bits, Bits::default());
 
-        Ok(Self { to_bits, root })
+        Ok(Self {
+            to_bits,
+            root,
+            frequencies: None,
+        })
     }
 }
 
@@ -640,7 +916,7 @@ mod tests {
     use crate::utilities::{
         bits::Bits,
         cursor::Cursor,
-        huffman::{Huffman, Node},
+        huffman::{Huffman, HuffmanSerError, Node},
     };
 
     #[test]
@@ -670,12 +946,110 @@ mod tests {
         assert_eq!(data, decoded);
     }
 
+    #[test]
+    fn test_code_length_stats() {
+        let data = "abcdeabcdabcabaaaaaa";
+        let huffman = Huffman::new_str(data).unwrap();
+
+        let encoded = huffman.encode_string(data).unwrap();
+        assert_eq!(huffman.encoded_len(data.chars()), Some(encoded.len()));
+        assert_eq!(huffman.encoded_len("abcz".chars()), None);
+
+        let expected_average = ["a", "b", "c", "d", "e"]
+            .into_iter()
+            .map(|ch| huffman.encode_string(ch).unwrap().len())
+            .sum::<usize>() as f64
+            / 5.0;
+        assert!((huffman.average_code_length() - expected_average).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_decode_truncated() {
+        let data = "abcdeabcdabcabaaaaaa";
+        let huffman = Huffman::new_str(data).unwrap();
+
+        let mut encoded = huffman.encode_string("e").unwrap();
+        assert!(encoded.len() > 1);
+        let _ = encoded.pop();
+
+        assert!(matches!(
+            huffman.decode_string(encoded),
+            Err(HuffmanSerError::LeftoverBits(_))
+        ));
+    }
+
+    #[test]
+    fn test_single_symbol_tree() {
+        let huffman = Huffman::new_str("aaaa").unwrap();
+
+        assert!(matches!(
+            huffman.encode_string("aa"),
+            Err(HuffmanSerError::UnableToCode)
+        ));
+        assert!(huffman.decode_string(Bits::default()).unwrap().is_empty());
+        assert!(matches!(
+            huffman.decode_string(Bits::from([true, false])),
+            Err(HuffmanSerError::UnableToCode)
+        ));
+    }
+
+    #[test]
+    fn test_merge() {
+        let a = Huffman::new_bytes(b"aaaaaaaabbbc").unwrap();
+        let b = Huffman::new(b"ccccccccccd".iter().copied()).unwrap();
+        let merged = Huffman::merge(a, b).unwrap();
+
+        let expected = Huffman::new_bytes(b"aaaaaaaabbbcccccccccccd").unwrap();
+        assert_eq!(merged.frequencies, expected.frequencies);
+        assert_eq!(merged.root, expected.root);
+
+        let from_deser = Huffman::<u8>::deser(&mut Cursor::new(&expected.ser())).unwrap();
+        assert!(Huffman::merge(merged, from_deser).is_none());
+    }
+
+    #[test]
+    fn test_deser_rejects_huge_trees() {
+        //every zero is another branch, so this would need far more leaves than there are bytes
+        let zeroes = alloc::vec![0_u8; 1_000_000];
+        assert!(matches!(
+            Huffman::<char>::deser(&mut Cursor::new(&zeroes)),
+            Err(HuffmanSerError::TreeTooLarge)
+        ));
+        assert!(matches!(
+            Huffman::<u8>::deser(&mut Cursor::new(&zeroes)),
+            Err(HuffmanSerError::TreeTooLarge)
+        ));
+
+        //a tree which only ever branches to the right, with plenty of bytes for its leaves
+        let lopsided = |depth: usize| {
+            let mut bytes = alloc::vec![];
+            for _ in 0..depth {
+                bytes.extend([0, b'a' + 1]);
+            }
+            bytes.push(b'b' + 1);
+            bytes
+        };
+        assert!(
+            Huffman::<char>::deser(&mut Cursor::new(&lopsided(Node::<char>::MAX_DEPTH))).is_ok()
+        );
+        assert!(matches!(
+            Huffman::<char>::deser(&mut Cursor::new(&lopsided(100_000))),
+            Err(HuffmanSerError::TreeTooLarge)
+        ));
+    }
+
     proptest! {
         #[test]
         fn doesnt_crash_string (s in "\\PC*") {
             let _ = Huffman::new_str(s);
         }
 
+        #[test]
+        fn decoding_garbage_doesnt_crash (bits: alloc::vec::Vec<bool>) {
+            let huffman = Huffman::new_str("abcdeabcdabcabaaaaaa").unwrap();
+            let _ = huffman.decode(Bits::from(&bits));
+        }
+
         #[test]
         fn works_on_arbritrary_ascii_strings (s in "[a-z]+[A-Z0-9]+") {
             let huffman = Huffman::new_str(&s).expect("unable to get huffman");
@@ -713,5 +1087,22 @@ mod tests {
             let decoded = deserialised_huffman.decode_string(deserialised_bits).expect("unable to decode");
             prop_assert_eq!(s, decoded);
         }
+
+        #[test]
+        fn bytes_fast_path_matches_generic (bytes in proptest::collection::vec(proptest::num::u8::ANY, 2..512), garbage: alloc::vec::Vec<bool>) {
+            let Ok(huffman) = Huffman::new_bytes(&bytes) else {
+                unreachable!("bytes can't be empty");
+            };
+            let generic = huffman.encode(bytes.iter().copied());
+            let fast = huffman.encode_bytes(&bytes);
+            prop_assert_eq!(generic.as_ref().ok(), fast.as_ref().ok());
+
+            if let Ok(encoded) = fast {
+                prop_assert_eq!(huffman.decode_bytes(&encoded).ok(), Some(bytes));
+            }
+
+            let garbage = Bits::from(&garbage);
+            prop_assert_eq!(huffman.decode_bytes(&garbage).ok(), huffman.decode(garbage).ok());
+        }
     }
 }
@@ -1,6 +1,6 @@
 //! This module contains the [`Value`] which is the value in the key-value [`crate::store::Store`].
 //!
-//! There are 16 variants, each of which stores one kind of item which I consider important. Variants can be constructed directly, by the `Value::xx` methods, or [`From`] implementations. There are also [`From`] implementations for all Rust integer types.
+//! Each variant stores one kind of item which I consider important, and some (like `uuid` and `regex`) are only available with their features enabled. Variants can be constructed directly, by the `Value::xx` methods, or [`From`] implementations. There are also [`From`] implementations for all Rust integer types.
 //!
 //! Values can be serialised into bytes using the infallible [`Value::ser`] method, and brought back from bytes using [`Value::deser`] (which uses a [`Cursor`]).
 //!
@@ -22,6 +22,8 @@
 //! assert_eq!(example_value_array, deserialised); //order is preserved when serialising arrays
 //! ```
 use alloc::{
+    boxed::Box,
+    collections::BTreeMap,
     string::{FromUtf8Error, String, ToString},
     vec,
     vec::Vec,
@@ -35,23 +37,36 @@ use core::{
 };
 
 use cfg_if::cfg_if;
-use chrono::{Datelike, NaiveDate, NaiveDateTime, NaiveTime, Timelike};
+use chrono::{
+    DateTime, Datelike, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta, TimeZone, Timelike,
+};
 use chrono_tz::Tz;
 use hashbrown::HashMap;
 use serde_json::{Error as SJError, Map as SJMap, Number, Value as SJValue};
 
 use crate::{
     types::{
-        binary::{BinaryCompression, BinaryData, BinarySerError},
+        binary::{
+            dictionary::{lz_with_known_dict, un_lz_with_known_dict, Dict},
+            huffman::MAX_RUN_LEN,
+            BinaryCompression, BinaryData, BinarySerError, STORE_DICT_COMPRESSION,
+        },
         imaginary::Imaginary,
         integer::{Integer, IntegerSerError, SignedState},
     },
     utilities::{
         bits::Bits,
-        cursor::Cursor,
+        cursor::{Cursor, CursorError},
         huffman::{Huffman, HuffmanSerError},
     },
 };
+#[cfg(feature = "regex")]
+use regex::Regex;
+
+#[cfg(feature = "cbor")]
+mod cbor;
+#[cfg(feature = "uuid")]
+use uuid::Uuid;
 
 ///The `Value` type used in [`crate::store::Store`]
 #[derive(Clone, Debug)]
@@ -72,8 +87,12 @@ pub enum Value {
     Imaginary(Imaginary),
     ///A point in time represented by [`NaiveDateTime`].
     ///
-    /// NB: Does not record a timezone - if you need times at specific locations, consider also encoding a [`Value::Timezone`].
+    /// NB: Does not record a timezone - if you need times at specific locations, use a [`Value::ZonedTimestamp`].
     Timestamp(NaiveDateTime),
+    ///A point in time in a specific [`Tz`], represented by a [`DateTime`].
+    ///
+    /// When serialised, this is the time in UTC (in the same format as a [`Value::Timestamp`]) followed by the name of the timezone, so the local time is never ambiguous.
+    ZonedTimestamp(DateTime<Tz>),
     ///A JSON value represented by [`serde_json::Value`].
     JSON(SJValue),
     ///A null value.
@@ -88,14 +107,123 @@ pub enum Value {
     Array(Vec<Value>),
     ///A map of [`String`]s to [`Value`]s.
     ///
-    /// NB: The order is not preserved through serialisation.
+    /// NB: The order is not preserved through serialisation - keys are always serialised in lexicographic order so that equal maps produce identical bytes. Use [`Value::OrderedMap`] if the order matters.
     Map(HashMap<String, Value>),
+    ///A list of [`String`] keys and [`Value`]s, which keeps its entries in insertion order.
+    ///
+    /// NB: Unlike [`Value::Map`], the order is preserved through serialisation and is taken into account for equality and hashing. Keys aren't checked for uniqueness - lookups use the first matching key.
+    OrderedMap(Vec<(String, Value)>),
     ///A timezone represented by [`chrono_tz::Tz`].
     Timezone(Tz),
     ///An IPV4 Address
     Ipv4Addr(Ipv4Addr),
     ///An IPV6 Address
     Ipv6Addr(Ipv6Addr),
+    ///A [`Uuid`], serialised as 16 little-endian bytes.
+    #[cfg(feature = "uuid")]
+    Uuid(Uuid),
+    ///A [`Regex`], serialised as its pattern and checked to be valid when deserialised.
+    #[cfg(feature = "regex")]
+    Regex(Regex),
+    ///A span of time, which can be negative.
+    ///
+    /// There are conversions to and from both [`core::time::Duration`] and [`chrono::TimeDelta`].
+    Duration {
+        ///The whole number of seconds, rounded towards negative infinity.
+        seconds: Integer,
+        ///The number of nanoseconds past `seconds` - this is always less than one billion.
+        nanoseconds: u32,
+    },
+    ///A value with a name attached, for sum types like Rust enums. This is clearer than using a [`Value::Map`] with a single key, as the tag can't be mistaken for a normal key.
+    ///
+    /// NB: When serialised, this takes up one more byte than a single-key [`Value::Map`], as it is an extended type and so needs a length for versions of the crate without tagged values to be able to skip over it (see [`ValueTy`]).
+    ///
+    /// This maps onto `serde`'s externally-tagged representation, so it becomes `{"<tag>": <value>}` in [`Value::convert_to_json`].
+    Tagged {
+        ///The name of the variant.
+        tag: String,
+        ///The contents of the variant.
+        value: Box<Value>,
+    },
+    ///A value of a type that this version of the crate doesn't know about, which was kept when deserialising with [`Value::deser_skip_unknown`].
+    ///
+    /// When serialised, this is written back out verbatim so that it isn't lost.
+    Unknown(UnknownValue),
+}
+
+///The contents of a [`Value::Unknown`].
+///
+/// The fields are private so that the type is always an extended type that this version of the crate doesn't know about - otherwise the bytes would be read back as a different kind of value. Use [`UnknownValue::new`] to make one by hand.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct UnknownValue {
+    ///The discriminant of the type - this is always an extended type, so between 16 and 30 inclusive.
+    ty: u8,
+    ///The serialised contents of the value, excluding the type byte and the length.
+    bytes: Vec<u8>,
+}
+
+impl UnknownValue {
+    ///Creates a new unknown value from the discriminant of its type and its serialised contents (excluding the type byte and the length).
+    ///
+    /// Returns [`None`] if `ty` isn't the discriminant of an extended type (between 16 and 30 inclusive - see [`ValueTy`]), or if it is one that this version of the crate knows about.
+    ///
+    /// ```rust
+    /// use sourisdb::values::{UnknownValue, ValueTy};
+    ///
+    /// assert!(UnknownValue::new(30, vec![1, 2, 3]).is_some());
+    /// assert!(UnknownValue::new(3, vec![1, 2, 3]).is_none()); //a boolean
+    /// assert!(UnknownValue::new(u8::from(ValueTy::Tagged), vec![]).is_none());
+    /// assert!(UnknownValue::new(31, vec![]).is_none()); //too big to fit in the type byte
+    /// ```
+    #[must_use]
+    pub fn new(ty: u8, bytes: Vec<u8>) -> Option<Self> {
+        let type_byte = ValueTy::type_byte_for_discriminant(ty);
+        if !ValueTy::is_extended_type_byte(type_byte)
+            || ValueTy::discriminant_from_type_byte(type_byte) != ty
+            || ValueTy::try_from(ty).is_ok()
+        {
+            return None;
+        }
+
+        Some(Self { ty, bytes })
+    }
+
+    ///Gets the discriminant of the type.
+    #[must_use]
+    pub fn ty(&self) -> u8 {
+        self.ty
+    }
+
+    ///Gets the serialised contents of the value, excluding the type byte and the length.
+    #[must_use]
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+///Settings for deserialising a [`Value`], which apply to everything nested inside it too.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct DeserOptions<'a> {
+    ///The tree for any huffman-encoded strings.
+    pub(crate) huffman: Option<&'a Huffman<char>>,
+    ///Whether to keep unknown extended types as [`Value::Unknown`]s - see [`Value::deser_skip_unknown`].
+    pub(crate) skip_unknown: bool,
+    ///The longest run of one repeated byte that a huffman-compressed [`Value::Binary`] can ask for - see [`BinaryData::deser_with_max_run_len`].
+    pub(crate) max_run_len: usize,
+    ///The dictionary of the store being deserialised, for any [`Value::Binary`]s compressed using it - see [`Value::ser_with_dict`].
+    pub(crate) dict: Option<&'a Dict>,
+}
+
+impl<'a> DeserOptions<'a> {
+    ///The options used by [`Value::deser`].
+    pub(crate) fn new(huffman: Option<&'a Huffman<char>>) -> Self {
+        Self {
+            huffman,
+            skip_unknown: false,
+            max_run_len: MAX_RUN_LEN,
+            dict: None,
+        }
+    }
 }
 
 macro_rules! as_ty {
@@ -166,7 +294,411 @@ macro_rules! as_ty {
     };
 }
 
-as_ty!(Character char -> char, String str -> String, Boolean bool -> bool, Integer int -> Integer, Imaginary imaginary -> Imaginary, Timestamp timestamp -> NaiveDateTime, JSON json -> SJValue, Null null -> (), DoubleFloat double_float -> f64, SingleFloat single_float -> f32, Array array -> Vec<Value>, Map map -> HashMap<String, Value>, Timezone tz -> Tz, Ipv4Addr ipv4 -> Ipv4Addr, Ipv6Addr ipv6 -> Ipv6Addr, Binary binary -> BinaryData);
+as_ty!(Character char -> char, String str -> String, Boolean bool -> bool, Integer int -> Integer, Imaginary imaginary -> Imaginary, Timestamp timestamp -> NaiveDateTime, ZonedTimestamp zoned_timestamp -> DateTime<Tz>, JSON json -> SJValue, Null null -> (), DoubleFloat double_float -> f64, SingleFloat single_float -> f32, Array array -> Vec<Value>, Map map -> HashMap<String, Value>, OrderedMap ordered_map -> Vec<(String, Value)>, Timezone tz -> Tz, Ipv4Addr ipv4 -> Ipv4Addr, Ipv6Addr ipv6 -> Ipv6Addr, Binary binary -> BinaryData);
+#[cfg(feature = "uuid")]
+as_ty!(Uuid uuid -> Uuid);
+#[cfg(feature = "regex")]
+as_ty!(Regex regex -> Regex);
+
+impl Value {
+    ///Gets a mutable reference to the contents of a [`Value::Array`], or an error if this isn't an array.
+    fn expect_mut_array(&mut self) -> Result<&mut Vec<Value>, ValueSerError> {
+        let found = self.as_ty();
+        self.as_mut_array()
+            .ok_or(ValueSerError::UnexpectedValueType {
+                found,
+                expected: ValueTy::Array,
+            })
+    }
+
+    ///Pushes a value onto the end of a [`Value::Array`].
+    ///
+    /// ```rust
+    /// use sourisdb::values::Value;
+    ///
+    /// let mut array = Value::Array(vec![]);
+    /// array.array_push(Value::Boolean(true)).unwrap();
+    /// array.array_extend([Value::Null(()), Value::Null(())]).unwrap();
+    ///
+    /// assert_eq!(array.array_len().unwrap(), 3);
+    /// assert!(Value::Null(()).array_push(Value::Boolean(false)).is_err());
+    /// ```
+    ///
+    /// # Errors
+    /// - [`ValueSerError::UnexpectedValueType`] if this isn't a [`Value::Array`].
+    pub fn array_push(&mut self, v: Value) -> Result<(), ValueSerError> {
+        self.expect_mut_array()?.push(v);
+        Ok(())
+    }
+
+    ///Adds all of the values onto the end of a [`Value::Array`].
+    ///
+    /// # Errors
+    /// - [`ValueSerError::UnexpectedValueType`] if this isn't a [`Value::Array`].
+    pub fn array_extend(
+        &mut self,
+        values: impl IntoIterator<Item = Value>,
+    ) -> Result<(), ValueSerError> {
+        self.expect_mut_array()?.extend(values);
+        Ok(())
+    }
+
+    ///Gets the number of elements in a [`Value::Array`].
+    ///
+    /// # Errors
+    /// - [`ValueSerError::UnexpectedValueType`] if this isn't a [`Value::Array`].
+    pub fn array_len(&self) -> Result<usize, ValueSerError> {
+        self.as_array()
+            .map(Vec::len)
+            .ok_or(ValueSerError::UnexpectedValueType {
+                found: self.as_ty(),
+                expected: ValueTy::Array,
+            })
+    }
+
+    ///Converts every element of a [`Value::Array`] into a `T`, stopping at the first one which can't be converted.
+    ///
+    /// ```rust
+    /// use sourisdb::values::{Value, ValueSerError};
+    ///
+    /// let array = Value::Array(vec![Value::from(1_u8), Value::from(-2_i32)]);
+    /// assert_eq!(array.array_of::<i64>().unwrap(), vec![1, -2]);
+    ///
+    /// let mixed = Value::Array(vec![Value::from(1_u8), Value::Boolean(true)]);
+    /// assert!(matches!(
+    ///     mixed.array_of::<i64>(),
+    ///     Err(ValueSerError::InvalidArrayElement { index: 1, .. })
+    /// ));
+    /// ```
+    ///
+    /// # Errors
+    /// - [`ValueSerError::UnexpectedValueType`] if this isn't a [`Value::Array`].
+    /// - [`ValueSerError::InvalidArrayElement`] with the index of the first element that couldn't be converted.
+    pub fn array_of<T: TryFrom<Value, Error = ValueSerError>>(
+        self,
+    ) -> Result<Vec<T>, ValueSerError> {
+        let found = self.as_ty();
+        let array = self.to_array().ok_or(ValueSerError::UnexpectedValueType {
+            found,
+            expected: ValueTy::Array,
+        })?;
+
+        array
+            .into_iter()
+            .enumerate()
+            .map(|(index, v)| {
+                T::try_from(v).map_err(|cause| ValueSerError::InvalidArrayElement {
+                    index,
+                    cause: Box::new(cause),
+                })
+            })
+            .collect()
+    }
+
+    ///Gets the type that every element of a [`Value::Array`] has, if they all have the same one.
+    ///
+    /// Returns [`None`] if this isn't an array, if the array is empty, or if the elements have different types.
+    ///
+    /// ```rust
+    /// use sourisdb::values::{Value, ValueTy};
+    ///
+    /// let array = Value::Array(vec![Value::from(1_u8), Value::from(2_u8)]);
+    /// assert_eq!(array.is_homogeneous(), Some(ValueTy::Integer));
+    ///
+    /// let mixed = Value::Array(vec![Value::from(1_u8), Value::Null(())]);
+    /// assert_eq!(mixed.is_homogeneous(), None);
+    /// assert_eq!(Value::Array(vec![]).is_homogeneous(), None);
+    /// ```
+    #[must_use]
+    pub fn is_homogeneous(&self) -> Option<ValueTy> {
+        let mut tys = self.as_array()?.iter().map(Value::as_ty);
+        let first = tys.next()?;
+        tys.all(|ty| ty == first).then_some(first)
+    }
+
+    ///Gets the length of a container-like value - the number of elements in a [`Value::Array`], entries in a [`Value::Map`] or [`Value::OrderedMap`], or bytes in a [`Value::String`] or [`Value::Binary`].
+    ///
+    /// Scalar types (and [`Value::JSON`], which may or may not be a container) return [`None`].
+    ///
+    /// ```rust
+    /// use sourisdb::values::Value;
+    ///
+    /// assert_eq!(Value::Array(vec![Value::Null(()); 3]).len(), Some(3));
+    /// assert_eq!(Value::String("souris".into()).len(), Some(6));
+    /// assert_eq!(Value::Boolean(true).len(), None);
+    /// ```
+    #[must_use]
+    pub fn len(&self) -> Option<usize> {
+        Some(match self {
+            Value::Array(a) => a.len(),
+            Value::Map(m) => m.len(),
+            Value::OrderedMap(m) => m.len(),
+            Value::String(s) => s.len(),
+            Value::Binary(b) => b.0.len(),
+            _ => return None,
+        })
+    }
+
+    ///Whether a container-like value is empty - see [`Value::len`] for which types count as containers.
+    ///
+    /// Scalar types return [`None`] rather than `false`, so that they can't be mistaken for non-empty containers.
+    ///
+    /// ```rust
+    /// use sourisdb::values::Value;
+    ///
+    /// assert_eq!(Value::Array(vec![]).is_empty(), Some(true));
+    /// assert_eq!(Value::String("souris".into()).is_empty(), Some(false));
+    /// assert_eq!(Value::from(0_u8).is_empty(), None);
+    /// ```
+    #[must_use]
+    pub fn is_empty(&self) -> Option<bool> {
+        self.len().map(|len| len == 0)
+    }
+
+    ///Iterates over the entries of a [`Value::Map`] or [`Value::OrderedMap`] whose keys are integers within `lo..=hi`, in numeric order. This is useful for things like time-series keyed by timestamps.
+    ///
+    /// Keys are parsed as [`Integer`]s, and entries whose keys aren't integers are skipped. Entries of a [`Value::OrderedMap`] whose keys are the same number (like `7` and `07`) stay in the map's order. Returns [`None`] if this isn't a map, or if either bound isn't an integer.
+    ///
+    /// ```rust
+    /// use sourisdb::values::Value;
+    ///
+    /// let map = Value::Map(
+    ///     [("10", 'a'), ("9", 'b'), ("100", 'c'), ("not a number", 'd')]
+    ///         .into_iter()
+    ///         .map(|(k, v)| (k.to_string(), Value::Character(v)))
+    ///         .collect(),
+    /// );
+    ///
+    /// let keys: Vec<_> = map.map_range("5", "50").unwrap().map(|(k, _)| k.as_str()).collect();
+    /// assert_eq!(keys, ["9", "10"]);
+    /// assert!(map.map_range("a", "b").is_none());
+    ///
+    /// let ordered = Value::OrderedMap(
+    ///     ["07", "8", "7", "007"].into_iter().map(|k| (k.to_string(), Value::Null(()))).collect(),
+    /// );
+    /// let keys: Vec<_> = ordered.map_range("0", "10").unwrap().map(|(k, _)| k.as_str()).collect();
+    /// assert_eq!(keys, ["07", "7", "007", "8"]);
+    /// ```
+    #[must_use]
+    pub fn map_range<'a>(
+        &'a self,
+        lo: &str,
+        hi: &str,
+    ) -> Option<impl Iterator<Item = (&'a String, &'a Value)>> {
+        let entries: Vec<_> = match self {
+            Value::Map(m) => m.iter().collect(),
+            Value::OrderedMap(m) => m.iter().map(|(k, v)| (k, v)).collect(),
+            _ => return None,
+        };
+        int_key_range(entries, lo, hi)
+    }
+
+    ///Gets a sorted view of a [`Value::Map`], which is useful for processing the entries in the same order every time without changing how the map is stored.
+    ///
+    /// Returns [`None`] for anything other than a [`Value::Map`] - [`Value::OrderedMap`]s already have their own order.
+    ///
+    /// ```rust
+    /// use sourisdb::values::Value;
+    ///
+    /// let map = Value::Map([("b", 2_u8), ("a", 1), ("c", 3)].into_iter().map(|(k, v)| (k.to_string(), Value::from(v))).collect());
+    ///
+    /// let keys: Vec<_> = map.map_as_btree().unwrap().into_keys().collect();
+    /// assert_eq!(keys, ["a", "b", "c"]);
+    /// assert!(Value::Array(vec![]).map_as_btree().is_none());
+    /// ```
+    #[must_use]
+    pub fn map_as_btree(&self) -> Option<BTreeMap<String, &Value>> {
+        let Value::Map(m) = self else {
+            return None;
+        };
+        Some(m.iter().map(|(k, v)| (k.clone(), v)).collect())
+    }
+
+    ///Creates a [`Value::Map`] from a [`BTreeMap`].
+    ///
+    /// The order of the entries isn't kept, as a [`Value::Map`] is backed by a [`HashMap`] - use [`Value::OrderedMap`] if the order matters.
+    #[must_use]
+    pub fn from_btree(map: BTreeMap<String, Value>) -> Value {
+        Value::Map(map.into_iter().collect())
+    }
+
+    ///Tries to convert this value into a value of type `ty`, which is useful for normalising loosely-typed data.
+    ///
+    /// If the value is already of type `ty`, it is returned unchanged. Otherwise, these are the only coercions which are attempted:
+    ///
+    /// | From | To | Notes |
+    /// |------|----|-------|
+    /// | [`Value::String`] | [`Value::Integer`], [`Value::DoubleFloat`], [`Value::SingleFloat`] | Parsed using [`FromStr`]. |
+    /// | [`Value::String`] | [`Value::Boolean`] | Only `"true"` and `"false"`. |
+    /// | [`Value::String`] | [`Value::Character`] | Only strings which are exactly one character long. |
+    /// | [`Value::String`] | [`Value::Timestamp`], [`Value::Timezone`], [`Value::Ipv4Addr`], [`Value::Ipv6Addr`], `Value::Uuid`, `Value::Regex` | Parsed using [`FromStr`]. |
+    /// | [`Value::Character`], [`Value::Integer`], [`Value::DoubleFloat`], [`Value::SingleFloat`], [`Value::Boolean`], [`Value::Timestamp`], [`Value::ZonedTimestamp`], [`Value::Timezone`], [`Value::Ipv4Addr`], [`Value::Ipv6Addr`], `Value::Uuid`, `Value::Regex` | [`Value::String`] | Uses [`Display`]. |
+    /// | [`Value::Integer`] | [`Value::DoubleFloat`], [`Value::SingleFloat`] | Only if the integer can be represented exactly. |
+    /// | [`Value::Integer`] | [`Value::Boolean`] | Only `0` and `1`. |
+    /// | [`Value::Boolean`] | [`Value::Integer`] | `false` is `0` and `true` is `1`. |
+    /// | [`Value::DoubleFloat`], [`Value::SingleFloat`] | [`Value::Integer`] | Only floats without a fractional part - see [`Integer`]'s `TryFrom<f64>` implementation. |
+    /// | [`Value::SingleFloat`] | [`Value::DoubleFloat`] | Always exact. |
+    /// | [`Value::DoubleFloat`] | [`Value::SingleFloat`] | Only if the float can be represented exactly. |
+    /// | [`Value::ZonedTimestamp`] | [`Value::Timestamp`] | Keeps the local time, dropping the timezone. |
+    /// | [`Value::Map`] | [`Value::OrderedMap`] | Keys are sorted lexicographically. |
+    /// | [`Value::OrderedMap`] | [`Value::Map`] | Only if no key appears more than once. |
+    ///
+    /// ```rust
+    /// use sourisdb::values::{Value, ValueTy};
+    ///
+    /// assert_eq!(Value::String("42".into()).coerce_to(ValueTy::Integer).unwrap(), Value::from(42_u8));
+    /// assert_eq!(Value::String("true".into()).coerce_to(ValueTy::Boolean).unwrap(), Value::Boolean(true));
+    /// assert_eq!(Value::from(3_u8).coerce_to(ValueTy::DoubleFloat).unwrap(), Value::DoubleFloat(3.0));
+    /// assert!(Value::DoubleFloat(1.5).coerce_to(ValueTy::Integer).is_err());
+    /// assert!(Value::Null(()).coerce_to(ValueTy::String).is_err());
+    /// ```
+    ///
+    /// # Errors
+    /// - [`ValueSerError::UnexpectedValueType`] if there is no coercion between the types, or the value couldn't be coerced (eg. a string which isn't a valid integer).
+    pub fn coerce_to(self, ty: ValueTy) -> Result<Value, ValueSerError> {
+        let found = self.as_ty();
+        if found == ty {
+            return Ok(self);
+        }
+
+        let coerced = match (self, ty) {
+            (Value::String(s), ty) => Self::coerce_str(&s, ty),
+            (Value::Character(c), ValueTy::String) => Some(Value::String(c.to_string())),
+            (Value::Integer(i), ValueTy::String) => Some(Value::String(i.to_string())),
+            (Value::DoubleFloat(f), ValueTy::String) => Some(Value::String(f.to_string())),
+            (Value::SingleFloat(f), ValueTy::String) => Some(Value::String(f.to_string())),
+            (Value::Boolean(b), ValueTy::String) => Some(Value::String(b.to_string())),
+            (Value::Timestamp(ts), ValueTy::String) => Some(Value::String(ts.to_string())),
+            (v @ Value::ZonedTimestamp(_), ValueTy::String) => Some(Value::String(v.to_string())),
+            (Value::ZonedTimestamp(dt), ValueTy::Timestamp) => {
+                Some(Value::Timestamp(dt.naive_local()))
+            }
+            (Value::Timezone(tz), ValueTy::String) => Some(Value::String(tz.to_string())),
+            (Value::Ipv4Addr(a), ValueTy::String) => Some(Value::String(a.to_string())),
+            (Value::Ipv6Addr(a), ValueTy::String) => Some(Value::String(a.to_string())),
+            #[cfg(feature = "uuid")]
+            (Value::Uuid(u), ValueTy::String) => Some(Value::String(u.to_string())),
+            #[cfg(feature = "regex")]
+            (Value::Regex(r), ValueTy::String) => Some(Value::String(r.as_str().to_string())),
+            (Value::Integer(i), ValueTy::DoubleFloat) => {
+                let f = f64::from(i);
+                (Integer::try_from(f).ok() == Some(i)).then_some(Value::DoubleFloat(f))
+            }
+            (Value::Integer(i), ValueTy::SingleFloat) => {
+                let f = f32::from(i);
+                (Integer::try_from(f).ok() == Some(i)).then_some(Value::SingleFloat(f))
+            }
+            (Value::Integer(i), ValueTy::Boolean) => match i.signum() {
+                0 => Some(Value::Boolean(false)),
+                1 if i == Integer::u8(1) => Some(Value::Boolean(true)),
+                _ => None,
+            },
+            (Value::Boolean(b), ValueTy::Integer) => Some(Value::from(u8::from(b))),
+            (v @ (Value::DoubleFloat(_) | Value::SingleFloat(_)), ValueTy::Integer) => {
+                v.to_integer().map(Value::Integer)
+            }
+            (Value::SingleFloat(f), ValueTy::DoubleFloat) => Some(Value::DoubleFloat(f64::from(f))),
+            (Value::DoubleFloat(f), ValueTy::SingleFloat) => {
+                #[allow(clippy::cast_possible_truncation)]
+                let single = f as f32;
+                #[allow(clippy::float_cmp)] //we only want exact conversions
+                (f64::from(single) == f).then_some(Value::SingleFloat(single))
+            }
+            (Value::Map(m), ValueTy::OrderedMap) => {
+                let mut entries: Vec<_> = m.into_iter().collect();
+                entries.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+                Some(Value::OrderedMap(entries))
+            }
+            (Value::OrderedMap(m), ValueTy::Map) => {
+                let len = m.len();
+                let map: HashMap<_, _> = m.into_iter().collect();
+                (map.len() == len).then_some(Value::Map(map))
+            }
+            _ => None,
+        };
+
+        coerced.ok_or(ValueSerError::UnexpectedValueType {
+            found,
+            expected: ty,
+        })
+    }
+
+    ///Gets this value as an [`f64`], if it is a [`Value::Integer`], [`Value::SingleFloat`] or [`Value::DoubleFloat`].
+    ///
+    /// Floats are always converted exactly, but integers are rounded to the nearest [`f64`] if they are too big to fit exactly (ie. their magnitude is above 2<sup>53</sup>). Use [`Value::coerce_to`] to only convert integers exactly.
+    ///
+    /// ```rust
+    /// use sourisdb::values::Value;
+    ///
+    /// assert_eq!(Value::from(3_u8).to_f64(), Some(3.0));
+    /// assert_eq!(Value::SingleFloat(0.5).to_f64(), Some(0.5));
+    /// assert_eq!(Value::from(u64::MAX).to_f64(), Some(u64::MAX as f64));
+    /// assert_eq!(Value::String("3".into()).to_f64(), None);
+    /// ```
+    #[must_use]
+    pub fn to_f64(&self) -> Option<f64> {
+        match self {
+            Value::Integer(i) => Some(f64::from(*i)),
+            Value::SingleFloat(f) => Some(f64::from(*f)),
+            Value::DoubleFloat(f) => Some(*f),
+            _ => None,
+        }
+    }
+
+    ///Gets this value as an [`Integer`], if it is a [`Value::Integer`], or a [`Value::SingleFloat`] or [`Value::DoubleFloat`] which can be converted without losing anything.
+    ///
+    /// Floats with any fractional part, `NaN`, infinities, and floats outside of the range of [`Integer`] all give `None` rather than being rounded or clamped. `-0.0` gives `0`.
+    ///
+    /// ```rust
+    /// use sourisdb::values::Value;
+    ///
+    /// assert_eq!(Value::DoubleFloat(-4.0).to_integer(), Some((-4_i8).into()));
+    /// assert_eq!(Value::SingleFloat(1e10).to_integer(), Some(10_000_000_000_u64.into()));
+    /// assert_eq!(Value::DoubleFloat(-1.5).to_integer(), None);
+    /// assert_eq!(Value::DoubleFloat(f64::NAN).to_integer(), None);
+    /// assert_eq!(Value::DoubleFloat(1e40).to_integer(), None);
+    /// ```
+    #[must_use]
+    pub fn to_integer(&self) -> Option<Integer> {
+        match self {
+            Value::Integer(i) => Some(*i),
+            Value::SingleFloat(f) => Integer::try_from(*f).ok(),
+            Value::DoubleFloat(f) => Integer::try_from(*f).ok(),
+            _ => None,
+        }
+    }
+
+    ///Parses a string for [`Value::coerce_to`].
+    fn coerce_str(s: &str, ty: ValueTy) -> Option<Value> {
+        Some(match ty {
+            ValueTy::Integer => Value::Integer(Integer::from_str(s).ok()?),
+            ValueTy::DoubleFloat => Value::DoubleFloat(f64::from_str(s).ok()?),
+            ValueTy::SingleFloat => Value::SingleFloat(f32::from_str(s).ok()?),
+            ValueTy::Boolean => match s {
+                "true" => Value::Boolean(true),
+                "false" => Value::Boolean(false),
+                _ => return None,
+            },
+            ValueTy::Character => {
+                let mut chars = s.chars();
+                let c = chars.next()?;
+                if chars.next().is_some() {
+                    return None;
+                }
+                Value::Character(c)
+            }
+            ValueTy::Timestamp => Value::Timestamp(NaiveDateTime::from_str(s).ok()?),
+            ValueTy::Timezone => Value::Timezone(Tz::from_str(s).ok()?),
+            ValueTy::Ipv4Addr => Value::Ipv4Addr(Ipv4Addr::from_str(s).ok()?),
+            ValueTy::Ipv6Addr => Value::Ipv6Addr(Ipv6Addr::from_str(s).ok()?),
+            #[cfg(feature = "uuid")]
+            ValueTy::Uuid => Value::Uuid(Uuid::from_str(s).ok()?),
+            #[cfg(feature = "regex")]
+            ValueTy::Regex => Value::Regex(Regex::new(s).ok()?),
+            _ => return None,
+        })
+    }
+}
 
 macro_rules! from_integer {
     ($($t:ty),+) => {
@@ -190,6 +722,77 @@ macro_rules! from_integer {
 
 from_integer!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
 
+impl Value {
+    ///Whether this value is a [`Value::Duration`].
+    #[must_use]
+    pub fn is_duration(&self) -> bool {
+        matches!(self, Value::Duration { .. })
+    }
+}
+
+impl From<core::time::Duration> for Value {
+    fn from(value: core::time::Duration) -> Self {
+        Self::Duration {
+            seconds: Integer::from(i128::from(value.as_secs())),
+            nanoseconds: value.subsec_nanos(),
+        }
+    }
+}
+
+impl From<TimeDelta> for Value {
+    fn from(value: TimeDelta) -> Self {
+        let (mut seconds, mut nanoseconds) = (value.num_seconds(), value.subsec_nanos());
+        if nanoseconds < 0 {
+            //`TimeDelta` rounds seconds towards zero, but we always keep the nanoseconds positive
+            seconds -= 1;
+            nanoseconds += 1_000_000_000;
+        }
+
+        Self::Duration {
+            seconds: Integer::from(i128::from(seconds)),
+            nanoseconds: nanoseconds.unsigned_abs(),
+        }
+    }
+}
+
+impl TryFrom<Value> for core::time::Duration {
+    type Error = ValueSerError;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        let Value::Duration {
+            seconds,
+            nanoseconds,
+        } = value
+        else {
+            return Err(ValueSerError::UnexpectedValueType {
+                found: value.as_ty(),
+                expected: ValueTy::Duration,
+            });
+        };
+
+        Ok(Self::new(u64::try_from(seconds)?, nanoseconds))
+    }
+}
+
+impl TryFrom<Value> for TimeDelta {
+    type Error = ValueSerError;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        let Value::Duration {
+            seconds,
+            nanoseconds,
+        } = value
+        else {
+            return Err(ValueSerError::UnexpectedValueType {
+                found: value.as_ty(),
+                expected: ValueTy::Duration,
+            });
+        };
+
+        TimeDelta::new(i64::try_from(seconds)?, nanoseconds).ok_or(ValueSerError::InvalidDateOrTime)
+    }
+}
+
 impl PartialEq for Value {
     fn eq(&self, other: &Self) -> bool {
         if self.as_ty() != other.as_ty() {
@@ -204,15 +807,42 @@ impl PartialEq for Value {
             (Self::Integer(i), Self::Integer(i2)) => i.eq(i2),
             (Self::Imaginary(i), Self::Imaginary(i2)) => i.eq(i2),
             (Self::Timestamp(t), Self::Timestamp(t2)) => t.eq(t2),
+            //`DateTime`'s equality only compares the instant, so check the timezones too
+            (Self::ZonedTimestamp(t), Self::ZonedTimestamp(t2)) => {
+                t.eq(t2) && t.timezone().eq(&t2.timezone())
+            }
             (Self::JSON(j), Self::JSON(j2)) => j.eq(j2),
             (Self::Null(()), Self::Null(())) => true,
             (Self::DoubleFloat(f), Self::DoubleFloat(f2)) => f.eq(f2),
             (Self::Array(a), Self::Array(a2)) => a.eq(a2),
             (Self::Map(m), Self::Map(m2)) => m.eq(m2),
+            (Self::OrderedMap(m), Self::OrderedMap(m2)) => m.eq(m2),
             (Self::Timezone(t), Self::Timezone(t2)) => t.eq(t2),
             (Self::Ipv4Addr(t), Self::Ipv4Addr(t2)) => t.eq(t2),
             (Self::Ipv6Addr(t), Self::Ipv6Addr(t2)) => t.eq(t2),
             (Self::SingleFloat(t), Self::SingleFloat(t2)) => t.eq(t2),
+            #[cfg(feature = "uuid")]
+            (Self::Uuid(u), Self::Uuid(u2)) => u.eq(u2),
+            #[cfg(feature = "regex")]
+            (Self::Regex(r), Self::Regex(r2)) => r.as_str().eq(r2.as_str()),
+            (
+                Self::Duration {
+                    seconds,
+                    nanoseconds,
+                },
+                Self::Duration {
+                    seconds: seconds2,
+                    nanoseconds: nanoseconds2,
+                },
+            ) => seconds.eq(seconds2) && nanoseconds.eq(nanoseconds2),
+            (
+                Self::Tagged { tag, value },
+                Self::Tagged {
+                    tag: tag2,
+                    value: value2,
+                },
+            ) => tag.eq(tag2) && value.eq(value2),
+            (Self::Unknown(unknown), Self::Unknown(unknown2)) => unknown.eq(unknown2),
             _ => unreachable!("already checked ty equality"),
         }
     }
@@ -220,6 +850,73 @@ impl PartialEq for Value {
 
 impl Eq for Value {}
 
+impl Value {
+    ///Compares two values, allowing floating point numbers to differ by up to `epsilon`.
+    ///
+    /// This covers [`Value::SingleFloat`], [`Value::DoubleFloat`] and [`Imaginary::PolarForm`] (where the modulus and argument are compared separately), and recurses into [`Value::Array`]s, [`Value::Map`]s, [`Value::OrderedMap`]s (where the order must match) and [`Value::Tagged`]s (where the tags must match). Every other variant falls back to strict equality using [`PartialEq`]. `NaN` is never approximately equal to anything, including itself.
+    ///
+    /// ```rust
+    /// use sourisdb::values::Value;
+    ///
+    /// let a = Value::Array(vec![Value::DoubleFloat(0.1 + 0.2), Value::from(1_u8)]);
+    /// let b = Value::Array(vec![Value::DoubleFloat(0.3), Value::from(1_u8)]);
+    ///
+    /// assert_ne!(a, b);
+    /// assert!(a.approx_eq(&b, 1e-10));
+    /// ```
+    #[must_use]
+    pub fn approx_eq(&self, other: &Value, epsilon: f64) -> bool {
+        fn floats_close(a: f64, b: f64, epsilon: f64) -> bool {
+            //deals with infinities, which would otherwise give a NaN difference
+            #[allow(clippy::float_cmp)]
+            if a == b {
+                return true;
+            }
+            let diff = a - b;
+            diff <= epsilon && -diff <= epsilon
+        }
+
+        match (self, other) {
+            (Self::SingleFloat(a), Self::SingleFloat(b)) => {
+                floats_close(f64::from(*a), f64::from(*b), epsilon)
+            }
+            (Self::DoubleFloat(a), Self::DoubleFloat(b)) => floats_close(*a, *b, epsilon),
+            (
+                Self::Imaginary(Imaginary::PolarForm { modulus, argument }),
+                Self::Imaginary(Imaginary::PolarForm {
+                    modulus: modulus2,
+                    argument: argument2,
+                }),
+            ) => {
+                floats_close(*modulus, *modulus2, epsilon)
+                    && floats_close(*argument, *argument2, epsilon)
+            }
+            (Self::Array(a), Self::Array(b)) => {
+                a.len() == b.len() && a.iter().zip(b).all(|(a, b)| a.approx_eq(b, epsilon))
+            }
+            (Self::Map(a), Self::Map(b)) => {
+                a.len() == b.len()
+                    && a.iter()
+                        .all(|(k, v)| b.get(k).is_some_and(|v2| v.approx_eq(v2, epsilon)))
+            }
+            (Self::OrderedMap(a), Self::OrderedMap(b)) => {
+                a.len() == b.len()
+                    && a.iter()
+                        .zip(b)
+                        .all(|((k, v), (k2, v2))| k == k2 && v.approx_eq(v2, epsilon))
+            }
+            (
+                Self::Tagged { tag, value },
+                Self::Tagged {
+                    tag: tag2,
+                    value: value2,
+                },
+            ) => tag == tag2 && value.approx_eq(value2, epsilon),
+            _ => self == other,
+        }
+    }
+}
+
 impl Hash for Value {
     fn hash<H: Hasher>(&self, state: &mut H) {
         core::mem::discriminant(self).hash(state);
@@ -245,14 +942,23 @@ impl Hash for Value {
             Value::Timestamp(v) => {
                 v.hash(state);
             }
+            Value::ZonedTimestamp(v) => (v.naive_utc(), v.timezone()).hash(state),
             Value::JSON(j) => {
                 j.to_string().hash(state);
             }
             Value::Map(m) => {
-                for k in m.keys() {
+                //sorted so that equal maps hash the same regardless of iteration order
+                let mut entries: Vec<_> = m.iter().collect();
+                entries.sort_unstable_by_key(|(k, _)| *k);
+
+                for (k, v) in entries {
                     k.hash(state);
+                    v.hash(state);
                 }
-                for v in m.values() {
+            }
+            Value::OrderedMap(m) => {
+                for (k, v) in m {
+                    k.hash(state);
                     v.hash(state);
                 }
             }
@@ -282,6 +988,23 @@ impl Hash for Value {
             Value::Ipv6Addr(a) => {
                 a.hash(state);
             }
+            #[cfg(feature = "uuid")]
+            Value::Uuid(u) => {
+                u.hash(state);
+            }
+            #[cfg(feature = "regex")]
+            Value::Regex(r) => {
+                r.as_str().hash(state);
+            }
+            Value::Duration {
+                seconds,
+                nanoseconds,
+            } => {
+                seconds.hash(state);
+                nanoseconds.hash(state);
+            }
+            Value::Tagged { tag, value } => (tag, value).hash(state),
+            Value::Unknown(unknown) => unknown.hash(state),
             Value::SingleFloat(f) => {
                 match f.classify() {
                     FpCategory::Nan => 0,
@@ -309,41 +1032,17 @@ impl Display for Value {
             Self::Integer(i) => write!(f, "{i}"),
             Self::Imaginary(i) => write!(f, "{i}"),
             Self::Timestamp(ndt) => write!(f, "{ndt}"),
+            Self::ZonedTimestamp(dt) => write!(f, "{} ({})", dt.to_rfc3339(), dt.timezone()),
             Self::JSON(v) => write!(f, "{v}"),
             Self::Null(_o) => write!(f, "null"),
             Self::Map(m) => {
-                cfg_if! {
-                    if #[cfg(feature = "std")] {
-                        use alloc::format;
-
-                        let mut table = comfy_table::Table::new();
-                        table
-                            .set_header(vec!["Key", "Value"])
-                            .load_preset(comfy_table::presets::UTF8_FULL)
-                            .apply_modifier(comfy_table::modifiers::UTF8_ROUND_CORNERS)
-                            .set_content_arrangement(comfy_table::ContentArrangement::Dynamic);
-
-                        for (k, v) in m {
-                            table.add_row(vec![format!("{k}"), format!("{v}")]);
-                        }
-                        write!(f, "\n{table}")
-                    } else {
-                        write!(f, "{{")?;
-
-                        let mut first = true;
-                        for (k, v) in m {
-                            if first {
-                                first = false;
+                //sorted so that the output is the same every time
+                let mut entries: Vec<_> = m.iter().collect();
+                entries.sort_unstable_by_key(|(k, _)| *k);
 
-                                write!(f, "{k}: {v}")?;
-                            } else {
-                                write!(f, ", {k}: {v}")?;
-                            }
-                        }
-                        write!(f, "}}")
-                    }
-                }
+                fmt_entries(f, entries)
             }
+            Self::OrderedMap(m) => fmt_entries(f, m.iter().map(|(k, v)| (k, v)).collect()),
             Self::Array(a) => {
                 write!(f, "[")?;
                 let mut first = true;
@@ -362,55 +1061,447 @@ impl Display for Value {
             Self::Ipv6Addr(v) => write!(f, "{v}"),
             Self::SingleFloat(v) => write!(f, "{v}"),
             Self::DoubleFloat(v) => write!(f, "{v}"),
+            #[cfg(feature = "uuid")]
+            Self::Uuid(v) => write!(f, "{v}"),
+            #[cfg(feature = "regex")]
+            Self::Regex(v) => write!(f, "/{v}/"),
+            Self::Duration {
+                seconds,
+                nanoseconds,
+            } => match i64::try_from(*seconds)
+                .ok()
+                .and_then(|seconds| TimeDelta::new(seconds, *nanoseconds))
+            {
+                Some(td) => write!(f, "{td}"),
+                None => write!(f, "{seconds}s + {nanoseconds}ns"),
+            },
+            Self::Tagged { tag, value } => write!(f, "{tag}({value})"),
+            Self::Unknown(UnknownValue { ty, bytes }) => {
+                write!(f, "<unknown type {ty}, {} bytes>", bytes.len())
+            }
         }
     }
 }
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
-#[allow(missing_docs)]
-///A type to represent the discriminant of [`Value`] - check the [`Value`] docs for more information on each type.
-pub enum ValueTy {
-    Character,
-    String,
-    Binary,
-    Boolean,
-    Integer,
-    Imaginary,
-    Timestamp,
-    JSON,
-    Null,
-    DoubleFloat,
-    Array,
-    Map,
-    Timezone,
-    Ipv4Addr,
-    Ipv6Addr,
-    SingleFloat,
-}
+///Filters key-value pairs down to those whose keys are integers within `lo..=hi`, sorted numerically. Keys for the same number (like `1` and `01`) keep the order they were given in. Returns [`None`] if either bound isn't an integer.
+pub(crate) fn int_key_range<'a>(
+    entries: Vec<(&'a String, &'a Value)>,
+    lo: &str,
+    hi: &str,
+) -> Option<impl Iterator<Item = (&'a String, &'a Value)>> {
+    let lo = Integer::from_str(lo).ok()?;
+    let hi = Integer::from_str(hi).ok()?;
 
-impl From<ValueTy> for u8 {
-    fn from(value: ValueTy) -> Self {
-        match value {
-            ValueTy::Character => 0,
-            ValueTy::String => 1,
-            ValueTy::Binary => 2,
-            ValueTy::Boolean => 3,
-            ValueTy::Integer => 4,
-            ValueTy::Imaginary => 5,
-            ValueTy::Timestamp => 6,
-            ValueTy::JSON => 7,
-            ValueTy::Map => 8,
-            ValueTy::Null => 9,
-            ValueTy::DoubleFloat => 10,
-            ValueTy::Array => 11,
-            ValueTy::Timezone => 12,
-            ValueTy::Ipv4Addr => 13,
-            ValueTy::Ipv6Addr => 14,
-            ValueTy::SingleFloat => 15,
-        }
-    }
+    let mut in_range: Vec<_> = entries
+        .into_iter()
+        .filter_map(|(k, v)| {
+            let n = Integer::from_str(k).ok()?;
+            (lo <= n && n <= hi).then_some((n, k, v))
+        })
+        .collect();
+    //stable, so that `Value::OrderedMap`s keep their order for equal numbers
+    in_range.sort_by_key(|(n, _, _)| *n);
+
+    Some(in_range.into_iter().map(|(_, k, v)| (k, v)))
 }
-impl TryFrom<u8> for ValueTy {
+
+///Writes out the key-value pairs of a [`Value::Map`] or [`Value::OrderedMap`] in the order given - as a table with `std`, or as `{k: v, ...}` without.
+fn fmt_entries(f: &mut Formatter<'_>, entries: Vec<(&String, &Value)>) -> core::fmt::Result {
+    cfg_if! {
+        if #[cfg(feature = "std")] {
+            use alloc::format;
+
+            let mut table = comfy_table::Table::new();
+            table
+                .set_header(vec!["Key", "Value"])
+                .load_preset(comfy_table::presets::UTF8_FULL)
+                .apply_modifier(comfy_table::modifiers::UTF8_ROUND_CORNERS)
+                .set_content_arrangement(comfy_table::ContentArrangement::Dynamic);
+
+            for (k, v) in entries {
+                table.add_row(vec![format!("{k}"), format!("{v}")]);
+            }
+            write!(f, "\n{table}")
+        } else {
+            write!(f, "{{")?;
+
+            let mut first = true;
+            for (k, v) in entries {
+                if first {
+                    first = false;
+
+                    write!(f, "{k}: {v}")?;
+                } else {
+                    write!(f, ", {k}: {v}")?;
+                }
+            }
+            write!(f, "}}")
+        }
+    }
+}
+
+impl Value {
+    ///Renders the value as indented, multi-line, YAML-ish text, with `indent` spaces for each level of nesting.
+    ///
+    /// Unlike [`Display`], this recurses through every [`Value::Map`], [`Value::OrderedMap`] and [`Value::Array`] rather than just the top level, which makes larger stores far easier to read and diff. [`Value::Map`] keys are sorted so the output is the same every time, and non-container values use their [`Display`] implementation.
+    ///
+    /// ```rust
+    /// use sourisdb::values::Value;
+    ///
+    /// let value = Value::Map(
+    ///     [
+    ///         ("name".to_string(), Value::String("souris".into())),
+    ///         ("list".to_string(), Value::Array(vec![Value::from(1_u8), Value::from(2_u8)])),
+    ///     ]
+    ///     .into_iter()
+    ///     .collect(),
+    /// );
+    ///
+    /// assert_eq!(value.to_pretty_string(2), "list:\n  - 1\n  - 2\nname: \"souris\"");
+    /// ```
+    #[must_use]
+    pub fn to_pretty_string(&self, indent: usize) -> String {
+        use alloc::format;
+
+        fn is_nested(value: &Value) -> bool {
+            match value {
+                Value::Map(m) => !m.is_empty(),
+                Value::OrderedMap(m) => !m.is_empty(),
+                Value::Array(a) => !a.is_empty(),
+                _ => false,
+            }
+        }
+
+        fn entry_lines<'a>(
+            entries: impl Iterator<Item = (&'a String, &'a Value)>,
+            indent: usize,
+            out: &mut Vec<String>,
+        ) {
+            let padding = " ".repeat(indent);
+            for (k, v) in entries {
+                if is_nested(v) {
+                    out.push(format!("{k}:"));
+
+                    let mut inner = vec![];
+                    lines(v, indent, &mut inner);
+                    out.extend(inner.into_iter().map(|line| format!("{padding}{line}")));
+                } else {
+                    out.push(format!("{k}: {v}"));
+                }
+            }
+        }
+
+        fn lines(value: &Value, indent: usize, out: &mut Vec<String>) {
+            match value {
+                Value::Map(m) if m.is_empty() => out.push("{}".into()),
+                Value::OrderedMap(m) if m.is_empty() => out.push("{}".into()),
+                Value::Array(a) if a.is_empty() => out.push("[]".into()),
+                Value::Map(m) => {
+                    //sorted so that the output is the same every time
+                    let mut entries: Vec<_> = m.iter().collect();
+                    entries.sort_unstable_by_key(|(k, _)| *k);
+
+                    entry_lines(entries.into_iter(), indent, out);
+                }
+                Value::OrderedMap(m) => entry_lines(m.iter().map(|(k, v)| (k, v)), indent, out),
+                Value::Array(a) => {
+                    //the dash takes up some of the indentation, so we need at least enough room for it and a space
+                    let width = indent.max(2);
+                    let dash = format!("{:<width$}", "-");
+                    let padding = " ".repeat(width);
+
+                    for v in a {
+                        let mut inner = vec![];
+                        lines(v, indent, &mut inner);
+
+                        for (i, line) in inner.into_iter().enumerate() {
+                            if i == 0 {
+                                out.push(format!("{dash}{line}"));
+                            } else {
+                                out.push(format!("{padding}{line}"));
+                            }
+                        }
+                    }
+                }
+                _ => out.push(format!("{value}")),
+            }
+        }
+
+        let mut out = vec![];
+        lines(self, indent, &mut out);
+        out.join("\n")
+    }
+}
+
+///Wraps an [`Integer`] so that human-readable formats get a string rather than a number, as formats like JSON can't always hold the full range of an [`Integer`].
+#[cfg(feature = "serde")]
+struct SerdeInteger(Integer);
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for SerdeInteger {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.collect_str(&self.0)
+        } else {
+            self.0.serialize(serializer)
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for SerdeInteger {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+
+        if deserializer.is_human_readable() {
+            let s = String::deserialize(deserializer)?;
+            Integer::from_str(&s).map(Self).map_err(D::Error::custom)
+        } else {
+            Integer::deserialize(deserializer).map(Self)
+        }
+    }
+}
+
+///Serialises a [`Value`] as a struct with a `type` field holding the name of the [`ValueTy`], and a `value` field holding the contents.
+///
+/// Unlike [`Value::convert_to_json`], this works for every variant with any [`serde`] format. [`Value::JSON`] and `Value::Uuid` are stored as strings, and [`Integer`]s are stored as strings in human-readable formats to avoid losing precision.
+///
+/// This is `serde`'s adjacently-tagged representation (`{"type": "Integer", "value": "5"}`) rather than the internally-tagged one (`{"type": "Integer", ...}`), for two reasons:
+/// - Internal tagging puts the tag alongside the contents' own fields, so it only works for contents which are maps or structs. Most variants hold scalars (eg. `"value": true`) or sequences, which have nowhere to put the tag.
+/// - Reading internally-tagged data back needs [`serde::Deserializer::deserialize_any`] to buffer the contents until the tag is found, which formats that aren't self-describing (like `bincode`) don't support. With adjacent tagging, the `type` is always read first and then tells us how to read the `value`.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Value {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let mut state = serializer.serialize_struct("Value", 2)?;
+        state.serialize_field("type", &alloc::format!("{:?}", self.as_ty()))?;
+        match self {
+            Value::Character(c) => state.serialize_field("value", c)?,
+            Value::String(s) => state.serialize_field("value", s)?,
+            Value::Binary(b) => state.serialize_field("value", &b.0)?,
+            Value::Boolean(b) => state.serialize_field("value", b)?,
+            Value::Integer(i) => state.serialize_field("value", &SerdeInteger(*i))?,
+            Value::Imaginary(i) => state.serialize_field("value", i)?,
+            Value::Timestamp(t) => state.serialize_field("value", t)?,
+            Value::ZonedTimestamp(t) => {
+                state.serialize_field("value", &(t.naive_utc(), t.timezone()))?;
+            }
+            Value::JSON(j) => state.serialize_field("value", &j.to_string())?,
+            Value::Null(()) => state.serialize_field("value", &())?,
+            Value::SingleFloat(f) => state.serialize_field("value", f)?,
+            Value::DoubleFloat(f) => state.serialize_field("value", f)?,
+            Value::Array(a) => state.serialize_field("value", a)?,
+            Value::Map(m) => state.serialize_field("value", m)?,
+            Value::OrderedMap(m) => state.serialize_field("value", m)?,
+            Value::Timezone(tz) => state.serialize_field("value", tz)?,
+            Value::Ipv4Addr(a) => state.serialize_field("value", a)?,
+            Value::Ipv6Addr(a) => state.serialize_field("value", a)?,
+            #[cfg(feature = "uuid")]
+            Value::Uuid(u) => state.serialize_field("value", &u.hyphenated().to_string())?,
+            #[cfg(feature = "regex")]
+            Value::Regex(r) => state.serialize_field("value", r.as_str())?,
+            Value::Duration {
+                seconds,
+                nanoseconds,
+            } => state.serialize_field("value", &(SerdeInteger(*seconds), nanoseconds))?,
+            Value::Tagged { tag, value } => state.serialize_field("value", &(tag, value))?,
+            Value::Unknown(UnknownValue { ty, bytes }) => {
+                state.serialize_field("value", &(ty, bytes))?;
+            }
+        }
+        state.end()
+    }
+}
+
+///Deserialises the contents of a [`Value`] once the [`ValueTy`] is known.
+#[cfg(feature = "serde")]
+struct ValueContentSeed(ValueTy);
+
+#[cfg(feature = "serde")]
+impl<'de> serde::de::DeserializeSeed<'de> for ValueContentSeed {
+    type Value = Value;
+
+    fn deserialize<D: serde::Deserializer<'de>>(self, deserializer: D) -> Result<Value, D::Error> {
+        use serde::{de::Error, Deserialize};
+
+        Ok(match self.0 {
+            ValueTy::Character => Value::Character(char::deserialize(deserializer)?),
+            ValueTy::String => Value::String(String::deserialize(deserializer)?),
+            ValueTy::Binary => Value::Binary(BinaryData(Vec::deserialize(deserializer)?)),
+            ValueTy::Boolean => Value::Boolean(bool::deserialize(deserializer)?),
+            ValueTy::Integer => Value::Integer(SerdeInteger::deserialize(deserializer)?.0),
+            ValueTy::Imaginary => Value::Imaginary(Imaginary::deserialize(deserializer)?),
+            ValueTy::Timestamp => Value::Timestamp(NaiveDateTime::deserialize(deserializer)?),
+            ValueTy::ZonedTimestamp => {
+                let (utc, tz) = <(NaiveDateTime, Tz)>::deserialize(deserializer)?;
+                Value::ZonedTimestamp(tz.from_utc_datetime(&utc))
+            }
+            ValueTy::JSON => {
+                let s = String::deserialize(deserializer)?;
+                Value::JSON(serde_json::from_str(&s).map_err(D::Error::custom)?)
+            }
+            ValueTy::Null => Value::Null(<()>::deserialize(deserializer)?),
+            ValueTy::SingleFloat => Value::SingleFloat(f32::deserialize(deserializer)?),
+            ValueTy::DoubleFloat => Value::DoubleFloat(f64::deserialize(deserializer)?),
+            ValueTy::Array => Value::Array(Vec::deserialize(deserializer)?),
+            ValueTy::Map => Value::Map(HashMap::deserialize(deserializer)?),
+            ValueTy::OrderedMap => Value::OrderedMap(Vec::deserialize(deserializer)?),
+            ValueTy::Timezone => Value::Timezone(Tz::deserialize(deserializer)?),
+            ValueTy::Ipv4Addr => Value::Ipv4Addr(Ipv4Addr::deserialize(deserializer)?),
+            ValueTy::Ipv6Addr => Value::Ipv6Addr(Ipv6Addr::deserialize(deserializer)?),
+            #[cfg(feature = "uuid")]
+            ValueTy::Uuid => {
+                let s = String::deserialize(deserializer)?;
+                Value::Uuid(Uuid::try_parse(&s).map_err(D::Error::custom)?)
+            }
+            #[cfg(feature = "regex")]
+            ValueTy::Regex => {
+                let s = String::deserialize(deserializer)?;
+                Value::Regex(Regex::new(&s).map_err(D::Error::custom)?)
+            }
+            ValueTy::Duration => {
+                let (SerdeInteger(seconds), nanoseconds) =
+                    <(SerdeInteger, u32)>::deserialize(deserializer)?;
+                if nanoseconds >= 1_000_000_000 {
+                    return Err(D::Error::custom(ValueSerError::InvalidDateOrTime));
+                }
+                Value::Duration {
+                    seconds,
+                    nanoseconds,
+                }
+            }
+            ValueTy::Tagged => {
+                let (tag, value) = <(String, Value)>::deserialize(deserializer)?;
+                Value::Tagged {
+                    tag,
+                    value: Box::new(value),
+                }
+            }
+            ValueTy::Unknown => {
+                let (ty, bytes) = <(u8, Vec<u8>)>::deserialize(deserializer)?;
+                let Some(unknown) = UnknownValue::new(ty, bytes) else {
+                    return Err(D::Error::custom(ValueSerError::InvalidType(ty)));
+                };
+                Value::Unknown(unknown)
+            }
+        })
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Value {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::{Error, MapAccess, SeqAccess, Visitor};
+
+        struct ValueVisitor;
+
+        impl<'de> Visitor<'de> for ValueVisitor {
+            type Value = Value;
+
+            fn expecting(&self, f: &mut Formatter) -> core::fmt::Result {
+                write!(f, "a souris value with a `type` and a `value`")
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Value, A::Error> {
+                let ty: String = seq
+                    .next_element()?
+                    .ok_or_else(|| A::Error::invalid_length(0, &self))?;
+                let ty = ValueTy::from_name(&ty)
+                    .ok_or_else(|| A::Error::custom(alloc::format!("unknown value type: {ty}")))?;
+
+                seq.next_element_seed(ValueContentSeed(ty))?
+                    .ok_or_else(|| A::Error::invalid_length(1, &self))
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Value, A::Error> {
+                //the type has to come first, as we need it to know how to deserialise the value
+                match map.next_key::<String>()?.as_deref() {
+                    Some("type") => {}
+                    Some(other) => return Err(A::Error::unknown_field(other, &["type"])),
+                    None => return Err(A::Error::missing_field("type")),
+                }
+                let ty: String = map.next_value()?;
+                let ty = ValueTy::from_name(&ty)
+                    .ok_or_else(|| A::Error::custom(alloc::format!("unknown value type: {ty}")))?;
+
+                match map.next_key::<String>()?.as_deref() {
+                    Some("value") => {}
+                    Some(other) => return Err(A::Error::unknown_field(other, &["value"])),
+                    None => return Err(A::Error::missing_field("value")),
+                }
+                map.next_value_seed(ValueContentSeed(ty))
+            }
+        }
+
+        deserializer.deserialize_struct("Value", &["type", "value"], ValueVisitor)
+    }
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[allow(missing_docs)]
+///A type to represent the discriminant of [`Value`] - check the [`Value`] docs for more information on each type.
+pub enum ValueTy {
+    Character,
+    String,
+    Binary,
+    Boolean,
+    Integer,
+    Imaginary,
+    Timestamp,
+    JSON,
+    Null,
+    DoubleFloat,
+    Array,
+    Map,
+    Timezone,
+    Ipv4Addr,
+    Ipv6Addr,
+    SingleFloat,
+    #[cfg(feature = "uuid")]
+    Uuid,
+    Duration,
+    #[cfg(feature = "regex")]
+    Regex,
+    OrderedMap,
+    ZonedTimestamp,
+    Tagged,
+    ///The type of [`Value::Unknown`] - this never appears in serialised bytes.
+    Unknown,
+}
+
+impl From<ValueTy> for u8 {
+    fn from(value: ValueTy) -> Self {
+        match value {
+            ValueTy::Character => 0,
+            ValueTy::String => 1,
+            ValueTy::Binary => 2,
+            ValueTy::Boolean => 3,
+            ValueTy::Integer => 4,
+            ValueTy::Imaginary => 5,
+            ValueTy::Timestamp => 6,
+            ValueTy::JSON => 7,
+            ValueTy::Map => 8,
+            ValueTy::Null => 9,
+            ValueTy::DoubleFloat => 10,
+            ValueTy::Array => 11,
+            ValueTy::Timezone => 12,
+            ValueTy::Ipv4Addr => 13,
+            ValueTy::Ipv6Addr => 14,
+            ValueTy::SingleFloat => 15,
+            #[cfg(feature = "uuid")]
+            ValueTy::Uuid => 16,
+            ValueTy::Duration => 17,
+            #[cfg(feature = "regex")]
+            ValueTy::Regex => 18,
+            ValueTy::OrderedMap => 19,
+            ValueTy::ZonedTimestamp => 20,
+            ValueTy::Tagged => 21,
+            ValueTy::Unknown => u8::MAX,
+        }
+    }
+}
+impl TryFrom<u8> for ValueTy {
     type Error = ValueSerError;
 
     fn try_from(value: u8) -> Result<Self, Self::Error> {
@@ -431,11 +1522,130 @@ impl TryFrom<u8> for ValueTy {
             13 => ValueTy::Ipv4Addr,
             14 => ValueTy::Ipv6Addr,
             15 => ValueTy::SingleFloat,
+            #[cfg(feature = "uuid")]
+            16 => ValueTy::Uuid,
+            17 => ValueTy::Duration,
+            #[cfg(feature = "regex")]
+            18 => ValueTy::Regex,
+            19 => ValueTy::OrderedMap,
+            20 => ValueTy::ZonedTimestamp,
+            21 => ValueTy::Tagged,
             _ => return Err(ValueSerError::InvalidType(value)),
         })
     }
 }
 
+impl ValueTy {
+    ///The number of types which fit into the top 4 bits of the type byte.
+    const BASE_TYPES: u8 = 16;
+
+    ///Gets the type byte which starts a serialised [`Value`] of this type, with the lower bits left empty for any extra information.
+    ///
+    /// There are only 4 bits available for the type, so any types after the first 16 are extended types, which are stored as a [`ValueTy::Null`] with the lower 4 bits set to signify which type it actually is. This means that those types cannot store any extra information in the type byte.
+    ///
+    /// Extended types are always followed by the length of their contents, so that versions of the crate which don't know about a type can skip over it (see [`Value::deser_skip_unknown`]). All new types are added as extended types so that this keeps working - base types aren't length-prefixed as that would cost at least one byte for every value.
+    ///
+    /// Strings inside extended types are never Huffman-encoded, even when they're nested inside other values. Those versions write the skipped bytes back out exactly as they were, but with a new Huffman tree, which wouldn't decode any strings that used the old one.
+    pub(crate) fn to_type_byte(self) -> u8 {
+        Self::type_byte_for_discriminant(u8::from(self))
+    }
+
+    ///Gets the type byte for a given discriminant - see [`ValueTy::to_type_byte`].
+    fn type_byte_for_discriminant(discriminant: u8) -> u8 {
+        if discriminant < Self::BASE_TYPES {
+            discriminant << 4
+        } else {
+            (u8::from(ValueTy::Null) << 4)
+                | (discriminant.wrapping_sub(Self::BASE_TYPES - 1) & 0b0000_1111)
+        }
+    }
+
+    ///Whether the type byte is for an extended type, and so is followed by the length of the contents.
+    pub(crate) fn is_extended_type_byte(byte: u8) -> bool {
+        (byte >> 4) == u8::from(ValueTy::Null) && (byte & 0b0000_1111) > 0
+    }
+
+    ///Whether this is an extended type - see [`ValueTy::to_type_byte`].
+    pub(crate) fn is_extended(self) -> bool {
+        u8::from(self) >= Self::BASE_TYPES
+    }
+
+    ///Whether values of this type hold other [`Value`]s - ie. whether [`Value::walk`] can find anything nested inside them.
+    ///
+    /// [`ValueTy::JSON`] doesn't count, as its contents are [`serde_json::Value`]s rather than [`Value`]s.
+    ///
+    /// ```rust
+    /// use sourisdb::values::ValueTy;
+    ///
+    /// let classifications = [
+    ///     (ValueTy::Character, false),
+    ///     (ValueTy::String, false),
+    ///     (ValueTy::Binary, false),
+    ///     (ValueTy::Boolean, false),
+    ///     (ValueTy::Integer, false),
+    ///     (ValueTy::Imaginary, false),
+    ///     (ValueTy::Timestamp, false),
+    ///     (ValueTy::JSON, false),
+    ///     (ValueTy::Null, false),
+    ///     (ValueTy::DoubleFloat, false),
+    ///     (ValueTy::Array, true),
+    ///     (ValueTy::Map, true),
+    ///     (ValueTy::Timezone, false),
+    ///     (ValueTy::Ipv4Addr, false),
+    ///     (ValueTy::Ipv6Addr, false),
+    ///     (ValueTy::SingleFloat, false),
+    ///     (ValueTy::Duration, false),
+    ///     (ValueTy::OrderedMap, true),
+    ///     (ValueTy::ZonedTimestamp, false),
+    ///     (ValueTy::Tagged, true),
+    ///     (ValueTy::Unknown, false),
+    /// ];
+    ///
+    /// for (ty, is_container) in classifications {
+    ///     assert_eq!(ty.is_container(), is_container, "{ty:?}");
+    ///     assert_eq!(ty.is_scalar(), !is_container, "{ty:?}");
+    /// }
+    /// ```
+    #[must_use]
+    pub const fn is_container(self) -> bool {
+        matches!(
+            self,
+            ValueTy::Array | ValueTy::Map | ValueTy::OrderedMap | ValueTy::Tagged
+        )
+    }
+
+    ///Whether values of this type are single values, rather than holding other [`Value`]s - the opposite of [`ValueTy::is_container`].
+    #[must_use]
+    pub const fn is_scalar(self) -> bool {
+        !self.is_container()
+    }
+
+    ///Gets a type back from its name, as given by its [`Debug`] implementation.
+    pub(crate) fn from_name(name: &str) -> Option<Self> {
+        (0..=u8::MAX)
+            .filter_map(|b| Self::try_from(b).ok())
+            .chain(core::iter::once(ValueTy::Unknown))
+            .find(|ty| alloc::format!("{ty:?}") == name)
+    }
+
+    ///Gets the type back from the type byte which starts a serialised [`Value`].
+    ///
+    /// # Errors
+    /// - [`ValueSerError::InvalidType`] if the byte doesn't correspond to a type.
+    pub(crate) fn from_type_byte(byte: u8) -> Result<Self, ValueSerError> {
+        Self::try_from(Self::discriminant_from_type_byte(byte))
+    }
+
+    ///Gets the discriminant from the type byte which starts a serialised [`Value`], without checking that it is valid.
+    fn discriminant_from_type_byte(byte: u8) -> u8 {
+        if Self::is_extended_type_byte(byte) {
+            (byte & 0b0000_1111) - 1 + Self::BASE_TYPES
+        } else {
+            byte >> 4
+        }
+    }
+}
+
 #[derive(Debug)]
 ///An error when serialising or deserialising a [`Value`]
 pub enum ValueSerError {
@@ -473,13 +1683,36 @@ pub enum ValueSerError {
     BinarySerError(BinarySerError),
     ///We tried to deserialise some huffman-encoded data, but couldn't
     HuffmanSerError(HuffmanSerError),
-    ///We tried to deserialise some JSON, found a `souris_type` for some variable, but the contents were invalid
+    ///We tried to deserialise some JSON, found a `souris_type` for some variable (or some CBOR with a tag for a specific type), but the contents were invalid
     InvalidSourisType {
         ///The `souris_type` we deserialised
         found: ValueTy,
         ///The issue with the object
         cause: InvalidSourisTypeError,
     },
+    ///We tried to deserialise a `Value::Regex`, but the pattern didn't compile.
+    #[cfg(feature = "regex")]
+    InvalidRegex(regex::Error),
+    ///We tried to convert a JSON or CBOR number, but it fit neither an [`Integer`] nor an [`f64`].
+    UnrepresentableNumber,
+    ///We tried to read some CBOR, but it wasn't valid.
+    #[cfg(feature = "cbor")]
+    InvalidCbor(String),
+    ///We found [`Value::Array`]s, [`Value::Map`]s or [`Value::OrderedMap`]s nested more deeply than the maximum depth allowed - see [`Value::deser_with_max_depth`].
+    MaxDepthExceeded,
+    ///An element of a [`Value::Array`] couldn't be converted - see [`Value::array_of`].
+    InvalidArrayElement {
+        ///The index of the element in the array
+        index: usize,
+        ///Why the element couldn't be converted
+        cause: Box<ValueSerError>,
+    },
+    ///We couldn't deserialise the [`Value::String`] inside a [`Value::JSON`] (eg. because it was huffman-encoded and no huffman tree was given).
+    JsonInnerString(Box<ValueSerError>),
+    ///We couldn't deserialise the [`Value::String`] inside a [`Value::Timezone`] (eg. because it was huffman-encoded and no huffman tree was given).
+    TimezoneInnerString(Box<ValueSerError>),
+    ///We read a length (eg. of a string or an array) which is too big to fit in a [`usize`], so couldn't possibly be valid. This holds what the length was for.
+    LengthTooLarge(&'static str),
 }
 
 #[derive(Debug)]
@@ -519,6 +1752,26 @@ impl Display for ValueSerError {
                 f,
                 "Error with JSON `souris_type` - was deserialising a {found:?}, but {cause:?}"
             ),
+            #[cfg(feature = "regex")]
+            ValueSerError::InvalidRegex(e) => write!(f, "Error compiling regex: {e}"),
+            ValueSerError::UnrepresentableNumber => {
+                write!(f, "Found number that couldn't be represented")
+            }
+            #[cfg(feature = "cbor")]
+            ValueSerError::InvalidCbor(e) => write!(f, "Error reading CBOR: {e}"),
+            ValueSerError::MaxDepthExceeded => {
+                write!(f, "Found values nested more deeply than allowed")
+            }
+            ValueSerError::InvalidArrayElement { index, cause } => {
+                write!(f, "Error converting array element {index}: {cause}")
+            }
+            ValueSerError::JsonInnerString(cause) => {
+                write!(f, "Error deserialising string inside JSON: {cause}")
+            }
+            ValueSerError::TimezoneInnerString(cause) => {
+                write!(f, "Error deserialising string inside timezone: {cause}")
+            }
+            ValueSerError::LengthTooLarge(what) => write!(f, "{what} length too large"),
         }
     }
 }
@@ -528,6 +1781,11 @@ impl From<IntegerSerError> for ValueSerError {
         Self::IntegerSerError(value)
     }
 }
+impl From<CursorError> for ValueSerError {
+    fn from(_: CursorError) -> Self {
+        Self::NotEnoughBytes
+    }
+}
 impl From<FromUtf8Error> for ValueSerError {
     fn from(value: FromUtf8Error) -> Self {
         Self::NonUTF8String(value)
@@ -553,6 +1811,12 @@ impl From<HuffmanSerError> for ValueSerError {
         Self::HuffmanSerError(value)
     }
 }
+#[cfg(feature = "regex")]
+impl From<regex::Error> for ValueSerError {
+    fn from(value: regex::Error) -> Self {
+        Self::InvalidRegex(value)
+    }
+}
 
 #[cfg(feature = "std")]
 impl std::error::Error for ValueSerError {
@@ -564,12 +1828,94 @@ impl std::error::Error for ValueSerError {
             ValueSerError::TzError(e) => Some(e),
             ValueSerError::BinarySerError(e) => Some(e),
             ValueSerError::HuffmanSerError(e) => Some(e),
+            #[cfg(feature = "regex")]
+            ValueSerError::InvalidRegex(e) => Some(e),
+            ValueSerError::InvalidArrayElement { cause, .. }
+            | ValueSerError::JsonInnerString(cause)
+            | ValueSerError::TimezoneInnerString(cause) => Some(cause.as_ref()),
             _ => None,
         }
     }
 }
 
 impl Value {
+    ///Converts a [`Value`] to a [`serde_json::Value`] without consuming it - see [`Value::convert_to_json`] for how each variant is converted.
+    ///
+    /// Arrays and maps are walked by reference, so only the contents which end up in the JSON (like the text of strings) get cloned.
+    ///
+    /// ```rust
+    /// use sourisdb::{serde_json::json, values::Value};
+    ///
+    /// let value = Value::Array(vec![Value::from(1_u8), Value::String("two".into())]);
+    /// assert_eq!(value.to_json_value(false), Some(json!([1, "two"])));
+    /// assert_eq!(value.to_json_value(false), value.convert_to_json(false));
+    /// ```
+    #[must_use]
+    pub fn to_json_value(&self, add_souris_types: bool) -> Option<SJValue> {
+        Some(match self {
+            Value::Array(arr) => SJValue::Array(
+                arr.iter()
+                    .map(|v| v.to_json_value(add_souris_types))
+                    .collect::<Option<Vec<_>>>()?,
+            ),
+            Value::Map(m) => SJValue::Object(
+                m.iter()
+                    .map(|(k, v)| v.to_json_value(add_souris_types).map(|v| (k.clone(), v)))
+                    .collect::<Option<SJMap<_, _>>>()?,
+            ),
+            Value::OrderedMap(m) => Self::ordered_map_to_json(
+                m.iter()
+                    .map(|(k, v)| v.to_json_value(add_souris_types).map(|v| (k.clone(), v)))
+                    .collect::<Option<Vec<_>>>()?,
+                add_souris_types,
+            ),
+            Value::Tagged { tag, value } => Self::tagged_to_json(
+                tag.clone(),
+                value.to_json_value(add_souris_types)?,
+                add_souris_types,
+            )?,
+            other => other.clone().convert_to_json(add_souris_types)?,
+        })
+    }
+
+    ///Converts the already-converted entries of a [`Value::OrderedMap`] to JSON. With `souris_type`s, the entries are kept as an array of pairs so that the order survives.
+    fn ordered_map_to_json(entries: Vec<(String, SJValue)>, add_souris_types: bool) -> SJValue {
+        if add_souris_types {
+            let entries = entries
+                .into_iter()
+                .map(|(k, v)| SJValue::Array(vec![SJValue::String(k), v]))
+                .collect();
+
+            let mut obj = SJMap::new();
+            obj.insert(
+                "souris_type".into(),
+                SJValue::Number(Number::from(u8::from(ValueTy::OrderedMap))),
+            );
+            obj.insert("entries".into(), SJValue::Array(entries));
+
+            SJValue::Object(obj)
+        } else {
+            SJValue::Object(entries.into_iter().collect())
+        }
+    }
+
+    ///Converts the tag and already-converted contents of a [`Value::Tagged`] to JSON. Returns [`None`] if `souris_type`s are being added and the tag is `souris_type`, as it would clash.
+    fn tagged_to_json(tag: String, value: SJValue, add_souris_types: bool) -> Option<SJValue> {
+        let mut obj = SJMap::new();
+        if add_souris_types {
+            if tag == "souris_type" {
+                return None;
+            }
+            obj.insert(
+                "souris_type".into(),
+                SJValue::Number(Number::from(u8::from(ValueTy::Tagged))),
+            );
+        }
+        obj.insert(tag, value);
+
+        Some(SJValue::Object(obj))
+    }
+
     ///Converts a [`Value`] to a [`serde_json::Value`].
     ///
     /// If `add_souris_types` is enabled, then some objects will have extra fields that can be used for more accurate conversions back the other way. For example, an [`Imaginary`] number will be read as an [`Imaginary`] number, rather than a [`Value::Map`].
@@ -581,8 +1927,16 @@ impl Value {
     /// - [`Value::Binary`]
     /// - [`Value::IPV4Addr`]
     /// - [`Value::IPV6Addr`]
+    /// - `Value::Uuid`
+    /// - [`Value::Duration`]
+    /// - `Value::Regex`
+    /// - [`Value::OrderedMap`] - without `souris_type`s, this becomes a normal JSON object, which doesn't keep the order.
+    /// - [`Value::ZonedTimestamp`] - without `souris_type`s, this becomes an RFC 3339 (ISO 8601) string with the offset, which doesn't keep the name of the timezone.
+    /// - [`Value::Tagged`] - this becomes `{"<tag>": <value>}`, like `serde`'s externally-tagged enums. Without `souris_type`s, this is read back as a [`Value::Map`] with one key.
+    ///
+    /// Since JSON only supports a maximum of 64-bit integers and finite floating point numbers, [`None`] will be returned if either of those are encountered. [`None`] is also returned for [`Value::Unknown`], as we don't know what it contains.
     ///
-    /// Since JSON only supports a maximum of 64-bit integers and finite floating point numbers, [`None`] will be returned if either of those are encountered.
+    /// NB: This is different to the `serde::Serialize` implementation (with the `serde` feature), which wraps every value in a struct with its type and never adds `souris_type`s - that is for round-tripping through any `serde` format, whereas this is for writing plain JSON.
     #[allow(clippy::too_many_lines)]
     #[must_use]
     pub fn convert_to_json(self, add_souris_types: bool) -> Option<SJValue> {
@@ -605,6 +1959,12 @@ impl Value {
                     .map(|(k, v)| Value::convert_to_json(v, add_souris_types).map(|v| (k, v)))
                     .collect::<Option<SJMap<_, _>>>()?,
             ),
+            Value::OrderedMap(m) => Self::ordered_map_to_json(
+                m.into_iter()
+                    .map(|(k, v)| Value::convert_to_json(v, add_souris_types).map(|v| (k, v)))
+                    .collect::<Option<Vec<_>>>()?,
+                add_souris_types,
+            ),
             Value::Imaginary(im) => {
                 let mut obj = SJMap::new();
                 if add_souris_types {
@@ -620,6 +1980,7 @@ impl Value {
                         obj.insert("imaginary".into(), imaginary.to_json()?);
                     }
                     Imaginary::PolarForm { modulus, argument } => {
+                        //`from_f64` gives `None` for NaN and infinite floats, so they fail the whole conversion rather than being written as something else
                         let to_json = |float| Number::from_f64(float).map(SJValue::Number);
 
                         obj.insert("modulus".into(), to_json(modulus)?);
@@ -642,6 +2003,26 @@ impl Value {
 
                 SJValue::Object(obj)
             }
+            Value::ZonedTimestamp(dt) => {
+                let timestamp = SJValue::String(dt.to_rfc3339());
+                if add_souris_types {
+                    let mut obj = SJMap::new();
+                    obj.insert(
+                        "souris_type".into(),
+                        SJValue::Number(Number::from(u8::from(ValueTy::ZonedTimestamp))),
+                    );
+
+                    obj.insert("timestamp".into(), timestamp);
+                    obj.insert(
+                        "timezone".into(),
+                        SJValue::String(dt.timezone().to_string()),
+                    );
+
+                    SJValue::Object(obj)
+                } else {
+                    timestamp
+                }
+            }
             Value::Timezone(tz) => {
                 let mut obj = SJMap::new();
                 if add_souris_types {
@@ -697,29 +2078,95 @@ impl Value {
                     arr
                 }
             }
-        })
-    }
+            #[cfg(feature = "uuid")]
+            Value::Uuid(u) => {
+                let hyphenated = SJValue::String(u.hyphenated().to_string());
+                if add_souris_types {
+                    let mut obj = SJMap::new();
+                    obj.insert(
+                        "souris_type".into(),
+                        SJValue::Number(Number::from(u8::from(ValueTy::Uuid))),
+                    );
 
-    ///Converts a [`serde_json::Value`] back into a [`Value`]. If `add_souris_types` was enabled, then certain variants will be constructed back into their proper variants. If not, then they will be added as [`Value::Map`]s.
-    ///
-    /// Those variants are:
-    /// - [`Value::Imaginary`]
-    /// - [`Value::Timestamp`]
-    /// - [`Value::Timezone`]
-    /// - [`Value::Binary`]
-    /// - [`Value::IPV4Addr`]
-    /// - [`Value::IPV6Addr`]
-    #[allow(clippy::too_many_lines)]
-    pub fn convert_from_json(val: SJValue) -> Result<Self, ValueSerError> {
-        Ok(match val {
-            SJValue::Null => Self::Null(()),
-            SJValue::Bool(b) => Self::Boolean(b),
+                    obj.insert("uuid".into(), hyphenated);
+
+                    SJValue::Object(obj)
+                } else {
+                    hyphenated
+                }
+            }
+            #[cfg(feature = "regex")]
+            Value::Regex(r) => {
+                let pattern = SJValue::String(r.as_str().to_string());
+                if add_souris_types {
+                    let mut obj = SJMap::new();
+                    obj.insert(
+                        "souris_type".into(),
+                        SJValue::Number(Number::from(u8::from(ValueTy::Regex))),
+                    );
+
+                    obj.insert("regex".into(), pattern);
+
+                    SJValue::Object(obj)
+                } else {
+                    pattern
+                }
+            }
+            Value::Duration {
+                seconds,
+                nanoseconds,
+            } => {
+                let mut obj = SJMap::new();
+                if add_souris_types {
+                    obj.insert(
+                        "souris_type".into(),
+                        SJValue::Number(Number::from(u8::from(ValueTy::Duration))),
+                    );
+                }
+
+                obj.insert("seconds".into(), seconds.to_json()?);
+                obj.insert(
+                    "nanoseconds".into(),
+                    SJValue::Number(Number::from(nanoseconds)),
+                );
+
+                SJValue::Object(obj)
+            }
+            Value::Tagged { tag, value } => Self::tagged_to_json(
+                tag,
+                value.convert_to_json(add_souris_types)?,
+                add_souris_types,
+            )?,
+            Value::Unknown(_) => return None,
+        })
+    }
+
+    ///Converts a [`serde_json::Value`] back into a [`Value`]. If `add_souris_types` was enabled, then certain variants will be constructed back into their proper variants. If not, then they will be added as [`Value::Map`]s.
+    ///
+    /// Those variants are:
+    /// - [`Value::Imaginary`]
+    /// - [`Value::Timestamp`]
+    /// - [`Value::Timezone`]
+    /// - [`Value::Binary`]
+    /// - [`Value::IPV4Addr`]
+    /// - [`Value::IPV6Addr`]
+    /// - `Value::Uuid`
+    /// - [`Value::Duration`]
+    /// - [`Value::OrderedMap`]
+    /// - [`Value::ZonedTimestamp`]
+    /// - [`Value::Tagged`] - only objects with exactly one key other than the `souris_type` are read as tagged values.
+    #[allow(clippy::too_many_lines)]
+    pub fn convert_from_json(val: SJValue) -> Result<Self, ValueSerError> {
+        Ok(match val {
+            SJValue::Null => Self::Null(()),
+            SJValue::Bool(b) => Self::Boolean(b),
             SJValue::Number(n) => {
                 if let Some(i) = Integer::from_json(&n) {
                     Self::Integer(i)
                 } else {
+                    //only possible if `serde_json/arbitrary_precision` gets enabled somewhere in the dependency tree
                     let Some(float) = n.as_f64() else {
-                        unreachable!("just checked if was integer");
+                        return Err(ValueSerError::UnrepresentableNumber);
                     };
                     Self::DoubleFloat(float)
                 }
@@ -802,6 +2249,29 @@ impl Value {
                                     })
                                 }
                             }
+                            ValueTy::ZonedTimestamp => {
+                                if let Some((SJValue::String(timestamp), SJValue::String(tz))) =
+                                    obj.get("timestamp").zip(obj.get("timezone"))
+                                {
+                                    if let Some((timestamp, tz)) =
+                                        DateTime::parse_from_rfc3339(timestamp)
+                                            .ok()
+                                            .zip(Tz::from_str(tz).ok())
+                                    {
+                                        Ok(Value::ZonedTimestamp(timestamp.with_timezone(&tz)))
+                                    } else {
+                                        Err(ValueSerError::InvalidSourisType {
+                                            found: ty,
+                                            cause: InvalidSourisTypeError::InvalidData,
+                                        })
+                                    }
+                                } else {
+                                    Err(ValueSerError::InvalidSourisType {
+                                        found: ty,
+                                        cause: InvalidSourisTypeError::NotFound,
+                                    })
+                                }
+                            }
                             ValueTy::Timezone => {
                                 if let Some(SJValue::String(tz)) = obj.get("timezone") {
                                     if let Ok(tz) = Tz::from_str(tz) {
@@ -884,6 +2354,117 @@ impl Value {
                                     })
                                 }
                             }
+                            ValueTy::Duration => {
+                                if let Some((
+                                    SJValue::Number(seconds),
+                                    SJValue::Number(nanoseconds),
+                                )) = obj
+                                    .get("seconds")
+                                    .cloned()
+                                    .zip(obj.get("nanoseconds").cloned())
+                                {
+                                    if let Some((seconds, nanoseconds)) =
+                                        Integer::from_json(&seconds)
+                                            .zip(
+                                                nanoseconds
+                                                    .as_u64()
+                                                    .and_then(|n| u32::try_from(n).ok()),
+                                            )
+                                            .filter(|(_, nanoseconds)| *nanoseconds < 1_000_000_000)
+                                    {
+                                        Ok(Value::Duration {
+                                            seconds,
+                                            nanoseconds,
+                                        })
+                                    } else {
+                                        Err(ValueSerError::InvalidSourisType {
+                                            found: ty,
+                                            cause: InvalidSourisTypeError::InvalidData,
+                                        })
+                                    }
+                                } else {
+                                    Err(ValueSerError::InvalidSourisType {
+                                        found: ty,
+                                        cause: InvalidSourisTypeError::NotFound,
+                                    })
+                                }
+                            }
+                            #[cfg(feature = "uuid")]
+                            ValueTy::Uuid => {
+                                if let Some(SJValue::String(uuid)) = obj.get("uuid") {
+                                    if let Ok(uuid) = Uuid::try_parse(uuid) {
+                                        Ok(Value::Uuid(uuid))
+                                    } else {
+                                        Err(ValueSerError::InvalidSourisType {
+                                            found: ty,
+                                            cause: InvalidSourisTypeError::InvalidData,
+                                        })
+                                    }
+                                } else {
+                                    Err(ValueSerError::InvalidSourisType {
+                                        found: ty,
+                                        cause: InvalidSourisTypeError::NotFound,
+                                    })
+                                }
+                            }
+                            #[cfg(feature = "regex")]
+                            ValueTy::Regex => {
+                                if let Some(SJValue::String(pattern)) = obj.get("regex") {
+                                    if let Ok(regex) = Regex::new(pattern) {
+                                        Ok(Value::Regex(regex))
+                                    } else {
+                                        Err(ValueSerError::InvalidSourisType {
+                                            found: ty,
+                                            cause: InvalidSourisTypeError::InvalidData,
+                                        })
+                                    }
+                                } else {
+                                    Err(ValueSerError::InvalidSourisType {
+                                        found: ty,
+                                        cause: InvalidSourisTypeError::NotFound,
+                                    })
+                                }
+                            }
+                            ValueTy::OrderedMap => {
+                                if let Some(SJValue::Array(entries)) = obj.get("entries") {
+                                    let mut map = Vec::with_capacity(entries.len());
+                                    for entry in entries {
+                                        let Some([SJValue::String(k), v]) =
+                                            entry.as_array().map(Vec::as_slice)
+                                        else {
+                                            return Err(ValueSerError::InvalidSourisType {
+                                                found: ty,
+                                                cause: InvalidSourisTypeError::InvalidData,
+                                            });
+                                        };
+                                        map.push((k.clone(), Value::convert_from_json(v.clone())?));
+                                    }
+                                    Ok(Value::OrderedMap(map))
+                                } else {
+                                    Err(ValueSerError::InvalidSourisType {
+                                        found: ty,
+                                        cause: InvalidSourisTypeError::NotFound,
+                                    })
+                                }
+                            }
+                            ValueTy::Tagged => {
+                                let mut contents =
+                                    obj.iter().filter(|(k, _)| k.as_str() != "souris_type");
+                                match (contents.next(), contents.next()) {
+                                    (Some((tag, value)), None) => Ok(Value::Tagged {
+                                        tag: tag.clone(),
+                                        value: Box::new(Value::convert_from_json(value.clone())?),
+                                    }),
+                                    (None, _) => Err(ValueSerError::InvalidSourisType {
+                                        found: ty,
+                                        cause: InvalidSourisTypeError::NotFound,
+                                    }),
+                                    (Some(_), Some(_)) => Err(ValueSerError::InvalidSourisType {
+                                        found: ty,
+                                        cause: InvalidSourisTypeError::InvalidData,
+                                    }),
+                                }
+                            }
                             _ => Err(ValueSerError::InvalidSourisType {
                                 found: ty,
                                 cause: InvalidSourisTypeError::NoSourisTypeApplicable,
@@ -902,6 +2483,220 @@ impl Value {
     }
 }
 
+///One step in a path through nested [`Value::Map`]s and [`Value::Array`]s, used by [`Value::deep_get`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PathSegment {
+    ///A key into a [`Value::Map`] or [`Value::OrderedMap`].
+    Key(String),
+    ///An index into a [`Value::Array`].
+    Index(usize),
+}
+
+impl From<&str> for PathSegment {
+    fn from(value: &str) -> Self {
+        Self::Key(value.into())
+    }
+}
+impl From<String> for PathSegment {
+    fn from(value: String) -> Self {
+        Self::Key(value)
+    }
+}
+impl From<usize> for PathSegment {
+    fn from(value: usize) -> Self {
+        Self::Index(value)
+    }
+}
+
+impl Value {
+    ///Follows a path through nested [`Value::Map`]s and [`Value::Array`]s, returning the value at the end.
+    ///
+    /// Returns [`None`] if any part of the path doesn't exist, or if a [`PathSegment::Key`] is used on something that isn't a [`Value::Map`] or [`Value::OrderedMap`] (or a [`PathSegment::Index`] on something that isn't a [`Value::Array`]). An empty path returns the value itself.
+    ///
+    /// ```rust
+    /// use sourisdb::values::{PathSegment, Value};
+    ///
+    /// let value = Value::Map([("a".to_string(), Value::Array(vec![Value::from(1_u8)]))].into_iter().collect());
+    ///
+    /// assert_eq!(value.deep_get(&["a".into(), 0.into()]), Some(&Value::from(1_u8)));
+    /// assert_eq!(value.deep_get(&[PathSegment::Index(0)]), None);
+    /// ```
+    #[must_use]
+    pub fn deep_get(&self, path: &[PathSegment]) -> Option<&Value> {
+        path.iter()
+            .try_fold(self, |current, segment| match (current, segment) {
+                (Value::Map(m), PathSegment::Key(k)) => m.get(k),
+                (Value::OrderedMap(m), PathSegment::Key(k)) => {
+                    m.iter().find(|(key, _)| key == k).map(|(_, v)| v)
+                }
+                (Value::Array(a), PathSegment::Index(i)) => a.get(*i),
+                _ => None,
+            })
+    }
+
+    ///Follows a path through nested [`Value::Map`]s and [`Value::Array`]s, returning a mutable reference to the value at the end.
+    ///
+    /// See [`Value::deep_get`] for more details.
+    #[must_use]
+    pub fn deep_get_mut(&mut self, path: &[PathSegment]) -> Option<&mut Value> {
+        path.iter()
+            .try_fold(self, |current, segment| match (current, segment) {
+                (Value::Map(m), PathSegment::Key(k)) => m.get_mut(k),
+                (Value::OrderedMap(m), PathSegment::Key(k)) => {
+                    m.iter_mut().find(|(key, _)| key == k).map(|(_, v)| v)
+                }
+                (Value::Array(a), PathSegment::Index(i)) => a.get_mut(*i),
+                _ => None,
+            })
+    }
+
+    ///Looks up a value using a [JSON Pointer](https://tools.ietf.org/html/rfc6901) like `/a/0/b`, mirroring [`serde_json::Value::pointer`].
+    ///
+    /// Each segment is used as a key for [`Value::Map`]s and [`Value::OrderedMap`]s and parsed as an index for [`Value::Array`]s. `~1` and `~0` are unescaped to `/` and `~` respectively. An empty pointer returns the value itself, and any pointer not starting with `/` returns [`None`].
+    ///
+    /// ```rust
+    /// use sourisdb::values::Value;
+    ///
+    /// let value = Value::Map([("a".to_string(), Value::Array(vec![Value::from(1_u8)]))].into_iter().collect());
+    ///
+    /// assert_eq!(value.pointer("/a/0"), Some(&Value::from(1_u8)));
+    /// assert_eq!(value.pointer("/a/1"), None);
+    /// assert_eq!(value.pointer(""), Some(&value));
+    /// ```
+    #[must_use]
+    pub fn pointer(&self, pointer: &str) -> Option<&Value> {
+        Self::parse_pointer(pointer)?.try_fold(self, |current, segment| match current {
+            Value::Map(m) => m.get(&segment),
+            Value::OrderedMap(m) => m.iter().find(|(k, _)| *k == segment).map(|(_, v)| v),
+            Value::Array(a) => a.get(segment.parse::<usize>().ok()?),
+            _ => None,
+        })
+    }
+
+    ///Looks up a value using a [JSON Pointer](https://tools.ietf.org/html/rfc6901), returning a mutable reference to it.
+    ///
+    /// See [`Value::pointer`] for more details.
+    #[must_use]
+    pub fn pointer_mut(&mut self, pointer: &str) -> Option<&mut Value> {
+        Self::parse_pointer(pointer)?.try_fold(self, |current, segment| match current {
+            Value::Map(m) => m.get_mut(&segment),
+            Value::OrderedMap(m) => m.iter_mut().find(|(k, _)| *k == segment).map(|(_, v)| v),
+            Value::Array(a) => a.get_mut(segment.parse::<usize>().ok()?),
+            _ => None,
+        })
+    }
+
+    ///Splits a JSON Pointer into its unescaped segments, or [`None`] if it is invalid.
+    fn parse_pointer(pointer: &str) -> Option<impl Iterator<Item = String> + '_> {
+        if !pointer.is_empty() && !pointer.starts_with('/') {
+            return None;
+        }
+
+        Some(
+            pointer
+                .split('/')
+                .skip(1)
+                .map(|segment| segment.replace("~1", "/").replace("~0", "~")),
+        )
+    }
+}
+
+impl Value {
+    ///Visits this value and then every value nested inside it, depth-first. Elements of a [`Value::Array`] and values of a [`Value::OrderedMap`] are visited in order, and the values (but not keys) of a [`Value::Map`] are visited in an arbitrary order.
+    ///
+    /// ```rust
+    /// use sourisdb::values::Value;
+    ///
+    /// let value = Value::Array(vec![
+    ///     Value::String("a".into()),
+    ///     Value::Array(vec![Value::String("b".into()), Value::Null(())]),
+    /// ]);
+    ///
+    /// let mut strings = vec![];
+    /// value.walk(&mut |v| {
+    ///     if let Some(s) = v.as_str() {
+    ///         strings.push(s.clone());
+    ///     }
+    /// });
+    ///
+    /// assert_eq!(strings, ["a", "b"]);
+    /// ```
+    pub fn walk(&self, visitor: &mut impl FnMut(&Value)) {
+        visitor(self);
+
+        match self {
+            Value::Array(a) => {
+                for v in a {
+                    v.walk(visitor);
+                }
+            }
+            Value::Map(m) => {
+                for v in m.values() {
+                    v.walk(visitor);
+                }
+            }
+            Value::OrderedMap(m) => {
+                for (_, v) in m {
+                    v.walk(visitor);
+                }
+            }
+            Value::Tagged { value, .. } => value.walk(visitor),
+            _ => {}
+        }
+    }
+
+    ///Visits this value and then every value nested inside it mutably, depth-first in the same order as [`Value::walk`].
+    ///
+    /// Each value is visited before its children, so if the visitor replaces a value, the replacement's children are what get visited next.
+    pub fn walk_mut(&mut self, visitor: &mut impl FnMut(&mut Value)) {
+        visitor(self);
+
+        match self {
+            Value::Array(a) => {
+                for v in a {
+                    v.walk_mut(visitor);
+                }
+            }
+            Value::Map(m) => {
+                for v in m.values_mut() {
+                    v.walk_mut(visitor);
+                }
+            }
+            Value::OrderedMap(m) => {
+                for (_, v) in m {
+                    v.walk_mut(visitor);
+                }
+            }
+            Value::Tagged { value, .. } => value.walk_mut(visitor),
+            _ => {}
+        }
+    }
+
+    ///Recursively replaces every [`Value::JSON`] (including ones nested inside [`Value::Array`]s and [`Value::Map`]s) with native variants using [`Value::convert_from_json`], so that they can use the more compact encoding.
+    ///
+    /// Any JSON that can't be converted (eg. because of an invalid `souris_type`) is left as a [`Value::JSON`].
+    ///
+    /// ```rust
+    /// use sourisdb::{serde_json::json, values::Value};
+    ///
+    /// let value = Value::Array(vec![Value::JSON(json!({"a": [1, true]}))]);
+    /// let flattened = value.flatten_json();
+    ///
+    /// assert_eq!(flattened.pointer("/0/a/1"), Some(&Value::Boolean(true)));
+    /// ```
+    #[must_use]
+    pub fn flatten_json(mut self) -> Value {
+        self.walk_mut(&mut |v| {
+            if let Value::JSON(j) = v {
+                if let Ok(converted) = Value::convert_from_json(j.clone()) {
+                    *v = converted;
+                }
+            }
+        });
+        self
+    }
+}
+
 impl Value {
     ///Converts a [`Value`] into a [`ValueTy`]
     pub(crate) const fn as_ty(&self) -> ValueTy {
@@ -913,6 +2708,7 @@ impl Value {
             Self::Integer(_) => ValueTy::Integer,
             Self::Imaginary(_) => ValueTy::Imaginary,
             Self::Timestamp(_) => ValueTy::Timestamp,
+            Self::ZonedTimestamp(_) => ValueTy::ZonedTimestamp,
             Self::JSON(_) => ValueTy::JSON,
             Self::Map(_) => ValueTy::Map,
             Self::Array(_) => ValueTy::Array,
@@ -922,27 +2718,56 @@ impl Value {
             Self::Ipv4Addr(_) => ValueTy::Ipv4Addr,
             Self::Ipv6Addr(_) => ValueTy::Ipv6Addr,
             Self::SingleFloat(_) => ValueTy::SingleFloat,
+            #[cfg(feature = "uuid")]
+            Self::Uuid(_) => ValueTy::Uuid,
+            Self::Duration { .. } => ValueTy::Duration,
+            #[cfg(feature = "regex")]
+            Self::Regex(_) => ValueTy::Regex,
+            Self::OrderedMap(_) => ValueTy::OrderedMap,
+            Self::Tagged { .. } => ValueTy::Tagged,
+            Self::Unknown(_) => ValueTy::Unknown,
         }
     }
 
+    ///How deeply [`Value::Array`]s, [`Value::Map`]s and [`Value::OrderedMap`]s can be nested when using [`Value::deser`] - see [`Value::deser_with_max_depth`].
+    pub const DEFAULT_MAX_DEPTH: usize = 128;
+
+    ///The most elements we'll pre-allocate space for when deserialising a [`Value::Map`] or [`Value::OrderedMap`], so that a large length doesn't immediately cause a huge allocation.
+    const MAX_PREALLOCATED_ELEMENTS: usize = 1024;
+
     ///[`Value::Map`]s and [`Value::Array`]s have special optimisations for storing the lengths of very short lists inside the 4 bits at the end of the type. This deserialises them.
+    ///
+    /// Every element takes at least one byte (two for maps as they also need a key), so lengths which couldn't possibly fit inside the remaining bytes return [`ValueSerError::NotEnoughBytes`].
     pub(crate) fn deser_array_or_map_len(
         byte: u8,
         input: &mut Cursor<u8>,
         expected_type: ValueTy,
     ) -> Result<usize, ValueSerError> {
-        let ty = ValueTy::try_from((byte & 0b1111_0000) >> 4)?;
+        let ty = ValueTy::from_type_byte(byte)?;
         if ty == expected_type {
             let len = {
                 if (byte & 0b0000_0001) > 0 {
                     // we used an integer
-                    Integer::deser(SignedState::Unsigned, input)?.try_into()?
+                    Integer::deser(SignedState::Unsigned, input)?
+                        .as_usize()
+                        .ok_or(ValueSerError::LengthTooLarge(
+                            if expected_type == ValueTy::Map {
+                                "Map"
+                            } else {
+                                "Array"
+                            },
+                        ))?
                 } else {
                     //we encoded it in the byte
                     ((byte & 0b0000_1110) >> 1) as usize
                 }
             };
 
+            let min_bytes_per_element = if expected_type == ValueTy::Map { 2 } else { 1 };
+            if len.saturating_mul(min_bytes_per_element) > input.items_remaining() {
+                return Err(ValueSerError::NotEnoughBytes);
+            }
+
             Ok(len)
         } else {
             Err(ValueSerError::UnexpectedValueType {
@@ -952,14 +2777,120 @@ impl Value {
         }
     }
 
+    ///Serialises the type byte and length which come before the entries of a [`Value::Map`] with `len` entries.
+    pub(crate) fn ser_map_len(len: usize, res: &mut Vec<u8>) {
+        let mut ty = ValueTy::Map.to_type_byte();
+
+        #[allow(clippy::cast_possible_truncation)]
+        if len < ((1_usize << 3) - 1) {
+            ty |= (len as u8) << 1;
+            res.push(ty);
+        } else {
+            let (_, integer_bytes) = Integer::from(len).ser();
+            ty |= 0b1; //to signify that we used an integer
+            res.push(ty);
+            res.extend(integer_bytes);
+        }
+    }
+
+    ///Serialises a map in exactly the same way as [`Value::Map`], without needing the map to be inside a [`Value`].
+    pub(crate) fn ser_map(
+        m: &HashMap<String, Value>,
+        huffman: Option<&Huffman<char>>,
+        dict: Option<&Dict>,
+        res: &mut Vec<u8>,
+    ) {
+        Self::ser_map_len(m.len(), res);
+
+        //sorted so that equal maps always serialise to the same bytes
+        let mut entries: Vec<_> = m.iter().collect();
+        entries.sort_unstable_by_key(|(k, _)| *k);
+
+        for (k, v) in entries {
+            res.extend(Value::String(k.clone()).ser(huffman));
+            res.extend(v.ser_with_dict(huffman, dict));
+        }
+    }
+
+    ///Gets a cheap estimate of how many bytes this value will take up when serialised, without actually serialising it.
+    ///
+    /// This is a lower bound, with one exception - strings (including keys, JSON and timezones) are assumed not to be huffman-encoded, as that can only be known by encoding them. Values which get compressed (like [`Value::Binary`]) or which are too expensive to measure (like [`Value::JSON`]) just count their minimum size.
+    ///
+    /// ```rust
+    /// use sourisdb::values::Value;
+    ///
+    /// let value = Value::Array(vec![Value::Null(()), Value::String("hello".into())]);
+    /// assert!(value.size_hint() <= value.ser(None).len());
+    /// ```
+    #[must_use]
+    pub fn size_hint(&self) -> usize {
+        //type byte, length and then contents
+        fn string_hint(s: &str) -> usize {
+            2 + s.len()
+        }
+
+        match self {
+            Self::Boolean(_) | Self::Null(()) => 1,
+            Self::Character(_) | Self::Integer(_) | Self::Binary(_) => 2,
+            Self::Imaginary(Imaginary::CartesianForm { .. }) | Self::JSON(_) => 3,
+            Self::Imaginary(Imaginary::PolarForm { .. }) | Self::Ipv6Addr(_) => 17,
+            Self::String(s) => string_hint(s),
+            Self::Timestamp(_) => 8,
+            Self::ZonedTimestamp(dt) => 10 + string_hint(dt.timezone().name()),
+            Self::Map(m) => {
+                1 + m
+                    .iter()
+                    .map(|(k, v)| string_hint(k) + v.size_hint())
+                    .sum::<usize>()
+            }
+            Self::OrderedMap(m) => {
+                2 + m
+                    .iter()
+                    .map(|(k, v)| string_hint(k) + v.size_hint())
+                    .sum::<usize>()
+            }
+            Self::Array(a) => 1 + a.iter().map(Value::size_hint).sum::<usize>(),
+            Self::Timezone(tz) => 1 + string_hint(tz.name()),
+            Self::Ipv4Addr(_) | Self::SingleFloat(_) => 5,
+            Self::DoubleFloat(_) => 9,
+            #[cfg(feature = "uuid")]
+            Self::Uuid(_) => 18,
+            #[cfg(feature = "regex")]
+            Self::Regex(r) => 2 + string_hint(r.as_str()),
+            Self::Duration { .. } => 5,
+            Self::Tagged { tag, value } => 2 + string_hint(tag) + value.size_hint(),
+            Self::Unknown(UnknownValue { bytes, .. }) => 2 + bytes.len(),
+        }
+    }
+
     ///Serialises a [`Value`] into bytes.
     ///
-    /// If a [`Huffman`] is passed in, it will be used to serialise the key names in a [`Map`] and all other Strings, including JSON.
-    #[allow(clippy::too_many_lines)]
+    /// If a [`Huffman`] is passed in, it will be used to serialise the key names in a [`Map`] and all other Strings, including JSON. It is never used inside extended types (see [`ValueTy::to_type_byte`]).
+    #[must_use]
     pub fn ser(&self, huffman: Option<&Huffman<char>>) -> Vec<u8> {
-        let mut res = vec![];
+        self.ser_with_dict(huffman, None)
+    }
+
+    ///Serialises a [`Value`] in the same way as [`Value::ser`], but compresses any [`Value::Binary`]s using the dictionary of the store they're in whenever that is smaller. Like the huffman tree, the dictionary is never used inside extended types.
+    #[allow(clippy::too_many_lines)]
+    pub(crate) fn ser_with_dict(
+        &self,
+        huffman: Option<&Huffman<char>>,
+        dict: Option<&Dict>,
+    ) -> Vec<u8> {
+        //a reader which doesn't know an extended type keeps its bytes as they are and writes them back out with whatever tree it has then, so they can't depend on this one
+        let (huffman, dict) = if self.as_ty().is_extended() {
+            (None, None)
+        } else {
+            (huffman, dict)
+        };
+        let mut res = Vec::with_capacity(self.size_hint());
 
-        let mut ty = u8::from(self.as_ty()) << 4;
+        let mut ty = if let Self::Unknown(UnknownValue { ty, .. }) = self {
+            ValueTy::type_byte_for_discriminant(*ty)
+        } else {
+            self.as_ty().to_type_byte()
+        };
 
         match self {
             Self::Character(ch) => {
@@ -987,9 +2918,17 @@ impl Value {
                 }
             }
             Self::Binary(b) => {
-                let (ct, bytes) = b.ser();
+                let (ct, mut bytes) = b.ser();
                 ty |= u8::from(ct);
 
+                if let Some(dict) = dict {
+                    let with_dict = lz_with_known_dict(b, dict);
+                    if with_dict.len() < bytes.len() {
+                        ty = (ty & !0b1111) | STORE_DICT_COMPRESSION;
+                        bytes = with_dict;
+                    }
+                }
+
                 res.push(ty);
                 res.extend(bytes.iter());
             }
@@ -1037,6 +2976,12 @@ impl Value {
                 res.extend(sec.iter());
                 res.extend(nanos.iter());
             }
+            Self::ZonedTimestamp(dt) => {
+                //the timestamp needs its own type byte for the year sign bit, so reuse the whole value
+                res.push(ty);
+                res.extend(Value::Timestamp(dt.naive_utc()).ser(huffman));
+                res.extend(Value::String(dt.timezone().name().into()).ser(huffman));
+            }
             Self::JSON(v) => {
                 res.push(ty);
                 res.extend(Value::String(v.to_string()).ser(huffman));
@@ -1052,38 +2997,22 @@ impl Value {
                 res.push(ty);
                 res.extend(f.to_le_bytes());
             }
-            Self::Map(m) => {
+            Self::Map(m) => Self::ser_map(m, huffman, dict, &mut res),
+            Self::Array(a) => {
+                // yes, DRY, but only 2 instances right next to each other so not too bad
                 #[allow(clippy::cast_possible_truncation)]
-                if m.len() < ((1_usize << 3) - 1) {
-                    ty |= (m.len() as u8) << 1;
+                if a.len() < ((1_usize << 3) - 1) {
+                    ty |= (a.len() as u8) << 1;
                     res.push(ty);
                 } else {
-                    let (_, integer_bytes) = Integer::from(m.len()).ser();
-                    ty |= 0b1; //to signify that we used an integer
-                    res.push(ty);
-                    res.extend(integer_bytes);
-                }
-
-                for (k, v) in m.clone() {
-                    res.extend(Value::String(k).ser(huffman));
-                    res.extend(v.ser(huffman));
-                }
-            }
-            Self::Array(a) => {
-                // yes, DRY, but only 2 instances right next to each other so not too bad
-                #[allow(clippy::cast_possible_truncation)]
-                if a.len() < ((1_usize << 3) - 1) {
-                    ty |= (a.len() as u8) << 1;
-                    res.push(ty);
-                } else {
-                    let (_, integer_bytes) = Integer::from(a.len()).ser();
+                    let (_, integer_bytes) = Integer::from(a.len()).ser();
                     ty |= 0b1; //to signify that we used an integer
                     res.push(ty);
                     res.extend(integer_bytes);
                 }
 
                 for v in a.clone() {
-                    res.extend(v.ser(huffman));
+                    res.extend(v.ser_with_dict(huffman, dict));
                 }
             }
             Self::Timezone(tz) => {
@@ -1099,6 +3028,55 @@ impl Value {
                 res.push(ty);
                 res.extend(a.segments().into_iter().flat_map(u16::to_le_bytes));
             }
+            #[cfg(feature = "uuid")]
+            Self::Uuid(u) => {
+                res.push(ty);
+                res.extend(u.as_u128().to_le_bytes());
+            }
+            #[cfg(feature = "regex")]
+            Self::Regex(r) => {
+                res.push(ty);
+                res.extend(Value::String(r.as_str().to_string()).ser(huffman));
+            }
+            Self::Duration {
+                seconds,
+                nanoseconds,
+            } => {
+                //no spare bits in the type byte for the signed state, so use a whole integer value
+                let (_, nanoseconds) = Integer::from(*nanoseconds).ser();
+
+                res.push(ty);
+                res.extend(Value::Integer(*seconds).ser(huffman));
+                res.extend(nanoseconds);
+            }
+            Self::OrderedMap(m) => {
+                //the length can't go in the type byte as it's an extended type
+                let (_, len) = Integer::from(m.len()).ser();
+
+                res.push(ty);
+                res.extend(len);
+                for (k, v) in m {
+                    res.extend(Value::String(k.clone()).ser(huffman));
+                    res.extend(v.ser(huffman));
+                }
+            }
+            Self::Tagged { tag, value } => {
+                res.push(ty);
+                res.extend(Value::String(tag.clone()).ser(huffman));
+                res.extend(value.ser(huffman));
+            }
+            Self::Unknown(UnknownValue { bytes, .. }) => {
+                res.push(ty);
+                res.extend(bytes);
+            }
+        }
+
+        if self.as_ty().is_extended() {
+            //extended types are length-prefixed so that they can be skipped
+            let contents = res.split_off(1);
+            let (_, len) = Integer::from(contents.len()).ser();
+            res.extend(len);
+            res.extend(contents);
         }
 
         res
@@ -1113,16 +3091,235 @@ impl Value {
     /// - [`IntegerSerError`] if we cannot deserialise an [`Integer`]/[`Imaginary`]
     /// - [`BinarySerError::NoCompressionTypeFound`] if we cannot find the compression type
     /// - [`BinarySerError`] if we cannot deserialise binary
-    /// - [`ValueSerError::UnexpectedValueType`] if we expected to find one type but found another. This can be found in the [`Value::Timezone`] deserialisation where we immediately try to deserialise a [`Value::String`].
-    #[allow(clippy::many_single_char_names, clippy::too_many_lines)]
+    /// - [`ValueSerError::UnexpectedValueType`] if we expected to find one type but found another. This can be found in the [`Value::ZonedTimestamp`] deserialisation where we immediately try to deserialise a [`Value::Timestamp`].
+    /// - [`ValueSerError::JsonInnerString`] or [`ValueSerError::TimezoneInnerString`] if we couldn't deserialise the [`Value::String`] inside a [`Value::JSON`] or [`Value::Timezone`].
+    /// - [`ValueSerError::LengthTooLarge`] if the length of something (eg. a string or an array) is too big to fit in a [`usize`].
+    /// - [`ValueSerError::MaxDepthExceeded`] if containers are nested more than [`Value::DEFAULT_MAX_DEPTH`] deep.
     pub fn deser(
         bytes: &mut Cursor<u8>,
         huffman: Option<&Huffman<char>>,
+    ) -> Result<Self, ValueSerError> {
+        Self::deser_inner(bytes, DeserOptions::new(huffman), Self::DEFAULT_MAX_DEPTH)
+    }
+
+    ///Deserialises bytes into a [`Value`], allowing [`Value::Array`]s, [`Value::Map`]s and [`Value::OrderedMap`]s to be nested at most `max_depth` deep.
+    ///
+    /// Deserialising nested containers is recursive, so this stops malicious input from overflowing the stack. [`Value::deser`] uses [`Value::DEFAULT_MAX_DEPTH`], and this can be used to raise it for trusted input (or lower it if there isn't much stack to spare).
+    ///
+    /// ```rust
+    /// use sourisdb::{utilities::cursor::Cursor, values::{Value, ValueSerError}};
+    ///
+    /// let nested = Value::Array(vec![Value::Array(vec![Value::Null(())])]);
+    /// let bytes = nested.ser(None);
+    ///
+    /// assert!(Value::deser_with_max_depth(&mut Cursor::new(&bytes), None, 2).is_ok());
+    /// assert!(matches!(
+    ///     Value::deser_with_max_depth(&mut Cursor::new(&bytes), None, 1),
+    ///     Err(ValueSerError::MaxDepthExceeded)
+    /// ));
+    /// ```
+    ///
+    /// # Errors
+    /// See [`Value::deser`].
+    pub fn deser_with_max_depth(
+        bytes: &mut Cursor<u8>,
+        huffman: Option<&Huffman<char>>,
+        max_depth: usize,
+    ) -> Result<Self, ValueSerError> {
+        Self::deser_inner(bytes, DeserOptions::new(huffman), max_depth)
+    }
+
+    ///Deserialises bytes into a [`Value`], keeping any values of types this version of the crate doesn't know about as [`Value::Unknown`] rather than failing.
+    ///
+    /// This only works for extended types (see [`ValueTy`]), as they are the only types which record their length - however, all types added after the first 16 are extended types. [`Value::Unknown`]s are serialised back out exactly as they were read in.
+    ///
+    /// # Errors
+    /// See [`Value::deser`] - the only difference is that [`ValueSerError::InvalidType`] will not be returned for unknown extended types.
+    pub fn deser_skip_unknown(
+        bytes: &mut Cursor<u8>,
+        huffman: Option<&Huffman<char>>,
+    ) -> Result<Self, ValueSerError> {
+        Self::deser_inner(
+            bytes,
+            DeserOptions {
+                skip_unknown: true,
+                ..DeserOptions::new(huffman)
+            },
+            Self::DEFAULT_MAX_DEPTH,
+        )
+    }
+
+    ///Deserialises an extended type (see [`ValueTy::to_type_byte`]), which has the length of its contents after the type byte.
+    #[allow(clippy::too_many_lines)]
+    fn deser_extended(
+        byte: u8,
+        bytes: &mut Cursor<u8>,
+        options: DeserOptions,
+        depth_left: usize,
+    ) -> Result<Self, ValueSerError> {
+        let len = Integer::deser(SignedState::Unsigned, bytes)?
+            .as_usize()
+            .ok_or(ValueSerError::LengthTooLarge("Extended value"))?;
+        let contents = bytes.read_checked(len)?;
+        let bytes = &mut Cursor::new(&contents);
+
+        let ty = match ValueTy::from_type_byte(byte) {
+            Ok(ty) => ty,
+            Err(_) if options.skip_unknown => {
+                //extended type bytes always give a discriminant between 16 and 30, and this one isn't known
+                return Ok(Self::Unknown(UnknownValue {
+                    ty: ValueTy::discriminant_from_type_byte(byte),
+                    bytes: contents.to_vec(),
+                }));
+            }
+            Err(e) => return Err(e),
+        };
+
+        Ok(match ty {
+            #[cfg(feature = "uuid")]
+            ValueTy::Uuid => {
+                let bytes = bytes.read_exact_checked()?;
+                Self::Uuid(Uuid::from_u128(u128::from_le_bytes(*bytes)))
+            }
+            #[cfg(feature = "regex")]
+            ValueTy::Regex => {
+                let val = Value::deser_nested_scalar(bytes, options, ValueTy::String)?;
+                let Value::String(pattern) = val else {
+                    return Err(ValueSerError::UnexpectedValueType {
+                        found: val.as_ty(),
+                        expected: ValueTy::String,
+                    });
+                };
+                Self::Regex(Regex::new(&pattern)?)
+            }
+            ValueTy::Duration => {
+                let val = Value::deser_nested_scalar(bytes, options, ValueTy::Integer)?;
+                let Value::Integer(seconds) = val else {
+                    return Err(ValueSerError::UnexpectedValueType {
+                        found: val.as_ty(),
+                        expected: ValueTy::Integer,
+                    });
+                };
+                let nanoseconds = Integer::deser(SignedState::Unsigned, bytes)?
+                    .as_u32()
+                    .ok_or(ValueSerError::InvalidDateOrTime)?;
+                if nanoseconds >= 1_000_000_000 {
+                    return Err(ValueSerError::InvalidDateOrTime);
+                }
+
+                Self::Duration {
+                    seconds,
+                    nanoseconds,
+                }
+            }
+            ValueTy::OrderedMap => {
+                let depth_left = depth_left
+                    .checked_sub(1)
+                    .ok_or(ValueSerError::MaxDepthExceeded)?;
+                let len = Integer::deser(SignedState::Unsigned, bytes)?
+                    .as_usize()
+                    .ok_or(ValueSerError::LengthTooLarge("Ordered map"))?;
+                //every entry needs at least a key and a value
+                if len.saturating_mul(2) > bytes.items_remaining() {
+                    return Err(ValueSerError::NotEnoughBytes);
+                }
+
+                let mut map = Vec::with_capacity(len.min(Self::MAX_PREALLOCATED_ELEMENTS));
+                for _ in 0..len {
+                    let key = Value::deser_inner(bytes, options, depth_left)?;
+                    let Value::String(key) = key else {
+                        return Err(ValueSerError::UnexpectedValueType {
+                            found: key.as_ty(),
+                            expected: ValueTy::String,
+                        });
+                    };
+                    let value = Value::deser_inner(bytes, options, depth_left)?;
+                    map.push((key, value));
+                }
+
+                Self::OrderedMap(map)
+            }
+            ValueTy::ZonedTimestamp => {
+                let val = Value::deser_nested_scalar(bytes, options, ValueTy::Timestamp)?;
+                let Value::Timestamp(utc) = val else {
+                    return Err(ValueSerError::UnexpectedValueType {
+                        found: val.as_ty(),
+                        expected: ValueTy::Timestamp,
+                    });
+                };
+                let val = Value::deser_nested_scalar(bytes, options, ValueTy::String)?;
+                let Value::String(tz) = val else {
+                    return Err(ValueSerError::UnexpectedValueType {
+                        found: val.as_ty(),
+                        expected: ValueTy::String,
+                    });
+                };
+                let tz = Tz::from_str(&tz)?;
+
+                Self::ZonedTimestamp(tz.from_utc_datetime(&utc))
+            }
+            ValueTy::Tagged => {
+                let depth_left = depth_left
+                    .checked_sub(1)
+                    .ok_or(ValueSerError::MaxDepthExceeded)?;
+                let tag = Value::deser_nested_scalar(bytes, options, ValueTy::String)?;
+                let Value::String(tag) = tag else {
+                    return Err(ValueSerError::UnexpectedValueType {
+                        found: tag.as_ty(),
+                        expected: ValueTy::String,
+                    });
+                };
+                let value = Value::deser_inner(bytes, options, depth_left)?;
+
+                Self::Tagged {
+                    tag,
+                    value: Box::new(value),
+                }
+            }
+            _ => unreachable!("only extended types have the extended type marker"),
+        })
+    }
+
+    ///Deserialises a value which must be of type `expected`, checking the type byte before reading anything else.
+    ///
+    /// This is for values held inside wrappers like [`Value::JSON`] - reading them with [`Value::deser_inner`] would let wrappers nest inside each other without ever reaching the depth limit, and overflow the stack. `expected` can't be a container, so nothing can be nested inside it.
+    fn deser_nested_scalar(
+        bytes: &mut Cursor<u8>,
+        options: DeserOptions,
+        expected: ValueTy,
+    ) -> Result<Self, ValueSerError> {
+        debug_assert!(!expected.is_container());
+
+        let &[byte] = bytes.peek_exact().ok_or(ValueSerError::NotEnoughBytes)?;
+        let found = ValueTy::from_type_byte(byte)?;
+        if found != expected {
+            return Err(ValueSerError::UnexpectedValueType { found, expected });
+        }
+
+        Self::deser_inner(
+            bytes,
+            DeserOptions {
+                skip_unknown: false,
+                ..options
+            },
+            0,
+        )
+    }
+
+    ///Deserialises one value. `depth_left` is how many more containers can be nested inside this value - see [`Value::deser_with_max_depth`].
+    #[allow(clippy::many_single_char_names, clippy::too_many_lines)]
+    pub(crate) fn deser_inner(
+        bytes: &mut Cursor<u8>,
+        options: DeserOptions,
+        depth_left: usize,
     ) -> Result<Self, ValueSerError> {
         let byte = bytes.next().ok_or(ValueSerError::NotEnoughBytes).copied()?;
 
-        let ty = (byte & 0b1111_0000) >> 4;
-        let ty = ValueTy::try_from(ty)?;
+        if ValueTy::is_extended_type_byte(byte) {
+            return Self::deser_extended(byte, bytes, options, depth_left);
+        }
+
+        let ty = ValueTy::from_type_byte(byte)?;
 
         //for lengths or single integers
 
@@ -1138,24 +3335,35 @@ impl Value {
                 Self::Imaginary(Imaginary::deser(magic_bits, bytes)?)
             }
             ValueTy::Character => {
-                let ch = char::from_u32(Integer::deser(SignedState::Unsigned, bytes)?.try_into()?)
+                let ch = Integer::deser(SignedState::Unsigned, bytes)?
+                    .as_u32()
+                    .and_then(char::from_u32)
                     .ok_or(ValueSerError::InvalidCharacter)?;
                 Self::Character(ch)
             }
             ValueTy::Timestamp => {
                 let year_signed_state = SignedState::try_from(byte & 0b0000_0001)?;
 
-                let year = Integer::deser(year_signed_state, bytes)?.try_into()?;
-                let month = Integer::deser(SignedState::Unsigned, bytes)?.try_into()?;
-                let day = Integer::deser(SignedState::Unsigned, bytes)?.try_into()?;
+                //parts which don't fit can't be valid either, so they get the same error as out-of-range parts
+                let year = Integer::deser(year_signed_state, bytes)?
+                    .as_i32()
+                    .ok_or(ValueSerError::InvalidDateOrTime)?;
+                let mut read_part = || -> Result<u32, ValueSerError> {
+                    Integer::deser(SignedState::Unsigned, bytes)?
+                        .as_u32()
+                        .ok_or(ValueSerError::InvalidDateOrTime)
+                };
+
+                let month = read_part()?;
+                let day = read_part()?;
 
                 let date = NaiveDate::from_ymd_opt(year, month, day)
                     .ok_or(ValueSerError::InvalidDateOrTime)?;
 
-                let hour = Integer::deser(SignedState::Unsigned, bytes)?.try_into()?;
-                let min = Integer::deser(SignedState::Unsigned, bytes)?.try_into()?;
-                let sec = Integer::deser(SignedState::Unsigned, bytes)?.try_into()?;
-                let ns = Integer::deser(SignedState::Unsigned, bytes)?.try_into()?;
+                let hour = read_part()?;
+                let min = read_part()?;
+                let sec = read_part()?;
+                let ns = read_part()?;
 
                 let time = NaiveTime::from_hms_nano_opt(hour, min, sec, ns)
                     .ok_or(ValueSerError::InvalidDateOrTime)?;
@@ -1165,98 +3373,108 @@ impl Value {
             ValueTy::String => {
                 if (byte & 0b1) > 0 {
                     //huffman-encoded
-                    let Some(huffman) = huffman else {
+                    let Some(huffman) = options.huffman else {
                         return Err(ValueSerError::NoHuffman);
                     };
                     let bits = Bits::deser(bytes)?;
                     Self::String(huffman.decode_string(bits)?)
                 } else {
-                    let len: usize = Integer::deser(SignedState::Unsigned, bytes)?.try_into()?;
-                    let str_bytes = bytes
-                        .read(len)
-                        .ok_or(ValueSerError::NotEnoughBytes)?
-                        .to_vec();
+                    let len = Integer::deser(SignedState::Unsigned, bytes)?
+                        .as_usize()
+                        .ok_or(ValueSerError::LengthTooLarge("String"))?;
+                    let str_bytes = bytes.read_checked(len)?.to_vec();
                     Self::String(String::from_utf8(str_bytes)?)
                 }
             }
             ValueTy::JSON => {
-                let val = Value::deser(bytes, huffman)?;
+                let val = Value::deser_nested_scalar(bytes, options, ValueTy::String)
+                    .map_err(|e| ValueSerError::JsonInnerString(Box::new(e)))?;
                 let Value::String(s) = val else {
-                    return Err(ValueSerError::UnexpectedValueType {
-                        found: val.as_ty(),
-                        expected: ValueTy::String,
-                    });
+                    return Err(ValueSerError::JsonInnerString(Box::new(
+                        ValueSerError::UnexpectedValueType {
+                            found: val.as_ty(),
+                            expected: ValueTy::String,
+                        },
+                    )));
                 };
                 let value: SJValue = serde_json::from_str(&s)?;
                 Self::JSON(value)
             }
             ValueTy::Binary => {
-                let ct = BinaryCompression::try_from(byte & 0b000_1111)?;
-                Self::Binary(BinaryData::deser(ct, bytes)?)
+                if byte & 0b000_1111 == STORE_DICT_COMPRESSION {
+                    let dict = options.dict.ok_or(BinarySerError::MissingDictionary)?;
+                    Self::Binary(BinaryData(un_lz_with_known_dict(bytes, dict)?))
+                } else {
+                    let ct = BinaryCompression::try_from(byte & 0b000_1111)?;
+                    Self::Binary(BinaryData::deser_with_max_run_len(
+                        ct,
+                        bytes,
+                        options.max_run_len,
+                    )?)
+                }
             }
             ValueTy::Boolean => Self::Boolean((byte & 0b0000_0001) > 0),
             ValueTy::Null => Self::Null(()),
             ValueTy::SingleFloat => {
-                let Some(bytes) = bytes.read_exact() else {
-                    return Err(ValueSerError::NotEnoughBytes);
-                };
-                Self::SingleFloat(f32::from_le_bytes(*bytes))
+                Self::SingleFloat(f32::from_le_bytes(*bytes.read_exact_checked()?))
             }
             ValueTy::DoubleFloat => {
-                let Some(bytes) = bytes.read_exact() else {
-                    return Err(ValueSerError::NotEnoughBytes);
-                };
-                Self::DoubleFloat(f64::from_le_bytes(*bytes))
+                Self::DoubleFloat(f64::from_le_bytes(*bytes.read_exact_checked()?))
             }
             ValueTy::Map => {
+                let depth_left = depth_left
+                    .checked_sub(1)
+                    .ok_or(ValueSerError::MaxDepthExceeded)?;
                 let len = Self::deser_array_or_map_len(byte, bytes, ty)?;
 
-                let mut map = HashMap::with_capacity(len);
+                let mut map = HashMap::with_capacity(len.min(Self::MAX_PREALLOCATED_ELEMENTS));
 
                 for _ in 0..len {
-                    let key = Value::deser(bytes, huffman)?;
+                    let key = Value::deser_inner(bytes, options, depth_left)?;
                     let Value::String(key) = key else {
                         return Err(ValueSerError::UnexpectedValueType {
                             found: key.as_ty(),
                             expected: ValueTy::String,
                         });
                     };
-                    let value = Value::deser(bytes, huffman)?;
+                    let value = Value::deser_inner(bytes, options, depth_left)?;
                     map.insert(key, value);
                 }
 
                 Value::Map(map)
             }
             ValueTy::Array => {
+                let depth_left = depth_left
+                    .checked_sub(1)
+                    .ok_or(ValueSerError::MaxDepthExceeded)?;
                 let len = Self::deser_array_or_map_len(byte, bytes, ty)?;
 
                 Value::Array(
                     (0..len)
-                        .map(|_| Value::deser(bytes, huffman))
+                        .map(|_| Value::deser_inner(bytes, options, depth_left))
                         .collect::<Result<_, _>>()?,
                 )
             }
             ValueTy::Timezone => {
-                let val = Value::deser(bytes, huffman)?;
+                let val = Value::deser_nested_scalar(bytes, options, ValueTy::String)
+                    .map_err(|e| ValueSerError::TimezoneInnerString(Box::new(e)))?;
                 let Value::String(val) = val else {
-                    return Err(ValueSerError::UnexpectedValueType {
-                        found: val.as_ty(),
-                        expected: ValueTy::String,
-                    });
+                    return Err(ValueSerError::TimezoneInnerString(Box::new(
+                        ValueSerError::UnexpectedValueType {
+                            found: val.as_ty(),
+                            expected: ValueTy::String,
+                        },
+                    )));
                 };
                 let tz = Tz::from_str(&val)?;
                 Self::Timezone(tz)
             }
             ValueTy::Ipv4Addr => {
-                let Some([a, b, c, d]) = bytes.read_exact() else {
-                    return Err(ValueSerError::NotEnoughBytes);
-                };
+                let [a, b, c, d] = bytes.read_exact_checked()?;
                 Self::Ipv4Addr(Ipv4Addr::new(*a, *b, *c, *d))
             }
             ValueTy::Ipv6Addr => {
-                let Some(bytes) = bytes.read_exact::<16>() else {
-                    return Err(ValueSerError::NotEnoughBytes);
-                };
+                let bytes = bytes.read_exact_checked::<16>()?;
 
                 let mut octets = [0_u16; 8];
                 for i in (0..8_usize).map(|x| x * 2) {
@@ -1266,6 +3484,7 @@ impl Value {
 
                 Self::Ipv6Addr(Ipv6Addr::new(a, b, c, d, e, f, g, h))
             }
+            _ => unreachable!("extended types are deserialised separately"),
         })
     }
 }
@@ -1273,16 +3492,26 @@ impl Value {
 #[cfg(test)]
 mod tests {
     use alloc::{
+        boxed::Box,
+        collections::BTreeMap,
         format,
         string::{String, ToString},
+        vec,
         vec::Vec,
     };
 
+    use chrono::TimeDelta;
+    use core::net::Ipv4Addr;
+    use hashbrown::HashMap;
     use proptest::{arbitrary::any, prop_assert_eq, proptest};
 
-    use super::Value;
+    use super::{PathSegment, UnknownValue, Value, ValueSerError, ValueTy};
     use crate::{
-        types::{binary::BinaryData, imaginary::Imaginary, integer::BiggestIntButSigned},
+        types::{
+            binary::BinaryData,
+            imaginary::Imaginary,
+            integer::{BiggestIntButSigned, Integer},
+        },
         utilities::cursor::Cursor,
     };
 
@@ -1364,4 +3593,905 @@ mod tests {
 
         //TODO: more tests :)
     }
+
+    #[cfg(feature = "uuid")]
+    proptest! {
+        #[test]
+        fn test_uuid (u in any::<u128>()) {
+            let v = Value::Uuid(uuid::Uuid::from_u128(u));
+
+            let bytes = v.ser(None);
+            prop_assert_eq!(bytes.len(), 18); //type, length, then contents
+            let out_value = Value::deser(&mut Cursor::new(&bytes), None).unwrap();
+            prop_assert_eq!(&v, &out_value);
+
+            let json = v.clone().convert_to_json(true).unwrap();
+            prop_assert_eq!(v, Value::convert_from_json(json).unwrap());
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn test_duration (seconds in any::<i64>(), nanoseconds in 0..1_000_000_000_u32) {
+            let td = TimeDelta::new(seconds / 1000, nanoseconds).unwrap();
+            let v = Value::from(td);
+
+            let bytes = v.ser(None);
+            let out_value = Value::deser(&mut Cursor::new(&bytes), None).unwrap();
+            prop_assert_eq!(&v, &out_value);
+            prop_assert_eq!(TimeDelta::try_from(out_value).unwrap(), td);
+
+            let json = v.clone().convert_to_json(true).unwrap();
+            prop_assert_eq!(v, Value::convert_from_json(json).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_size_hint_is_lower_bound() {
+        let values = [
+            Value::Null(()),
+            Value::Boolean(true),
+            Value::Character('a'),
+            Value::String("hello world".into()),
+            Value::Binary(BinaryData(vec![0xAB; 100])),
+            Value::Integer(Integer::u8(0)),
+            Value::Integer(Integer::i128(i128::MIN)),
+            Value::Imaginary(Imaginary::CartesianForm {
+                real: Integer::u8(0),
+                imaginary: Integer::u8(0),
+            }),
+            Value::Imaginary(Imaginary::PolarForm {
+                modulus: 1.0,
+                argument: 2.0,
+            }),
+            Value::Timestamp(chrono::NaiveDateTime::default()),
+            Value::JSON(serde_json::Value::Null),
+            Value::JSON(serde_json::json!(0)),
+            Value::Timezone(chrono_tz::Tz::Europe__London),
+            Value::Ipv4Addr(core::net::Ipv4Addr::LOCALHOST),
+            Value::Ipv6Addr(core::net::Ipv6Addr::LOCALHOST),
+            Value::SingleFloat(1.0),
+            Value::DoubleFloat(1.0),
+            Value::from(TimeDelta::zero()),
+            Value::Array(vec![Value::Null(()), Value::from(1_u8)]),
+            Value::Map(
+                [("key".to_string(), Value::Boolean(false))]
+                    .into_iter()
+                    .collect(),
+            ),
+            Value::OrderedMap(vec![("key".to_string(), Value::Boolean(false))]),
+            Value::Tagged {
+                tag: "Some".to_string(),
+                value: Box::new(Value::String("hello".into())),
+            },
+            Value::ZonedTimestamp(chrono::TimeZone::from_utc_datetime(
+                &chrono_tz::Tz::Asia__Kolkata,
+                &chrono::NaiveDateTime::default(),
+            )),
+        ];
+
+        for value in values {
+            assert!(
+                value.size_hint() <= value.ser(None).len(),
+                "{value:?} had too large a size hint"
+            );
+        }
+    }
+
+    #[test]
+    fn test_std_duration() {
+        let d = core::time::Duration::new(90, 5);
+        let v = Value::from(d);
+        assert_eq!(core::time::Duration::try_from(v).unwrap(), d);
+
+        let negative = Value::from(TimeDelta::milliseconds(-1500));
+        assert_eq!(
+            negative,
+            Value::Duration {
+                seconds: Integer::from(-2_i128),
+                nanoseconds: 500_000_000
+            }
+        );
+        assert!(core::time::Duration::try_from(negative).is_err());
+    }
+
+    #[test]
+    fn test_coerce_to() {
+        let s = |s: &str| Value::String(s.to_string());
+
+        assert_eq!(
+            s("-12").coerce_to(ValueTy::Integer).unwrap(),
+            Value::from(-12_i8)
+        );
+        assert_eq!(
+            s("1.5").coerce_to(ValueTy::DoubleFloat).unwrap(),
+            Value::DoubleFloat(1.5)
+        );
+        assert_eq!(
+            s("x").coerce_to(ValueTy::Character).unwrap(),
+            Value::Character('x')
+        );
+        assert_eq!(
+            s("127.0.0.1").coerce_to(ValueTy::Ipv4Addr).unwrap(),
+            Value::Ipv4Addr(Ipv4Addr::LOCALHOST)
+        );
+        assert!(s("xy").coerce_to(ValueTy::Character).is_err());
+        assert!(s("yes").coerce_to(ValueTy::Boolean).is_err());
+        assert!(s("1").coerce_to(ValueTy::Array).is_err());
+
+        assert_eq!(
+            Value::from(1_u8).coerce_to(ValueTy::Boolean).unwrap(),
+            Value::Boolean(true)
+        );
+        assert!(Value::from(2_u8).coerce_to(ValueTy::Boolean).is_err());
+        assert!(Value::from(-1_i8).coerce_to(ValueTy::Boolean).is_err());
+        assert_eq!(
+            Value::Boolean(true).coerce_to(ValueTy::Integer).unwrap(),
+            Value::from(1_u8)
+        );
+        assert_eq!(
+            Value::from(-7_i8).coerce_to(ValueTy::String).unwrap(),
+            s("-7")
+        );
+
+        //2^53 + 1 can't be represented exactly by an f64
+        assert!(Value::from((1_u64 << 53) + 1)
+            .coerce_to(ValueTy::DoubleFloat)
+            .is_err());
+        assert!(Value::DoubleFloat(0.1)
+            .coerce_to(ValueTy::SingleFloat)
+            .is_err());
+        assert_eq!(
+            Value::DoubleFloat(0.5)
+                .coerce_to(ValueTy::SingleFloat)
+                .unwrap(),
+            Value::SingleFloat(0.5)
+        );
+        assert_eq!(
+            Value::Null(()).coerce_to(ValueTy::Null).unwrap(),
+            Value::Null(())
+        );
+    }
+
+    #[test]
+    fn test_numeric_casts() {
+        assert_eq!(Value::from(-7_i8).to_f64(), Some(-7.0));
+        assert_eq!(Value::SingleFloat(0.1).to_f64(), Some(f64::from(0.1_f32)));
+        assert_eq!(
+            Value::DoubleFloat(f64::INFINITY).to_f64(),
+            Some(f64::INFINITY)
+        );
+        assert_eq!(Value::Boolean(true).to_f64(), None);
+
+        assert_eq!(Value::from(12_u8).to_integer(), Some(12_u8.into()));
+        assert_eq!(Value::SingleFloat(3.0).to_integer(), Some(3_u8.into()));
+        assert_eq!(Value::DoubleFloat(-2.0).to_integer(), Some((-2_i8).into()));
+        assert_eq!(Value::DoubleFloat(-0.0).to_integer(), Some(0_u8.into()));
+        assert_eq!(Value::DoubleFloat(-1.5).to_integer(), None);
+        assert_eq!(Value::DoubleFloat(1e-300).to_integer(), None);
+        assert_eq!(Value::SingleFloat(f32::NEG_INFINITY).to_integer(), None);
+        assert_eq!(Value::DoubleFloat(-1e40).to_integer(), None);
+        assert_eq!(Value::Character('1').to_integer(), None);
+
+        assert_eq!(
+            Value::SingleFloat(8.0).coerce_to(ValueTy::Integer).unwrap(),
+            Value::from(8_u8)
+        );
+        assert!(Value::DoubleFloat(-0.5)
+            .coerce_to(ValueTy::Integer)
+            .is_err());
+    }
+
+    #[test]
+    fn test_non_finite_polar_form_to_json() {
+        let infinite = Value::Imaginary(Imaginary::PolarForm {
+            modulus: f64::INFINITY,
+            argument: 1.0,
+        });
+        assert_eq!(infinite.clone().convert_to_json(true), None);
+        assert_eq!(infinite.convert_to_json(false), None);
+
+        let nan = Value::Imaginary(Imaginary::PolarForm {
+            modulus: 1.0,
+            argument: f64::NAN,
+        });
+        assert_eq!(nan.convert_to_json(true), None);
+
+        let finite = Value::Imaginary(Imaginary::PolarForm {
+            modulus: 2.0,
+            argument: 0.5,
+        });
+        let json = finite.clone().convert_to_json(true).unwrap();
+        assert_eq!(Value::convert_from_json(json).unwrap(), finite);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_serde_round_trip() {
+        use chrono::NaiveDate;
+        use core::net::{Ipv4Addr, Ipv6Addr};
+
+        let values = [
+            Value::Character('🖖'),
+            Value::String("hello".into()),
+            Value::Binary(BinaryData(vec![1, 2, 3])),
+            Value::Boolean(true),
+            Value::Integer(Integer::from(u128::MAX)),
+            Value::Integer(Integer::from(i128::MIN)),
+            Value::Imaginary(Imaginary::CartesianForm {
+                real: Integer::from(3_i32),
+                imaginary: Integer::from(-4_i32),
+            }),
+            Value::Imaginary(Imaginary::PolarForm {
+                modulus: 1.5,
+                argument: 0.25,
+            }),
+            Value::Timestamp(
+                NaiveDate::from_ymd_opt(2024, 5, 6)
+                    .unwrap()
+                    .and_hms_nano_opt(7, 8, 9, 10)
+                    .unwrap(),
+            ),
+            Value::JSON(serde_json::json!({"a": [1, 2, null]})),
+            Value::Null(()),
+            Value::SingleFloat(1.5),
+            Value::DoubleFloat(-2.25),
+            Value::Array(vec![Value::Null(()), Value::from(1_u8)]),
+            Value::Map(
+                [("key".to_string(), Value::Boolean(false))]
+                    .into_iter()
+                    .collect(),
+            ),
+            Value::Timezone(chrono_tz::Tz::Europe__London),
+            Value::Ipv4Addr(Ipv4Addr::LOCALHOST),
+            Value::Ipv6Addr(Ipv6Addr::LOCALHOST),
+            Value::from(TimeDelta::milliseconds(-1500)),
+            #[cfg(feature = "uuid")]
+            Value::Uuid(uuid::Uuid::from_u128(0x1234_5678_9abc_def0)),
+        ];
+
+        for value in values {
+            let json = serde_json::to_string(&value).unwrap();
+            let back: Value = serde_json::from_str(&json).unwrap();
+            assert_eq!(value, back, "{json}");
+        }
+    }
+
+    #[test]
+    fn test_pretty_string() {
+        let value = Value::Map(
+            [
+                ("empty".to_string(), Value::Array(vec![])),
+                (
+                    "items".to_string(),
+                    Value::Array(vec![
+                        Value::Map(
+                            [
+                                ("a".to_string(), Value::from(1_u8)),
+                                ("b".to_string(), Value::Boolean(true)),
+                            ]
+                            .into_iter()
+                            .collect(),
+                        ),
+                        Value::Array(vec![Value::Character('x')]),
+                    ]),
+                ),
+                (
+                    "nested".to_string(),
+                    Value::Map(
+                        [("inner".to_string(), Value::Null(()))]
+                            .into_iter()
+                            .collect(),
+                    ),
+                ),
+            ]
+            .into_iter()
+            .collect(),
+        );
+
+        let expected = "empty: []
+items:
+    -   a: 1
+        b: true
+    -   -   'x'
+nested:
+    inner: null";
+        assert_eq!(value.to_pretty_string(4), expected);
+        assert_eq!(Value::from(5_u8).to_pretty_string(2), "5");
+    }
+
+    #[test]
+    fn test_approx_eq() {
+        let a = Value::Map(
+            [
+                ("f".to_string(), Value::SingleFloat(1.0)),
+                (
+                    "i".to_string(),
+                    Value::Imaginary(Imaginary::PolarForm {
+                        modulus: 2.0,
+                        argument: 0.5,
+                    }),
+                ),
+                ("n".to_string(), Value::from(5_u8)),
+            ]
+            .into_iter()
+            .collect(),
+        );
+        let b = Value::Map(
+            [
+                ("f".to_string(), Value::SingleFloat(1.000_01)),
+                (
+                    "i".to_string(),
+                    Value::Imaginary(Imaginary::PolarForm {
+                        modulus: 2.000_01,
+                        argument: 0.499_99,
+                    }),
+                ),
+                ("n".to_string(), Value::from(5_u8)),
+            ]
+            .into_iter()
+            .collect(),
+        );
+
+        assert_ne!(a, b);
+        assert!(a.approx_eq(&b, 1e-4));
+        assert!(!a.approx_eq(&b, 1e-6));
+
+        assert!(!Value::from(5_u8).approx_eq(&Value::from(6_u8), 10.0));
+        assert!(!Value::DoubleFloat(f64::NAN).approx_eq(&Value::DoubleFloat(f64::NAN), 1.0));
+        assert!(
+            Value::DoubleFloat(f64::INFINITY).approx_eq(&Value::DoubleFloat(f64::INFINITY), 0.0)
+        );
+    }
+
+    #[test]
+    fn test_walk_mut() {
+        let mut value = Value::Map(
+            [
+                ("a".to_string(), Value::from(1_u8)),
+                (
+                    "b".to_string(),
+                    Value::Array(vec![Value::from(2_u8), Value::Null(())]),
+                ),
+            ]
+            .into_iter()
+            .collect(),
+        );
+
+        value.walk_mut(&mut |v| {
+            if let Some(i) = v.as_mut_int() {
+                *i = *i * Integer::u8(10);
+            }
+        });
+
+        let mut total = 0_u32;
+        let mut count = 0;
+        value.walk(&mut |v| {
+            count += 1;
+            if let Some(i) = v.as_int() {
+                total += u32::try_from(*i).unwrap();
+            }
+        });
+
+        assert_eq!(total, 30);
+        assert_eq!(count, 5);
+    }
+
+    #[test]
+    fn test_deep_get() {
+        let inner: HashMap<String, Value> = [
+            ("b".to_string(), Value::Boolean(true)),
+            ("c/d".to_string(), Value::Null(())),
+        ]
+        .into_iter()
+        .collect();
+        let mut value = Value::Map(
+            [(
+                "a".to_string(),
+                Value::Array(vec![Value::Map(inner), Value::from(5_u8)]),
+            )]
+            .into_iter()
+            .collect(),
+        );
+
+        let path = [
+            PathSegment::from("a"),
+            PathSegment::from(0),
+            PathSegment::from("b"),
+        ];
+        assert_eq!(value.deep_get(&path), Some(&Value::Boolean(true)));
+        assert_eq!(value.pointer("/a/0/b"), Some(&Value::Boolean(true)));
+        assert_eq!(value.pointer("/a/0/c~1d"), Some(&Value::Null(())));
+        assert_eq!(value.pointer("/a/1"), Some(&Value::from(5_u8)));
+
+        assert_eq!(value.deep_get(&[PathSegment::from(0)]), None);
+        assert_eq!(value.pointer("/a/2"), None);
+        assert_eq!(value.pointer("/a/b"), None);
+        assert_eq!(value.pointer("a"), None);
+
+        *value.deep_get_mut(&path).unwrap() = Value::Boolean(false);
+        assert_eq!(value.pointer("/a/0/b"), Some(&Value::Boolean(false)));
+        *value.pointer_mut("/a/1").unwrap() = Value::from(6_u8);
+        assert_eq!(value.pointer("/a/1"), Some(&Value::from(6_u8)));
+    }
+
+    #[test]
+    fn test_huge_lengths_dont_allocate() {
+        let (_, huge) = Integer::from(usize::MAX).ser();
+
+        for ty in [ValueTy::Array, ValueTy::Map] {
+            let mut bytes = vec![ty.to_type_byte() | 1];
+            bytes.extend(huge.iter());
+
+            assert!(matches!(
+                Value::deser(&mut Cursor::new(&bytes), None),
+                Err(ValueSerError::NotEnoughBytes)
+            ));
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "regex")]
+    fn test_regex() {
+        use crate::store::Store;
+        use regex::Regex;
+
+        let v = Value::Regex(Regex::new("^(a|b)+$").unwrap());
+
+        let bytes = v.ser(None);
+        let out_value = Value::deser(&mut Cursor::new(&bytes), None).unwrap();
+        assert_eq!(v, out_value);
+        assert!(out_value.as_regex().unwrap().is_match("abba"));
+
+        let json = v.clone().convert_to_json(true).unwrap();
+        assert_eq!(v, Value::convert_from_json(json).unwrap());
+
+        let store: Store = [("pattern".to_string(), v.clone())].into_iter().collect();
+        assert_eq!(Store::deser(&store.ser().unwrap()).unwrap(), store);
+
+        //swap the pattern for one of the same length which doesn't compile
+        let pattern_start = bytes.len() - "^(a|b)+$".len();
+        let mut invalid = bytes.clone();
+        invalid[pattern_start..].copy_from_slice(b"^((a|b)+");
+        assert!(matches!(
+            Value::deser(&mut Cursor::new(&invalid), None),
+            Err(ValueSerError::InvalidRegex(_))
+        ));
+    }
+
+    #[test]
+    fn test_unknown_types_are_kept() {
+        //an extended type with the largest discriminant, which doesn't exist (yet)
+        let unknown = [0b1001_1111, 3, 1, 2, 3];
+        let mut bytes = vec![ValueTy::Array.to_type_byte() | (2 << 1)];
+        bytes.extend(unknown);
+        bytes.extend(Value::Boolean(true).ser(None));
+
+        assert!(matches!(
+            Value::deser(&mut Cursor::new(&bytes), None),
+            Err(ValueSerError::InvalidType(30))
+        ));
+
+        let value = Value::deser_skip_unknown(&mut Cursor::new(&bytes), None).unwrap();
+        assert_eq!(
+            value,
+            Value::Array(vec![
+                Value::Unknown(UnknownValue::new(30, vec![1, 2, 3]).unwrap()),
+                Value::Boolean(true)
+            ])
+        );
+        assert_eq!(value.ser(None), bytes);
+    }
+
+    #[test]
+    fn test_unknown_types_survive_a_new_huffman_tree() {
+        use crate::utilities::huffman::Huffman;
+
+        //pretend that tagged values don't exist yet, like in an older version
+        const UNKNOWN_TYPE_BYTE: u8 = 0b1001_1111;
+        let set_type_byte = |mut bytes: Vec<u8>, ty: u8| {
+            bytes[0] = ty;
+            bytes
+        };
+
+        let value = Value::Tagged {
+            tag: "quux".into(),
+            value: Box::new(Value::Map(
+                [("hello".into(), Value::String("world xyzzy".into()))]
+                    .into_iter()
+                    .collect(),
+            )),
+        };
+        let first_tree = Huffman::new_str("hello world quux xyzzy").unwrap();
+        let second_tree = Huffman::new_str("something completely different").unwrap();
+
+        let written = value.ser(Some(&first_tree));
+        let old_reader_bytes = set_type_byte(written, UNKNOWN_TYPE_BYTE);
+        let unknown =
+            Value::deser_skip_unknown(&mut Cursor::new(&old_reader_bytes), Some(&first_tree))
+                .unwrap();
+        assert_eq!(unknown.as_ty(), ValueTy::Unknown);
+
+        //the older version saves again, with a tree for its own strings
+        let resaved = set_type_byte(
+            unknown.ser(Some(&second_tree)),
+            value.as_ty().to_type_byte(),
+        );
+        assert_eq!(
+            Value::deser(&mut Cursor::new(&resaved), Some(&second_tree)).unwrap(),
+            value
+        );
+    }
+
+    #[test]
+    fn test_null_is_unaffected_by_extended_types() {
+        let bytes = Value::Null(()).ser(None);
+        assert_eq!(bytes, [0b1001_0000]);
+        assert_eq!(
+            Value::deser(&mut Cursor::new(&bytes), None).unwrap(),
+            Value::Null(())
+        );
+    }
+
+    #[test]
+    fn test_map_ser_is_deterministic() {
+        let keys = [
+            "zebra", "apple", "mango", "banana", "kiwi", "cherry", "fig", "date",
+        ];
+
+        let forwards: HashMap<String, Value> = keys
+            .iter()
+            .enumerate()
+            .map(|(i, k)| (k.to_string(), Value::from(i)))
+            .collect();
+        let backwards: HashMap<String, Value> = keys
+            .iter()
+            .enumerate()
+            .rev()
+            .map(|(i, k)| (k.to_string(), Value::from(i)))
+            .collect();
+
+        let forwards = Value::Map(forwards);
+        let backwards = Value::Map(backwards);
+
+        assert_eq!(forwards.ser(None), forwards.ser(None));
+        assert_eq!(forwards.ser(None), backwards.ser(None));
+    }
+
+    #[test]
+    fn test_ordered_map_keeps_order() {
+        use crate::{store::Store, utilities::huffman::Huffman};
+
+        let keys = [
+            "zebra", "apple", "mango", "banana", "kiwi", "cherry", "fig", "date",
+        ];
+        let value = Value::OrderedMap(
+            keys.iter()
+                .enumerate()
+                .map(|(i, k)| (k.to_string(), Value::from(i + 1)))
+                .collect(),
+        );
+
+        let bytes = value.ser(None);
+        assert_eq!(Value::deser(&mut Cursor::new(&bytes), None).unwrap(), value);
+
+        let huffman = Huffman::new_str(keys.concat()).unwrap();
+        let bytes = value.ser(Some(&huffman));
+        assert_eq!(
+            Value::deser(&mut Cursor::new(&bytes), Some(&huffman)).unwrap(),
+            value
+        );
+
+        let json = value.clone().convert_to_json(true).unwrap();
+        assert_eq!(Value::convert_from_json(json).unwrap(), value);
+
+        let store: Store = [("ordered".to_string(), value.clone())]
+            .into_iter()
+            .collect();
+        assert_eq!(Store::deser(&store.ser().unwrap()).unwrap(), store);
+
+        let mut reversed = value.clone();
+        reversed.as_mut_ordered_map().unwrap().reverse();
+        assert_ne!(value, reversed);
+        assert_eq!(
+            value.clone().coerce_to(ValueTy::Map).unwrap(),
+            reversed.coerce_to(ValueTy::Map).unwrap()
+        );
+
+        assert_eq!(value.pointer("/mango"), Some(&Value::from(3_usize)));
+    }
+
+    #[test]
+    fn test_tagged() {
+        use crate::{store::Store, utilities::huffman::Huffman};
+        use serde_json::json;
+
+        let value = Value::Tagged {
+            tag: "Circle".to_string(),
+            value: Box::new(Value::Map(
+                [("radius".to_string(), Value::from(5_u8))]
+                    .into_iter()
+                    .collect(),
+            )),
+        };
+
+        let bytes = value.ser(None);
+        assert_eq!(Value::deser(&mut Cursor::new(&bytes), None).unwrap(), value);
+
+        let huffman = Huffman::new_str("Circleradius").unwrap();
+        let bytes = value.ser(Some(&huffman));
+        assert_eq!(
+            Value::deser(&mut Cursor::new(&bytes), Some(&huffman)).unwrap(),
+            value
+        );
+
+        let Value::Tagged { tag, value: inner } = value.clone() else {
+            unreachable!()
+        };
+        let as_map = Value::Map([(tag, *inner)].into_iter().collect());
+        //the only overhead over a single-key map is the length of the extended type
+        assert_eq!(value.ser(None).len(), as_map.ser(None).len() + 1);
+
+        //without souris types, this is the same as a single-key map
+        let json = value.clone().convert_to_json(false).unwrap();
+        assert_eq!(json, json!({"Circle": {"radius": 5}}));
+        assert_eq!(Value::convert_from_json(json).unwrap(), as_map);
+
+        let json = value.to_json_value(true).unwrap();
+        assert_eq!(json, value.clone().convert_to_json(true).unwrap());
+        assert_eq!(Value::convert_from_json(json).unwrap(), value);
+
+        assert!(matches!(
+            Value::convert_from_json(json!({"souris_type": 21, "a": 1, "b": 2})),
+            Err(ValueSerError::InvalidSourisType { .. })
+        ));
+        let clashing = Value::Tagged {
+            tag: "souris_type".to_string(),
+            value: Box::new(Value::Null(())),
+        };
+        assert!(clashing.to_json_value(true).is_none());
+        assert!(clashing.to_json_value(false).is_some());
+
+        let mut nested = Value::Null(());
+        for _ in 0..3 {
+            nested = Value::Tagged {
+                tag: "Wrap".to_string(),
+                value: Box::new(nested),
+            };
+        }
+        let bytes = nested.ser(None);
+        assert!(Value::deser_with_max_depth(&mut Cursor::new(&bytes), None, 3).is_ok());
+        assert!(matches!(
+            Value::deser_with_max_depth(&mut Cursor::new(&bytes), None, 2),
+            Err(ValueSerError::MaxDepthExceeded)
+        ));
+
+        let store: Store = [("shape".to_string(), value.clone())].into_iter().collect();
+        assert_eq!(Store::deser(&store.ser().unwrap()).unwrap(), store);
+    }
+
+    #[test]
+    fn test_ordered_map_rejects_non_string_keys() {
+        let mut contents = Integer::from(1_usize).ser().1;
+        contents.extend(Value::Boolean(true).ser(None));
+        contents.extend(Value::Null(()).ser(None));
+
+        let mut bytes = vec![ValueTy::OrderedMap.to_type_byte()];
+        bytes.extend(Integer::from(contents.len()).ser().1);
+        bytes.extend(contents);
+
+        assert!(matches!(
+            Value::deser(&mut Cursor::new(&bytes), None),
+            Err(ValueSerError::UnexpectedValueType {
+                found: ValueTy::Boolean,
+                expected: ValueTy::String
+            })
+        ));
+    }
+
+    #[test]
+    fn test_zoned_timestamp() {
+        use crate::{store::Store, utilities::huffman::Huffman};
+        use chrono::TimeZone;
+        use chrono_tz::Tz;
+
+        let tz = Tz::America__New_York;
+        let dt = tz.with_ymd_and_hms(2024, 3, 10, 1, 30, 0).unwrap();
+        let value = Value::ZonedTimestamp(dt);
+
+        let bytes = value.ser(None);
+        assert_eq!(Value::deser(&mut Cursor::new(&bytes), None).unwrap(), value);
+
+        let huffman = Huffman::new_str(tz.name()).unwrap();
+        let bytes = value.ser(Some(&huffman));
+        assert_eq!(
+            Value::deser(&mut Cursor::new(&bytes), Some(&huffman)).unwrap(),
+            value
+        );
+
+        let json = value.clone().convert_to_json(true).unwrap();
+        assert_eq!(Value::convert_from_json(json).unwrap(), value);
+        assert_eq!(
+            value.clone().convert_to_json(false).unwrap(),
+            serde_json::Value::String("2024-03-10T01:30:00-05:00".into())
+        );
+
+        let store: Store = [("zoned".to_string(), value.clone())].into_iter().collect();
+        assert_eq!(Store::deser(&store.ser().unwrap()).unwrap(), store);
+
+        //same instant, different timezone
+        assert_ne!(value, Value::ZonedTimestamp(dt.with_timezone(&Tz::UTC)));
+        assert_eq!(
+            value.coerce_to(ValueTy::Timestamp).unwrap(),
+            Value::Timestamp(dt.naive_local())
+        );
+    }
+
+    #[test]
+    fn test_length_too_large() {
+        let too_large = Integer::u128(u128::MAX).ser().1;
+
+        let mut array = vec![ValueTy::Array.to_type_byte() | 0b1];
+        array.extend(&too_large);
+        array.push(0);
+        assert!(matches!(
+            Value::deser(&mut Cursor::new(&array), None),
+            Err(ValueSerError::LengthTooLarge("Array"))
+        ));
+
+        let mut string = vec![ValueTy::String.to_type_byte()];
+        string.extend(&too_large);
+        assert!(matches!(
+            Value::deser(&mut Cursor::new(&string), None),
+            Err(ValueSerError::LengthTooLarge("String"))
+        ));
+    }
+
+    #[test]
+    fn test_inner_string_errors() {
+        use crate::utilities::huffman::Huffman;
+        use chrono_tz::Tz;
+
+        let json = Value::JSON(serde_json::json!({"a": [1, 2]}));
+        let huffman = Huffman::new_str(json.to_string()).unwrap();
+        let bytes = json.ser(Some(&huffman));
+        assert!(matches!(
+            Value::deser(&mut Cursor::new(&bytes), None),
+            Err(ValueSerError::JsonInnerString(cause)) if matches!(*cause, ValueSerError::NoHuffman)
+        ));
+        assert_eq!(
+            Value::deser(&mut Cursor::new(&bytes), Some(&huffman)).unwrap(),
+            json
+        );
+
+        let tz = Value::Timezone(Tz::Europe__London);
+        let huffman = Huffman::new_str(Tz::Europe__London.name()).unwrap();
+        let bytes = tz.ser(Some(&huffman));
+        assert!(matches!(
+            Value::deser(&mut Cursor::new(&bytes), None),
+            Err(ValueSerError::TimezoneInnerString(cause)) if matches!(*cause, ValueSerError::NoHuffman)
+        ));
+
+        //swap the inner string for a boolean
+        let mut bytes = tz.ser(None);
+        bytes.truncate(1);
+        bytes.extend(Value::Boolean(true).ser(None));
+        assert!(matches!(
+            Value::deser(&mut Cursor::new(&bytes), None),
+            Err(ValueSerError::TimezoneInnerString(cause)) if matches!(
+                *cause,
+                ValueSerError::UnexpectedValueType {
+                    found: ValueTy::Boolean,
+                    expected: ValueTy::String
+                }
+            )
+        ));
+    }
+
+    #[test]
+    fn test_btree_round_trip() {
+        let btree: BTreeMap<String, Value> = [
+            ("zebra", Value::from(1_u8)),
+            ("apple", Value::Boolean(true)),
+            ("mango", Value::Array(vec![Value::Null(())])),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v))
+        .collect();
+
+        let value = Value::from_btree(btree.clone());
+        assert_eq!(value.len(), Some(3));
+
+        let view = value.map_as_btree().unwrap();
+        assert!(view.keys().eq(btree.keys()));
+        assert!(view.values().copied().eq(btree.values()));
+
+        assert!(Value::OrderedMap(vec![]).map_as_btree().is_none());
+        assert_eq!(
+            Value::from_btree(BTreeMap::new()).map_as_btree(),
+            Some(BTreeMap::new())
+        );
+    }
+
+    #[test]
+    fn test_array_of() {
+        let array = Value::Array(vec![Value::from(1_u8), Value::from(300_u16)]);
+        assert_eq!(array.is_homogeneous(), Some(ValueTy::Integer));
+        assert!(matches!(
+            array.clone().array_of::<u8>(),
+            Err(ValueSerError::InvalidArrayElement { index: 1, cause }) if matches!(*cause, ValueSerError::IntegerSerError(_))
+        ));
+        assert_eq!(array.array_of::<u16>().unwrap(), vec![1, 300]);
+
+        assert!(matches!(
+            Value::Null(()).array_of::<u8>(),
+            Err(ValueSerError::UnexpectedValueType {
+                found: ValueTy::Null,
+                expected: ValueTy::Array
+            })
+        ));
+        assert_eq!(Value::Null(()).is_homogeneous(), None);
+    }
+
+    #[test]
+    fn test_max_depth() {
+        fn nested_arrays(depth: usize) -> Vec<u8> {
+            //arrays with one element only store their length in the type byte, so we can just repeat the prefix
+            let one = Value::Array(vec![Value::Null(())]).ser(None);
+            let (array_header, null) = one.split_at(one.len() - Value::Null(()).ser(None).len());
+
+            let mut bytes = array_header.repeat(depth);
+            bytes.extend_from_slice(null);
+            bytes
+        }
+
+        let bytes = nested_arrays(10_000);
+        assert!(matches!(
+            Value::deser(&mut Cursor::new(&bytes), None),
+            Err(ValueSerError::MaxDepthExceeded)
+        ));
+
+        let bytes = nested_arrays(Value::DEFAULT_MAX_DEPTH);
+        let mut value = Value::deser(&mut Cursor::new(&bytes), None).unwrap();
+        for _ in 0..Value::DEFAULT_MAX_DEPTH {
+            let Value::Array(mut arr) = value else {
+                panic!("expected array");
+            };
+            value = arr.pop().unwrap();
+        }
+        assert_eq!(value, Value::Null(()));
+
+        let bytes = nested_arrays(Value::DEFAULT_MAX_DEPTH + 1);
+        assert!(matches!(
+            Value::deser(&mut Cursor::new(&bytes), None),
+            Err(ValueSerError::MaxDepthExceeded)
+        ));
+        assert!(Value::deser_with_max_depth(
+            &mut Cursor::new(&bytes),
+            None,
+            Value::DEFAULT_MAX_DEPTH + 1
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn test_nested_wrappers_hit_errors_not_the_stack() {
+        //each JSON or timezone value would hold another one, forever
+        for ty in [ValueTy::JSON, ValueTy::Timezone] {
+            let bytes = vec![ty.to_type_byte(); 2_000_000];
+            assert!(Value::deser(&mut Cursor::new(&bytes), None).is_err());
+        }
+
+        //extended wrappers each need a length, so build them from the inside out
+        for ty in [ValueTy::Duration, ValueTy::ZonedTimestamp, ValueTy::Tagged] {
+            let mut reversed = vec![];
+            for _ in 0..100_000 {
+                let len = Integer::usize(reversed.len()).ser().1;
+                reversed.extend(len.into_iter().rev());
+                reversed.push(ty.to_type_byte());
+            }
+            reversed.reverse();
+            assert!(Value::deser(&mut Cursor::new(&reversed), None).is_err());
+        }
+    }
 }
@@ -0,0 +1,602 @@
+//! Conversions between [`Value`]s and [CBOR](https://cbor.io), using [`ciborium`].
+//!
+//! Types which CBOR can represent natively (strings, byte strings, booleans, integers, floats, arrays, maps and null) are written as-is. Types which have a registered CBOR tag are written using that tag:
+//! - [`Value::Ipv4Addr`] and [`Value::Ipv6Addr`] use tags `52` and `54`, holding the bytes of the address.
+//! - `Value::Uuid` uses tag `37`, holding the 16 bytes of the UUID.
+//! - `Value::Regex` uses tag `35`, holding the pattern.
+//!
+//! Everything else is wrapped in a tag of [`Value::CBOR_TAG_BASE`] plus the discriminant of its [`ValueTy`], so that it can be read back as the same type.
+
+use alloc::{boxed::Box, string::ToString, vec, vec::Vec};
+use core::{
+    net::{Ipv4Addr, Ipv6Addr},
+    str::FromStr,
+};
+
+use chrono::{DateTime, NaiveDateTime};
+use chrono_tz::Tz;
+use ciborium::{
+    de::Error as CborDeError,
+    value::{Integer as CborInteger, Value as CborValue},
+};
+use serde_json::{Number, Value as SJValue};
+
+use super::{InvalidSourisTypeError, UnknownValue, Value, ValueSerError, ValueTy};
+use crate::types::{
+    binary::BinaryData,
+    imaginary::Imaginary,
+    integer::{BiggestInt, BiggestIntButSigned, Integer},
+};
+
+///The format used for [`Value::Timestamp`]s, which [`NaiveDateTime::from_str`] can read back - unlike its [`Display`](core::fmt::Display) implementation.
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%dT%H:%M:%S%.f";
+///The registered tag for a positive bignum, holding its big-endian bytes.
+const BIGNUM_POSITIVE_TAG: u64 = 2;
+///The registered tag for a negative bignum, holding the big-endian bytes of `-1 - n`.
+const BIGNUM_NEGATIVE_TAG: u64 = 3;
+///The registered tag for a regular expression.
+#[cfg(feature = "regex")]
+const REGEX_TAG: u64 = 35;
+///The registered tag for a binary UUID.
+#[cfg(feature = "uuid")]
+const UUID_TAG: u64 = 37;
+///The registered tag for an IPv4 address.
+const IPV4_TAG: u64 = 52;
+///The registered tag for an IPv6 address.
+const IPV6_TAG: u64 = 54;
+
+impl Value {
+    ///The first CBOR tag used for `souris`-specific types - each type uses this plus the discriminant of its [`ValueTy`].
+    ///
+    /// NB: These tags are in the first-come-first-served range, but aren't registered with IANA.
+    pub const CBOR_TAG_BASE: u64 = 0x534F_5500;
+
+    ///Converts a [`Value`] into [CBOR](https://cbor.io), which can be read back using [`Value::from_cbor`].
+    ///
+    /// Unlike [`Value::convert_to_json`], every variant can be converted, and the conversion keeps the exact type. Types that CBOR doesn't have are tagged - see [`Value::CBOR_TAG_BASE`].
+    ///
+    /// ```rust
+    /// use sourisdb::{types::imaginary::Imaginary, values::Value};
+    ///
+    /// let value = Value::Array(vec![
+    ///     Value::from(42_u8),
+    ///     Value::Imaginary(Imaginary::CartesianForm {
+    ///         real: 1_u8.into(),
+    ///         imaginary: (-1_i8).into(),
+    ///     }),
+    /// ]);
+    ///
+    /// let cbor = value.to_cbor();
+    /// assert_eq!(Value::from_cbor(&cbor).unwrap(), value);
+    /// ```
+    #[must_use]
+    pub fn to_cbor(&self) -> Vec<u8> {
+        let mut bytes = vec![];
+        if ciborium::ser::into_writer(&self.to_cbor_value(), &mut bytes).is_err() {
+            unreachable!("writing CBOR into a Vec can't fail");
+        }
+        bytes
+    }
+
+    ///Reads a [`Value`] from [CBOR](https://cbor.io), like that written by [`Value::to_cbor`].
+    ///
+    /// CBOR from other sources can be read too - tags which aren't understood are skipped over, and the value inside them is read instead. Only the first CBOR item in `bytes` is read.
+    ///
+    /// # Errors
+    /// - [`ValueSerError::InvalidCbor`] if the bytes aren't valid CBOR.
+    /// - [`ValueSerError::NotEnoughBytes`] if the bytes end part-way through an item.
+    /// - [`ValueSerError::MaxDepthExceeded`] if arrays, maps and tags are nested more than [`Value::DEFAULT_MAX_DEPTH`] deep.
+    /// - [`ValueSerError::UnexpectedValueType`] if a map has a key which isn't a string.
+    /// - [`ValueSerError::UnrepresentableNumber`] if an integer doesn't fit into an [`Integer`].
+    /// - [`ValueSerError::InvalidSourisType`] if a tagged item holds the wrong data for its type.
+    /// - [`ValueSerError::InvalidType`] if a tagged item for a type this version doesn't know about doesn't hold a byte string.
+    pub fn from_cbor(bytes: &[u8]) -> Result<Self, ValueSerError> {
+        let cbor: CborValue =
+            ciborium::de::from_reader_with_recursion_limit(bytes, Self::DEFAULT_MAX_DEPTH)
+                .map_err(|e| match e {
+                    CborDeError::RecursionLimitExceeded => ValueSerError::MaxDepthExceeded,
+                    CborDeError::Io(_) => ValueSerError::NotEnoughBytes,
+                    e => ValueSerError::InvalidCbor(e.to_string()),
+                })?;
+
+        Self::from_cbor_value(cbor)
+    }
+
+    fn to_cbor_value(&self) -> CborValue {
+        let tagged = |ty: ValueTy, value: CborValue| {
+            CborValue::Tag(
+                Self::CBOR_TAG_BASE + u64::from(u8::from(ty)),
+                Box::new(value),
+            )
+        };
+
+        match self {
+            Self::Character(c) => tagged(ValueTy::Character, CborValue::Text(c.to_string())),
+            Self::String(s) => CborValue::Text(s.clone()),
+            Self::Binary(b) => CborValue::Bytes(b.0.clone()),
+            Self::Boolean(b) => CborValue::Bool(*b),
+            Self::Integer(i) => integer_to_cbor(*i),
+            Self::Imaginary(Imaginary::CartesianForm { real, imaginary }) => tagged(
+                ValueTy::Imaginary,
+                CborValue::Array(vec![integer_to_cbor(*real), integer_to_cbor(*imaginary)]),
+            ),
+            Self::Imaginary(Imaginary::PolarForm { modulus, argument }) => tagged(
+                ValueTy::Imaginary,
+                CborValue::Array(vec![
+                    CborValue::Float(*modulus),
+                    CborValue::Float(*argument),
+                ]),
+            ),
+            Self::Timestamp(ts) => tagged(
+                ValueTy::Timestamp,
+                CborValue::Text(ts.format(TIMESTAMP_FORMAT).to_string()),
+            ),
+            Self::ZonedTimestamp(dt) => tagged(
+                ValueTy::ZonedTimestamp,
+                CborValue::Array(vec![
+                    CborValue::Text(dt.to_rfc3339()),
+                    CborValue::Text(dt.timezone().name().to_string()),
+                ]),
+            ),
+            Self::JSON(j) => tagged(ValueTy::JSON, json_to_cbor(j)),
+            Self::Null(()) => CborValue::Null,
+            Self::SingleFloat(f) => tagged(ValueTy::SingleFloat, CborValue::Float(f64::from(*f))),
+            Self::DoubleFloat(f) => CborValue::Float(*f),
+            Self::Array(a) => CborValue::Array(a.iter().map(Self::to_cbor_value).collect()),
+            Self::Map(m) => {
+                //sorted so that equal maps produce identical bytes, like `Value::ser`
+                let mut entries: Vec<_> = m.iter().collect();
+                entries.sort_unstable_by_key(|(k, _)| *k);
+
+                CborValue::Map(
+                    entries
+                        .into_iter()
+                        .map(|(k, v)| (CborValue::Text(k.clone()), v.to_cbor_value()))
+                        .collect(),
+                )
+            }
+            Self::OrderedMap(m) => tagged(
+                ValueTy::OrderedMap,
+                CborValue::Map(
+                    m.iter()
+                        .map(|(k, v)| (CborValue::Text(k.clone()), v.to_cbor_value()))
+                        .collect(),
+                ),
+            ),
+            Self::Timezone(tz) => tagged(ValueTy::Timezone, CborValue::Text(tz.name().to_string())),
+            Self::Ipv4Addr(a) => {
+                CborValue::Tag(IPV4_TAG, Box::new(CborValue::Bytes(a.octets().to_vec())))
+            }
+            Self::Ipv6Addr(a) => {
+                CborValue::Tag(IPV6_TAG, Box::new(CborValue::Bytes(a.octets().to_vec())))
+            }
+            #[cfg(feature = "uuid")]
+            Self::Uuid(u) => {
+                CborValue::Tag(UUID_TAG, Box::new(CborValue::Bytes(u.as_bytes().to_vec())))
+            }
+            #[cfg(feature = "regex")]
+            Self::Regex(r) => {
+                CborValue::Tag(REGEX_TAG, Box::new(CborValue::Text(r.as_str().to_string())))
+            }
+            Self::Duration {
+                seconds,
+                nanoseconds,
+            } => tagged(
+                ValueTy::Duration,
+                CborValue::Array(vec![
+                    integer_to_cbor(*seconds),
+                    CborValue::Integer(CborInteger::from(*nanoseconds)),
+                ]),
+            ),
+            Self::Tagged { tag, value } => tagged(
+                ValueTy::Tagged,
+                CborValue::Array(vec![CborValue::Text(tag.clone()), value.to_cbor_value()]),
+            ),
+            Self::Unknown(unknown) => CborValue::Tag(
+                Self::CBOR_TAG_BASE + u64::from(unknown.ty()),
+                Box::new(CborValue::Bytes(unknown.bytes().to_vec())),
+            ),
+        }
+    }
+
+    fn from_cbor_value(cbor: CborValue) -> Result<Self, ValueSerError> {
+        Ok(match cbor {
+            CborValue::Integer(i) => Self::Integer(integer_from_cbor_integer(i)),
+            CborValue::Bytes(b) => Self::Binary(BinaryData(b)),
+            CborValue::Float(f) => Self::DoubleFloat(f),
+            CborValue::Text(s) => Self::String(s),
+            CborValue::Bool(b) => Self::Boolean(b),
+            CborValue::Null => Self::Null(()),
+            CborValue::Array(a) => Self::Array(
+                a.into_iter()
+                    .map(Self::from_cbor_value)
+                    .collect::<Result<_, _>>()?,
+            ),
+            CborValue::Map(m) => Self::Map(map_from_cbor(m)?.into_iter().collect()),
+            CborValue::Tag(tag, inner) => Self::from_cbor_tag(tag, *inner)?,
+            _ => return Err(ValueSerError::InvalidCbor("unsupported CBOR item".into())),
+        })
+    }
+
+    fn from_cbor_tag(tag: u64, inner: CborValue) -> Result<Self, ValueSerError> {
+        if let Some(discriminant) = tag
+            .checked_sub(Self::CBOR_TAG_BASE)
+            .and_then(|d| u8::try_from(d).ok())
+        {
+            return match (ValueTy::try_from(discriminant), inner) {
+                (Ok(ty), inner) => Self::from_souris_tag(ty, inner),
+                (Err(e), CborValue::Bytes(bytes)) => UnknownValue::new(discriminant, bytes)
+                    .map(Self::Unknown)
+                    .ok_or(e),
+                (Err(e), _) => Err(e),
+            };
+        }
+
+        Ok(match (tag, inner) {
+            (BIGNUM_POSITIVE_TAG | BIGNUM_NEGATIVE_TAG, inner) => {
+                let Some(i) = integer_from_cbor(CborValue::Tag(tag, Box::new(inner))) else {
+                    return Err(ValueSerError::UnrepresentableNumber);
+                };
+                Self::Integer(i)
+            }
+            (IPV4_TAG, CborValue::Bytes(b)) => {
+                let Ok(octets) = <[u8; 4]>::try_from(b) else {
+                    return Err(invalid_data(ValueTy::Ipv4Addr));
+                };
+                Self::Ipv4Addr(Ipv4Addr::from(octets))
+            }
+            (IPV6_TAG, CborValue::Bytes(b)) => {
+                let Ok(octets) = <[u8; 16]>::try_from(b) else {
+                    return Err(invalid_data(ValueTy::Ipv6Addr));
+                };
+                Self::Ipv6Addr(Ipv6Addr::from(octets))
+            }
+            #[cfg(feature = "uuid")]
+            (UUID_TAG, CborValue::Bytes(b)) => {
+                let Ok(bytes) = <[u8; 16]>::try_from(b) else {
+                    return Err(invalid_data(ValueTy::Uuid));
+                };
+                Self::Uuid(uuid::Uuid::from_bytes(bytes))
+            }
+            #[cfg(feature = "regex")]
+            (REGEX_TAG, CborValue::Text(pattern)) => Self::Regex(regex::Regex::new(&pattern)?),
+            //tags that we don't know about just add meaning to the value inside, so skip them
+            (_, inner) => Self::from_cbor_value(inner)?,
+        })
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn from_souris_tag(ty: ValueTy, inner: CborValue) -> Result<Self, ValueSerError> {
+        let invalid = || invalid_data(ty);
+
+        Ok(match (ty, inner) {
+            (ValueTy::Character, CborValue::Text(s)) => {
+                let mut chars = s.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => Self::Character(c),
+                    _ => return Err(invalid()),
+                }
+            }
+            (ValueTy::Imaginary, CborValue::Array(parts)) => {
+                match <[CborValue; 2]>::try_from(parts) {
+                    Ok([CborValue::Float(modulus), CborValue::Float(argument)]) => {
+                        Self::Imaginary(Imaginary::PolarForm { modulus, argument })
+                    }
+                    Ok([real, imaginary]) => {
+                        let (Some(real), Some(imaginary)) =
+                            (integer_from_cbor(real), integer_from_cbor(imaginary))
+                        else {
+                            return Err(invalid());
+                        };
+                        Self::Imaginary(Imaginary::CartesianForm { real, imaginary })
+                    }
+                    Err(_) => return Err(invalid()),
+                }
+            }
+            (ValueTy::Timestamp, CborValue::Text(ts)) => {
+                Self::Timestamp(NaiveDateTime::from_str(&ts).map_err(|_| invalid())?)
+            }
+            (ValueTy::ZonedTimestamp, CborValue::Array(parts)) => {
+                let Ok([CborValue::Text(timestamp), CborValue::Text(tz)]) =
+                    <[CborValue; 2]>::try_from(parts)
+                else {
+                    return Err(invalid());
+                };
+                let (Ok(timestamp), Ok(tz)) =
+                    (DateTime::parse_from_rfc3339(&timestamp), Tz::from_str(&tz))
+                else {
+                    return Err(invalid());
+                };
+                Self::ZonedTimestamp(timestamp.with_timezone(&tz))
+            }
+            (ValueTy::JSON, inner) => Self::JSON(inner.deserialized().map_err(|_| invalid())?),
+            (ValueTy::SingleFloat, CborValue::Float(f)) => Self::SingleFloat(f as f32),
+            (ValueTy::OrderedMap, CborValue::Map(m)) => Self::OrderedMap(map_from_cbor(m)?),
+            (ValueTy::Timezone, CborValue::Text(tz)) => {
+                Self::Timezone(Tz::from_str(&tz).map_err(|_| invalid())?)
+            }
+            (ValueTy::Duration, CborValue::Array(parts)) => {
+                let Ok([seconds, CborValue::Integer(nanoseconds)]) =
+                    <[CborValue; 2]>::try_from(parts)
+                else {
+                    return Err(invalid());
+                };
+                let (Some(seconds), Ok(nanoseconds)) =
+                    (integer_from_cbor(seconds), u32::try_from(nanoseconds))
+                else {
+                    return Err(invalid());
+                };
+                if nanoseconds >= 1_000_000_000 {
+                    return Err(invalid());
+                }
+
+                Self::Duration {
+                    seconds,
+                    nanoseconds,
+                }
+            }
+            (ValueTy::Tagged, CborValue::Array(parts)) => {
+                let Ok([CborValue::Text(tag), value]) = <[CborValue; 2]>::try_from(parts) else {
+                    return Err(invalid());
+                };
+                Self::Tagged {
+                    tag,
+                    value: Box::new(Self::from_cbor_value(value)?),
+                }
+            }
+            (
+                ValueTy::Character
+                | ValueTy::Imaginary
+                | ValueTy::Timestamp
+                | ValueTy::ZonedTimestamp
+                | ValueTy::SingleFloat
+                | ValueTy::OrderedMap
+                | ValueTy::Timezone
+                | ValueTy::Duration
+                | ValueTy::Tagged,
+                _,
+            ) => return Err(invalid()),
+            //the rest are never tagged with our tags, as they can be written natively or with a registered tag
+            (ty, _) => {
+                return Err(ValueSerError::InvalidSourisType {
+                    found: ty,
+                    cause: InvalidSourisTypeError::NoSourisTypeApplicable,
+                })
+            }
+        })
+    }
+}
+
+fn invalid_data(ty: ValueTy) -> ValueSerError {
+    ValueSerError::InvalidSourisType {
+        found: ty,
+        cause: InvalidSourisTypeError::InvalidData,
+    }
+}
+
+///Reads the entries of a CBOR map, which must all have string keys.
+fn map_from_cbor(
+    entries: Vec<(CborValue, CborValue)>,
+) -> Result<Vec<(alloc::string::String, Value)>, ValueSerError> {
+    entries
+        .into_iter()
+        .map(|(k, v)| {
+            let CborValue::Text(k) = k else {
+                return Err(ValueSerError::UnexpectedValueType {
+                    found: Value::from_cbor_value(k)?.as_ty(),
+                    expected: ValueTy::String,
+                });
+            };
+            Ok((k, Value::from_cbor_value(v)?))
+        })
+        .collect()
+}
+
+///Writes an [`Integer`] as a CBOR integer, or a bignum if it doesn't fit into 64 bits.
+fn integer_to_cbor(i: Integer) -> CborValue {
+    if i.is_negative() {
+        let Ok(i) = BiggestIntButSigned::try_from(i) else {
+            unreachable!("negative integers always fit into an i128")
+        };
+        CborValue::from(i)
+    } else {
+        let Ok(i) = BiggestInt::try_from(i) else {
+            unreachable!("positive integers always fit into a u128")
+        };
+        CborValue::from(i)
+    }
+}
+
+fn integer_from_cbor_integer(i: CborInteger) -> Integer {
+    let i = i128::from(i);
+    u128::try_from(i).map_or_else(|_| Integer::from(i), Integer::from)
+}
+
+///Reads an [`Integer`] from a CBOR integer or bignum, returning [`None`] if it isn't an integer or doesn't fit.
+fn integer_from_cbor(cbor: CborValue) -> Option<Integer> {
+    let (is_negative, bytes) = match cbor {
+        CborValue::Integer(i) => return Some(integer_from_cbor_integer(i)),
+        CborValue::Tag(BIGNUM_POSITIVE_TAG, inner) => (false, inner.into_bytes().ok()?),
+        CborValue::Tag(BIGNUM_NEGATIVE_TAG, inner) => (true, inner.into_bytes().ok()?),
+        _ => return None,
+    };
+
+    //bignums can have leading zeroes, so skip them before checking the length
+    let first_non_zero = bytes.iter().position(|b| *b != 0).unwrap_or(bytes.len());
+    let bytes = &bytes[first_non_zero..];
+    if bytes.len() > 16 {
+        return None;
+    }
+    let mut be_bytes = [0; 16];
+    be_bytes[16 - bytes.len()..].copy_from_slice(bytes);
+    let magnitude = u128::from_be_bytes(be_bytes);
+
+    Some(if is_negative {
+        //negative bignums store `-1 - n`
+        Integer::from(-1 - i128::try_from(magnitude).ok()?)
+    } else {
+        Integer::from(magnitude)
+    })
+}
+
+///Converts JSON into the equivalent CBOR.
+fn json_to_cbor(json: &SJValue) -> CborValue {
+    match json {
+        SJValue::Null => CborValue::Null,
+        SJValue::Bool(b) => CborValue::Bool(*b),
+        SJValue::Number(n) => number_to_cbor(n),
+        SJValue::String(s) => CborValue::Text(s.clone()),
+        SJValue::Array(a) => CborValue::Array(a.iter().map(json_to_cbor).collect()),
+        SJValue::Object(o) => CborValue::Map(
+            o.iter()
+                .map(|(k, v)| (CborValue::Text(k.clone()), json_to_cbor(v)))
+                .collect(),
+        ),
+    }
+}
+
+fn number_to_cbor(n: &Number) -> CborValue {
+    if let Some(u) = n.as_u64() {
+        CborValue::Integer(u.into())
+    } else if let Some(i) = n.as_i64() {
+        CborValue::Integer(i.into())
+    } else {
+        //JSON numbers are always finite, so this only fails with `serde_json/arbitrary_precision`, where the best we can do is a float anyway
+        CborValue::Float(n.as_f64().unwrap_or(f64::NAN))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{boxed::Box, string::ToString, vec, vec::Vec};
+
+    use chrono::{NaiveDateTime, TimeZone};
+    use chrono_tz::Tz;
+    use ciborium::value::Value as CborValue;
+
+    use super::{UnknownValue, Value, ValueSerError, ValueTy};
+    use crate::types::{binary::BinaryData, imaginary::Imaginary, integer::Integer};
+
+    fn cbor_bytes(cbor: &CborValue) -> Vec<u8> {
+        let mut bytes = vec![];
+        ciborium::ser::into_writer(cbor, &mut bytes).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let values = [
+            Value::Null(()),
+            Value::Boolean(true),
+            Value::Character('🖖'),
+            Value::String("hello world".into()),
+            Value::Binary(BinaryData(vec![0xAB; 100])),
+            Value::Integer(Integer::u8(0)),
+            Value::Integer(Integer::i64(-5)),
+            Value::Integer(Integer::u128(u128::MAX)),
+            Value::Integer(Integer::i128(i128::MIN)),
+            Value::Imaginary(Imaginary::CartesianForm {
+                real: Integer::u8(3),
+                imaginary: Integer::i8(-4),
+            }),
+            Value::Imaginary(Imaginary::PolarForm {
+                modulus: 1.0,
+                argument: 2.5,
+            }),
+            Value::Timestamp(NaiveDateTime::default()),
+            Value::ZonedTimestamp(
+                Tz::America__New_York
+                    .with_ymd_and_hms(2024, 3, 10, 1, 30, 0)
+                    .unwrap(),
+            ),
+            Value::JSON(serde_json::json!({"a": [1, -2, 3.5, null, "b"]})),
+            Value::Timezone(Tz::Europe__London),
+            Value::Ipv4Addr(core::net::Ipv4Addr::LOCALHOST),
+            Value::Ipv6Addr(core::net::Ipv6Addr::LOCALHOST),
+            Value::SingleFloat(1.5),
+            Value::DoubleFloat(1.5),
+            Value::from(chrono::TimeDelta::milliseconds(-1500)),
+            Value::Array(vec![Value::Null(()), Value::from(1_u8)]),
+            Value::Map(
+                [
+                    ("key".to_string(), Value::Boolean(false)),
+                    ("other".to_string(), Value::Character('a')),
+                ]
+                .into_iter()
+                .collect(),
+            ),
+            Value::OrderedMap(vec![
+                ("z".to_string(), Value::Boolean(false)),
+                ("a".to_string(), Value::Null(())),
+            ]),
+            Value::Tagged {
+                tag: "Some".to_string(),
+                value: Box::new(Value::from(1_u8)),
+            },
+            Value::Unknown(UnknownValue::new(30, vec![1, 2, 3]).unwrap()),
+        ];
+
+        for value in values {
+            let cbor = value.to_cbor();
+            assert_eq!(Value::from_cbor(&cbor).unwrap(), value);
+        }
+    }
+
+    #[test]
+    fn test_foreign_cbor() {
+        //an unknown tag (1 is an epoch timestamp) just gives back the value inside
+        let bytes = cbor_bytes(&CborValue::Tag(1, Box::new(CborValue::Integer(5.into()))));
+        assert_eq!(Value::from_cbor(&bytes).unwrap(), Value::from(5_u8));
+
+        let bytes = cbor_bytes(&CborValue::Map(vec![(
+            CborValue::Integer(1.into()),
+            CborValue::Null,
+        )]));
+        assert!(matches!(
+            Value::from_cbor(&bytes),
+            Err(ValueSerError::UnexpectedValueType {
+                found: ValueTy::Integer,
+                expected: ValueTy::String
+            })
+        ));
+
+        //a character tag holding more than one character
+        let bytes = cbor_bytes(&CborValue::Tag(
+            Value::CBOR_TAG_BASE + u64::from(u8::from(ValueTy::Character)),
+            Box::new(CborValue::Text("ab".into())),
+        ));
+        assert!(matches!(
+            Value::from_cbor(&bytes),
+            Err(ValueSerError::InvalidSourisType {
+                found: ValueTy::Character,
+                ..
+            })
+        ));
+
+        assert!(matches!(
+            Value::from_cbor(&Value::from(u64::MAX).to_cbor()[..3]),
+            Err(ValueSerError::NotEnoughBytes)
+        ));
+
+        //a discriminant which could never be an extended type isn't kept as an unknown value
+        let bytes = cbor_bytes(&CborValue::Tag(
+            Value::CBOR_TAG_BASE + 200,
+            Box::new(CborValue::Bytes(vec![1, 2, 3])),
+        ));
+        assert!(matches!(
+            Value::from_cbor(&bytes),
+            Err(ValueSerError::InvalidType(200))
+        ));
+    }
+
+    #[test]
+    fn test_max_depth() {
+        let mut bytes = vec![0x81; 10_000]; //an array with one element
+        bytes.push(0xF6); //null
+
+        assert!(matches!(
+            Value::from_cbor(&bytes),
+            Err(ValueSerError::MaxDepthExceeded)
+        ));
+    }
+}